@@ -1,11 +1,21 @@
 mod object;
 
-use colorsys::{Hsl, Rgb};
-use video_generator_lib::{node::*, shapes::*, signal::*};
+use video_generator_lib::{
+    color::Color,
+    config::{MotionBlur, RenderConfig},
+    node::*,
+    renderer::{
+        available_adapters, AdapterOptions, AdapterSummary, CpuRenderer, GpuError, GpuRenderer,
+        Renderer, ShaderSources,
+    },
+    shapes::*,
+    signal::*,
+};
 
-fn generate_frames(save_frame: &mut dyn FnMut(Vec<Shape>)) {
+fn generate_frames(config: &RenderConfig, save_frame: &mut dyn FnMut(Vec<Shape>)) {
     let inverse_lerp = |x, min, max| (x - min) / (max - min);
-    let centre = Signal::new((720.0 / 2.0, 720.0 / 2.0));
+    let (width, height) = (config.width as f32, config.height as f32);
+    let centre = Signal::new((width / 2.0, height / 2.0));
     let velocity = Signal::new((3.0, 0.0));
     let radius = 50.0f32;
 
@@ -15,45 +25,55 @@ fn generate_frames(save_frame: &mut dyn FnMut(Vec<Shape>)) {
         || radius,
         || {
             centre.map(|c| {
-                let hue = inverse_lerp(c.0, 0.0, 720.0) * 360.0;
-                let saturation = 100.0;
-                let luminance = inverse_lerp(c.1, 350.0, 720.0) * 50.0;
-                let colour = Hsl::new(hue as f64, saturation as f64, luminance as f64, Some(1.0));
-                let [red, green, blue]: [u8; 3] = Rgb::from(colour).into();
-                0xFF000000 + ((red as u32) << 16) + ((green as u32) << 8) + blue as u32
+                let hue = inverse_lerp(c.0, 0.0, width) * 360.0;
+                let lightness = inverse_lerp(c.1, height / 2.0 - 10.0, height) * 0.5;
+                Color::hsl(hue, 1.0, lightness)
             })
         },
     );
-    for _ in 0..600 {
-        let (new_centre, _, new_velocity) = physics_update(centre.get(), radius, velocity.get());
+    let mut previous_time = -1.0;
+    for time in config.frame_times(config.fps as usize * 10) {
+        let (new_centre, _, new_velocity) = physics_update(
+            (width, height),
+            centre.get(),
+            radius,
+            velocity.get(),
+            time - previous_time,
+        );
+        previous_time = time;
         centre.update(|c| *c = new_centre);
         velocity.update(|c| *c = new_velocity);
 
         save_frame(vec![
-            RectangleData::new_shape((0.0, 0.0), (720.0, 720.0), 0),
             circle.to_shape(),
-            RectangleData::new_shape((720.0 / 2.0, 720.0 / 2.0), (100.0, 200.0), 0xFFFF0000),
+            RectangleData::new_shape(
+                (width / 2.0, height / 2.0),
+                (100.0, 200.0),
+                Color::rgb(255, 0, 0),
+            ),
         ]);
     }
 }
 
 fn physics_update<'a>(
+    (width, height): (f32, f32),
     mut centre: (f32, f32),
     radius: f32,
     mut velocity: (f32, f32),
+    dt: f32,
 ) -> ((f32, f32), f32, (f32, f32)) {
-    velocity.1 += 0.2;
+    velocity.1 += 0.2 * dt;
 
-    centre.0 += velocity.0;
-    centre.1 += velocity.1;
+    centre.0 += velocity.0 * dt;
+    centre.1 += velocity.1 * dt;
 
-    if centre.1 + radius >= 720.0 {
-        centre.1 = 720.0 - radius;
+    if centre.1 + radius >= height {
+        centre.1 = height - radius;
         velocity.1 = -velocity.1.abs();
     }
 
-    if centre.0 + radius >= 720.0 {
-        centre.0 = 720.0 - radius;
+    if centre.0 + radius >= width {
+        centre.0 = width - radius;
         velocity.0 = -velocity.0.abs();
     }
 
@@ -67,24 +87,96 @@ fn physics_update<'a>(
 
 pub fn main() {
     let args: Vec<_> = std::env::args().skip(1).collect();
+    let use_cpu = args.iter().skip(2).any(|x| x == "--cpu");
+    let motion_blur = args.iter().skip(2).any(|x| x == "--motion-blur");
+    // Draws shapes through the raster pipelines, to compare with the kernels.
+    let rasterized = args.iter().skip(2).any(|x| x == "--raster");
+    let value_of = |flag: &str| {
+        args.iter().position(|x| x == flag).map(|i| {
+            args.get(i + 1)
+                .unwrap_or_else(|| panic!("{flag} needs a value"))
+        })
+    };
+    // Shaders edited in this directory are picked up without rebuilding.
+    let shader_directory = value_of("--shaders");
+    let mut adapter_options = AdapterOptions::from_env()
+        .with_fallback_adapter(args.iter().any(|x| x == "--fallback-adapter"));
+    if let Some(backends) = value_of("--backend") {
+        adapter_options =
+            adapter_options.with_backends(wgpu::util::parse_backends_from_comma_list(backends));
+    }
+    if let Some(name) = value_of("--adapter") {
+        adapter_options = adapter_options.with_adapter_name(Some(name.clone()));
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if args.iter().any(|x| x == "--list-adapters") {
+        for info in available_adapters(adapter_options.backends) {
+            println!("{}", AdapterSummary(&info));
+        }
+        return;
+    }
     let (start_frame, end_frame): (usize, usize) =
         (args[0].parse().unwrap(), args[1].parse().unwrap());
+    let config =
+        RenderConfig::default().with_motion_blur(motion_blur.then(|| MotionBlur::new(8, 180.0)));
     #[cfg(not(target_arch = "wasm32"))]
     {
-        pollster::block_on(video_generator_lib::run(
+        let mut renderer: Box<dyn Renderer> = if use_cpu {
+            Box::new(CpuRenderer::new(config.width, config.height))
+        } else {
+            let renderer = shader_directory
+                .map_or_else(|| Ok(ShaderSources::built_in()), ShaderSources::load)
+                .map_err(GpuError::from)
+                .and_then(|sources| {
+                    pollster::block_on(GpuRenderer::with_options(
+                        config.width,
+                        config.height,
+                        &sources,
+                        &adapter_options,
+                    ))
+                });
+            match renderer {
+                Ok(mut renderer) => {
+                    renderer.set_rasterized(rasterized);
+                    // Broken shaders don't stop the render: changed ones are kept as they were,
+                    // and custom ones left out.
+                    renderer.set_shader_error_handler(|error| eprintln!("Shader error: {error}"));
+                    if let Some(directory) = shader_directory {
+                        renderer.watch_shaders(directory);
+                    }
+                    Box::new(renderer)
+                }
+                Err(error) => {
+                    eprintln!("{error}");
+                    std::process::exit(1);
+                }
+            }
+        };
+        video_generator_lib::run(
+            &config,
+            renderer.as_mut(),
             generate_frames,
             start_frame,
             end_frame,
-        ));
+        );
     }
     #[cfg(target_arch = "wasm32")]
     {
         std::panic::set_hook(Box::new(console_error_panic_hook::hook));
         console_log::init().expect("could not initialize logger");
-        wasm_bindgen_futures::spawn_local(video_generator_lib::run(
-            generate_frames,
-            start_frame,
-            end_frame,
-        ));
+        wasm_bindgen_futures::spawn_local(async move {
+            let mut renderer: Box<dyn Renderer> = if use_cpu {
+                Box::new(CpuRenderer::new(config.width, config.height))
+            } else {
+                Box::new(GpuRenderer::new(config.width, config.height).await)
+            };
+            video_generator_lib::run(
+                &config,
+                renderer.as_mut(),
+                generate_frames,
+                start_frame,
+                end_frame,
+            );
+        });
     }
 }
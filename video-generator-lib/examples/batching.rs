@@ -0,0 +1,75 @@
+//! Compares drawing 10,000 shapes per frame with one dispatch per shape against one tiled dispatch.
+//!
+//! `cargo run --release --example batching [shape count] [frame count]`
+
+use std::time::Instant;
+
+use video_generator_lib::{
+    color::Color,
+    renderer::{GpuRenderer, Renderer},
+    shapes::*,
+};
+
+const SIZE: u32 = 720;
+
+/// A xorshift generator, so every run draws the same particles.
+fn random(state: &mut u32) -> f32 {
+    *state ^= *state << 13;
+    *state ^= *state >> 17;
+    *state ^= *state << 5;
+    *state as f32 / u32::MAX as f32
+}
+
+fn particles(count: usize, seed: u32) -> Vec<Shape> {
+    let mut state = seed;
+    (0..count)
+        .map(|i| {
+            let position = (
+                random(&mut state) * SIZE as f32,
+                random(&mut state) * SIZE as f32,
+            );
+            let rgb = (random(&mut state) * 0xFFFFFF as f32) as u32;
+            let colour = Color::hex_alpha((rgb << 8) | 0x80);
+            if i % 4 == 0 {
+                RectangleData::new_shape(position, (8.0, 8.0), colour)
+            } else {
+                CircleData::new_shape(position, 2.0 + random(&mut state) * 6.0, colour)
+            }
+        })
+        .collect()
+}
+
+fn main() {
+    let args: Vec<usize> = std::env::args()
+        .skip(1)
+        .map(|x| x.parse().unwrap())
+        .collect();
+    let (count, frame_count) = (
+        args.first().copied().unwrap_or(10_000),
+        args.get(1).copied().unwrap_or(10),
+    );
+    let frames: Vec<_> = (0..frame_count)
+        .map(|i| particles(count, i as u32 + 1))
+        .collect();
+
+    let mut renderer = pollster::block_on(GpuRenderer::new(SIZE, SIZE));
+    let mut outputs = vec![];
+    for batched in [false, true] {
+        renderer.set_batched(batched);
+        // The first frame also pays for pipeline compilation, so it isn't timed.
+        renderer.render_frame(&frames[0]);
+
+        let start = Instant::now();
+        let output: Vec<_> = frames.iter().map(|x| renderer.render_frame(x)).collect();
+        let duration = start.elapsed().as_secs_f64() * 1000.0 / frame_count as f64;
+        println!(
+            "{mode}: {duration:.1}ms per frame of {count} shapes",
+            mode = if batched { "batched" } else { "per shape" },
+        );
+        outputs.push(output);
+    }
+    assert!(
+        outputs[0] == outputs[1],
+        "batched and per-shape frames differ!"
+    );
+}
@@ -0,0 +1,197 @@
+use std::fmt;
+
+/// A colour with straight alpha, its channels sRGB encoded from 0.0 to 1.0 as screens and
+/// image files expect. Renderers blend in linear light, see [`Color::to_linear`].
+///
+/// Frames and the kernels' buffers hold colours packed as [`Color::to_packed`] describes.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Color {
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+    pub alpha: f32,
+}
+
+impl Color {
+    pub const TRANSPARENT: Self = Self::new(0.0, 0.0, 0.0, 0.0);
+    pub const BLACK: Self = Self::rgb(0, 0, 0);
+    pub const WHITE: Self = Self::rgb(255, 255, 255);
+
+    pub const fn new(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
+        Self {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+
+    pub const fn rgb(red: u8, green: u8, blue: u8) -> Self {
+        Self::rgba(red, green, blue, 255)
+    }
+
+    pub const fn rgba(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
+        Self::new(
+            red as f32 / 255.0,
+            green as f32 / 255.0,
+            blue as f32 / 255.0,
+            alpha as f32 / 255.0,
+        )
+    }
+
+    /// An opaque colour written as `0xRRGGBB`, the way CSS writes them.
+    pub const fn hex(rgb: u32) -> Self {
+        Self::hex_alpha((rgb << 8) | 0xFF)
+    }
+
+    /// A colour written as `0xRRGGBBAA`.
+    pub const fn hex_alpha(rgba: u32) -> Self {
+        let [red, green, blue, alpha] = rgba.to_be_bytes();
+        Self::rgba(red, green, blue, alpha)
+    }
+
+    /// Parses CSS hex colours: `#RGB`, `#RGBA`, `#RRGGBB` or `#RRGGBBAA`, with or without the `#`.
+    pub fn parse_hex(text: &str) -> Result<Self, ParseColorError> {
+        let digits = text.strip_prefix('#').unwrap_or(text);
+        if !digits.chars().all(|x| x.is_ascii_hexdigit()) {
+            return Err(ParseColorError(text.to_string()));
+        }
+        let value = u32::from_str_radix(digits, 16).map_err(|_| ParseColorError(text.into()))?;
+        // Short forms repeat each digit, so #F80 is #FF8800.
+        let widen = |x: u32| {
+            (0..4)
+                .rev()
+                .fold(0, |v, i| (v << 8) | (((x >> (i * 4)) & 0xF) * 0x11))
+        };
+        match digits.len() {
+            3 => Ok(Self::hex_alpha(widen((value << 4) | 0xF))),
+            4 => Ok(Self::hex_alpha(widen(value))),
+            6 => Ok(Self::hex(value)),
+            8 => Ok(Self::hex_alpha(value)),
+            _ => Err(ParseColorError(text.to_string())),
+        }
+    }
+
+    /// `hue` in degrees, with `saturation` and `lightness` from 0.0 to 1.0.
+    pub fn hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+        let (saturation, lightness) = (saturation.clamp(0.0, 1.0), lightness.clamp(0.0, 1.0));
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        Self::from_hue(hue, chroma, lightness - chroma / 2.0)
+    }
+
+    /// `hue` in degrees, with `saturation` and `value` from 0.0 to 1.0.
+    pub fn hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let (saturation, value) = (saturation.clamp(0.0, 1.0), value.clamp(0.0, 1.0));
+        let chroma = value * saturation;
+        Self::from_hue(hue, chroma, value - chroma)
+    }
+
+    /// The colour with `hue` at `chroma`, lifted by `lightest` on every channel.
+    fn from_hue(hue: f32, chroma: f32, lightest: f32) -> Self {
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (red, green, blue) = match sector as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        Self::new(red + lightest, green + lightest, blue + lightest, 1.0)
+    }
+
+    /// A colour in OKLCH, whose `lightness` from 0.0 to 1.0 and `chroma`, up to about 0.4,
+    /// look evenly spaced to the eye at any `hue` in degrees. Colours outside sRGB are clamped
+    /// when drawn.
+    pub fn oklch(lightness: f32, chroma: f32, hue: f32) -> Self {
+        let (a, b) = (
+            chroma * hue.to_radians().cos(),
+            chroma * hue.to_radians().sin(),
+        );
+        let l = (lightness + 0.396_337_78 * a + 0.215_803_76 * b).powi(3);
+        let m = (lightness - 0.105_561_346 * a - 0.063_854_17 * b).powi(3);
+        let s = (lightness - 0.089_484_18 * a - 1.291_485_5 * b).powi(3);
+        Self::from_linear([
+            4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+            -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+            -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+            1.0,
+        ])
+    }
+
+    pub fn with_alpha(self, alpha: f32) -> Self {
+        Self { alpha, ..self }
+    }
+
+    /// The channels in linear light, where they add up as light does, and alpha unchanged.
+    pub fn to_linear(self) -> [f32; 4] {
+        [
+            srgb_to_linear(self.red),
+            srgb_to_linear(self.green),
+            srgb_to_linear(self.blue),
+            self.alpha,
+        ]
+    }
+
+    pub fn from_linear([red, green, blue, alpha]: [f32; 4]) -> Self {
+        Self::new(
+            linear_to_srgb(red),
+            linear_to_srgb(green),
+            linear_to_srgb(blue),
+            alpha,
+        )
+    }
+
+    /// The colour as renderers store it: the channels clamped, rounded to bytes and packed
+    /// little-endian in the order red, green, blue, alpha. Written out, that's `0xAABBGGRR`,
+    /// and the bytes of a frame of them are RGBA pixels.
+    pub fn to_packed(self) -> u32 {
+        u32::from_le_bytes(
+            [self.red, self.green, self.blue, self.alpha]
+                .map(|x| (x.clamp(0.0, 1.0) * 255.0 + 0.5).floor() as u8),
+        )
+    }
+
+    pub fn from_packed(colour: u32) -> Self {
+        let [red, green, blue, alpha] = colour.to_le_bytes();
+        Self::rgba(red, green, blue, alpha)
+    }
+}
+
+/// Colours written as `0xAARRGGBB`, which is how colours were given before there was a `Color`,
+/// so `0xFFFF0000` is red. It's [`Color::hex_alpha`] with the alpha moved to the front, not the
+/// order renderers store colours in, which [`Color::from_packed`] reads.
+impl From<u32> for Color {
+    fn from(argb: u32) -> Self {
+        let [alpha, red, green, blue] = argb.to_be_bytes();
+        Self::rgba(red, green, blue, alpha)
+    }
+}
+
+/// A string which isn't a hex colour.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseColorError(String);
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} isn't a hex colour", self.0)
+    }
+}
+impl std::error::Error for ParseColorError {}
+
+/// The sRGB transfer function, matching `srgb_to_linear` in shader-common.wgsl.
+fn srgb_to_linear(x: f32) -> f32 {
+    if x <= 0.04045 {
+        x / 12.92
+    } else {
+        ((x + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(x: f32) -> f32 {
+    if x <= 0.0031308 {
+        x * 12.92
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    }
+}
@@ -0,0 +1,124 @@
+use std::path::{Path, PathBuf};
+
+/// The image format frames are saved in before ffmpeg stitches them into a video.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FrameFormat {
+    /// Uncompressed, so the fastest to write.
+    #[default]
+    Bmp,
+    /// Compressed, for long renders that would otherwise fill the disk.
+    Png,
+}
+impl FrameFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            FrameFormat::Bmp => "bmp",
+            FrameFormat::Png => "png",
+        }
+    }
+}
+
+/// Renders each frame as the average of several sub-frames spread over part of its interval,
+/// so fast movement blurs as it would on film instead of strobing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MotionBlur {
+    pub samples: u32,
+    /// How long the shutter is open each frame, in degrees of the 360 between one frame and the next.
+    /// Film usually uses 180.
+    pub shutter_angle: f32,
+}
+impl MotionBlur {
+    pub fn new(samples: u32, shutter_angle: f32) -> Self {
+        Self {
+            samples,
+            shutter_angle,
+        }
+    }
+}
+
+/// Everything about a render that isn't the scene itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderConfig {
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+    /// Where the finished video is written.
+    /// Intermediate frames are saved next to it, and deleted once the video is made.
+    pub output_path: PathBuf,
+    pub frame_format: FrameFormat,
+    pub motion_blur: Option<MotionBlur>,
+}
+impl RenderConfig {
+    pub fn new(width: u32, height: u32, fps: u32, output_path: impl AsRef<Path>) -> Self {
+        Self {
+            width,
+            height,
+            fps,
+            output_path: output_path.as_ref().to_path_buf(),
+            frame_format: FrameFormat::default(),
+            motion_blur: None,
+        }
+    }
+
+    pub fn with_frame_format(self, frame_format: FrameFormat) -> Self {
+        Self {
+            frame_format,
+            ..self
+        }
+    }
+
+    pub fn with_motion_blur(self, motion_blur: Option<MotionBlur>) -> Self {
+        Self {
+            motion_blur,
+            ..self
+        }
+    }
+
+    /// How many sub-frames make up each frame, one without motion blur.
+    pub fn sub_frames(&self) -> usize {
+        self.motion_blur.map_or(1, |x| x.samples.max(1) as usize)
+    }
+
+    /// The times, in frames, at which the scene is sampled for frame `index`.
+    /// The first is always the frame's own time, and the rest follow while the shutter is open.
+    pub fn sub_frame_times(&self, index: usize) -> impl Iterator<Item = f32> {
+        let count = self.sub_frames();
+        let open = self
+            .motion_blur
+            .map_or(0.0, |x| x.shutter_angle.clamp(0.0, 360.0) / 360.0);
+        (0..count).map(move |i| index as f32 + open * i as f32 / count as f32)
+    }
+
+    /// The times of every sub-frame of the first `frames` frames, in order.
+    /// Scenes save a sub-frame at each of them, so motion blur can average them.
+    pub fn frame_times(&self, frames: usize) -> impl Iterator<Item = f32> + '_ {
+        (0..frames).flat_map(|i| self.sub_frame_times(i))
+    }
+
+    /// The path of intermediate frame `index`, such as `output/output-12.bmp`.
+    pub fn frame_path(&self, index: usize) -> PathBuf {
+        self.frame_path_with(&index.to_string())
+    }
+
+    /// The frame paths in the printf-style pattern ffmpeg expects, such as `output/output-%d.bmp`.
+    pub fn frame_path_pattern(&self) -> PathBuf {
+        self.frame_path_with("%d")
+    }
+
+    fn frame_path_with(&self, index: &str) -> PathBuf {
+        let stem = self
+            .output_path
+            .file_stem()
+            .map(|x| x.to_string_lossy())
+            .unwrap_or_default();
+        self.output_path.with_file_name(format!(
+            "{stem}-{index}.{extension}",
+            extension = self.frame_format.extension()
+        ))
+    }
+}
+impl Default for RenderConfig {
+    fn default() -> Self {
+        Self::new(720, 720, 60, "output/output.mp4")
+    }
+}
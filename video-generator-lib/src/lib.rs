@@ -1,28 +1,36 @@
+pub mod color;
+pub mod config;
 pub mod node;
+pub mod renderer;
 pub mod shapes;
 pub mod signal;
 
+use config::RenderConfig;
 use image::RgbaImage;
-use node::Circle;
+use renderer::Renderer;
 use shapes::*;
 use signal::*;
-use std::{path::Path, time::Instant};
-use wgpu::Buffer;
-
-pub async fn run(
-    generate_frames: impl Fn(&mut dyn FnMut(Vec<Shape>)),
+use std::{path::PathBuf, time::Instant};
+
+/// Renders frames `start_frame..end_frame` of a scene with `renderer` and exports them as a video.
+/// `renderer` must draw at the size given in `config`, and the scene save
+/// [`RenderConfig::sub_frames`] sub-frames per frame, at the times of [`RenderConfig::frame_times`].
+pub fn run(
+    config: &RenderConfig,
+    renderer: &mut dyn Renderer,
+    generate_frames: impl Fn(&RenderConfig, &mut dyn FnMut(Vec<Shape>)),
     start_frame: usize,
     end_frame: usize,
 ) {
-    let gpu_instance = GpuInstance::new(
-        720,
-        720,
-        include_str!("shader.wgsl"),
-        include_str!("shader-rect.wgsl"),
-    )
-    .await;
-
-    let format_name = |i| format!("output/test-{i}.bmp");
+    assert_eq!(
+        (renderer.width(), renderer.height()),
+        (config.width, config.height),
+        "The renderer's size doesn't match the config!"
+    );
+    if let Some(directory) = config.output_path.parent() {
+        std::fs::create_dir_all(directory).expect("Failed to create the output directory!");
+    }
+    let format_name = |i| config.frame_path(i);
 
     // let clamp = |x: f32, min, max| x.min(max).max(min);
     // let clamp01 = |x| clamp(x, 0.0, 1.0);
@@ -30,28 +38,13 @@ pub async fn run(
 
     println!("Starting...");
     let start = Instant::now();
-    let mut frames = Vec::with_capacity(120);
-    let mut save_frame = |frame: Vec<Shape>| frames.push(frame);
-
-    generate_frames(&mut save_frame);
-
-    let start_frame = start_frame.min(frames.len() - 1);
-    let end_frame = end_frame.min(frames.len() - 1).max(start_frame);
-    let count = end_frame - start_frame;
+    let frames = collect_frames(config, generate_frames, start_frame, end_frame);
+    let count = frames.len();
     let generate_frames_end = Instant::now();
-    render_and_save_frames(
-        &gpu_instance,
-        frames
-            .into_iter()
-            .skip(start_frame)
-            .take(end_frame - start_frame),
-        0,
-        format_name,
-    )
-    .await;
+    render_and_save_frames(renderer, frames.into_iter(), 0, format_name);
     let frames_end = Instant::now();
     println!("Saved frames. Exporting video...");
-    export_to_video();
+    export_to_video(config);
     delete_saved_videos(0, count, format_name);
 
     let end = Instant::now();
@@ -65,178 +58,95 @@ pub async fn run(
     );
 }
 
-fn delete_saved_videos(start_index: usize, count: usize, format_name: impl Fn(usize) -> String) {
+/// Renders frames `start_frame..end_frame` of a scene in memory, without touching the disk.
+pub fn render_scene(
+    config: &RenderConfig,
+    renderer: &mut dyn Renderer,
+    generate_frames: impl Fn(&RenderConfig, &mut dyn FnMut(Vec<Shape>)),
+    start_frame: usize,
+    end_frame: usize,
+) -> Vec<RgbaImage> {
+    collect_frames(config, generate_frames, start_frame, end_frame)
+        .iter()
+        .map(|sub_frames| render_image(renderer, sub_frames))
+        .collect()
+}
+
+fn collect_frames(
+    config: &RenderConfig,
+    generate_frames: impl Fn(&RenderConfig, &mut dyn FnMut(Vec<Shape>)),
+    start_frame: usize,
+    end_frame: usize,
+) -> Vec<Vec<Vec<Shape>>> {
+    let mut sub_frames = Vec::with_capacity(120 * config.sub_frames());
+    let mut save_frame = |frame: Vec<Shape>| sub_frames.push(frame);
+
+    generate_frames(config, &mut save_frame);
+
+    let mut frames = vec![];
+    let mut sub_frames = sub_frames.into_iter().peekable();
+    while sub_frames.peek().is_some() {
+        frames.push(
+            sub_frames
+                .by_ref()
+                .take(config.sub_frames())
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    let Some(last_frame) = frames.len().checked_sub(1) else {
+        return frames;
+    };
+    let start_frame = start_frame.min(last_frame);
+    let end_frame = end_frame.min(last_frame).max(start_frame);
+    frames
+        .into_iter()
+        .skip(start_frame)
+        .take(end_frame - start_frame)
+        .collect()
+}
+
+fn delete_saved_videos(start_index: usize, count: usize, format_name: impl Fn(usize) -> PathBuf) {
     for name in (start_index..count + start_index).map(format_name) {
         std::fs::remove_file(&name)
-            .expect(format!("Failed to delete file {name}", name = &name).as_str());
+            .unwrap_or_else(|_| panic!("Failed to delete file {name}", name = name.display()));
     }
 }
 
-fn export_to_video() {
+fn export_to_video(config: &RenderConfig) {
+    let fps = config.fps.to_string();
     std::process::Command::new("cmd")
-        .args([
-            "/C",
-            "ffmpeg",
-            "-framerate",
-            "60",
-            "-i",
-            "output/test-%d.bmp",
-            "-c:v",
-            "libx264",
-            "-pix_fmt",
-            "yuv420p",
-            "-r",
-            "60",
-            "output/output.mp4",
-            "-y",
-        ])
+        .args(["/C", "ffmpeg", "-framerate"])
+        .arg(&fps)
+        .arg("-i")
+        .arg(config.frame_path_pattern())
+        .args(["-c:v", "libx264", "-pix_fmt", "yuv420p", "-r"])
+        .arg(&fps)
+        .arg(&config.output_path)
+        .arg("-y")
         .stderr(std::process::Stdio::inherit())
         .output()
         .expect("Failed to execute!");
 }
 
-async fn render_and_save_frames(
-    gpu_instance: &GpuInstance,
-    frames: impl Iterator<Item = Vec<Shape>>,
+fn render_and_save_frames(
+    renderer: &mut dyn Renderer,
+    mut frames: impl Iterator<Item = Vec<Vec<Shape>>>,
     start_index: usize,
-    format_name: impl Fn(usize) -> String,
+    format_name: impl Fn(usize) -> PathBuf + Sync,
 ) {
-    let size = (std::mem::size_of::<u8>() as u64
-        * gpu_instance.width as u64
-        * gpu_instance.height as u64
-        * 4) as wgpu::BufferAddress;
-
-    let staging_buffer = gpu_instance.device.create_buffer(&wgpu::BufferDescriptor {
-        label: None,
-        size,
-        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
-        mapped_at_creation: false,
-    });
-
-    let output_buffer = gpu_instance.device.create_buffer(&wgpu::BufferDescriptor {
-        label: Some("Output Buffer"),
-        size,
-        usage: wgpu::BufferUsages::STORAGE
-            | wgpu::BufferUsages::COPY_DST
-            | wgpu::BufferUsages::COPY_SRC,
-        mapped_at_creation: false,
+    let (width, height) = (renderer.width(), renderer.height());
+    renderer.render_frames(&mut frames, &|i, pixel_data| {
+        RgbaImage::from_raw(width, height, pixel_data)
+            .expect("Failed to create image!")
+            .save(format_name(i + start_index))
+            .expect("Failed to save image!");
     });
-
-    for (i, frame) in frames.enumerate().map(|(i, x)| (i + start_index, x)) {
-        render_and_save_frame(
-            gpu_instance,
-            frame,
-            format_name(i).as_str(),
-            &staging_buffer,
-            &output_buffer,
-        )
-        .await;
-    }
-}
-
-async fn render_and_save_frame(
-    gpu_instance: &GpuInstance,
-    shapes: Vec<Shape>,
-    name: &str,
-    staging_buffer: &Buffer,
-    output_buffer: &Buffer,
-) {
-    let pixel_data = render_frame(&gpu_instance, shapes, staging_buffer, output_buffer)
-        .await
-        .unwrap();
-
-    let image = RgbaImage::from_raw(gpu_instance.width, gpu_instance.height, pixel_data)
-        .expect("Failed to create image!");
-
-    image.save(name).expect("Failed to save image!");
 }
 
-async fn render_frame(
-    gpu_instance: &GpuInstance,
-    shapes: Vec<Shape>,
-    staging_buffer: &Buffer,
-    output_buffer: &Buffer,
-) -> Option<Vec<u8>> {
-    let (width, height, device, circle_compute_pipeline, rect_compute_pipeline) = (
-        gpu_instance.width,
-        gpu_instance.height,
-        &gpu_instance.device,
-        &gpu_instance.circle_compute_pipeline,
-        &gpu_instance.rect_compute_pipeline,
-    );
-
-    let circle_bind_group_layout = circle_compute_pipeline.get_bind_group_layout(0);
-    let rect_bind_group_layout = rect_compute_pipeline.get_bind_group_layout(0);
-    let shape_bind_groups: Vec<_> = shapes
-        .iter()
-        .map(|c| {
-            device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: None,
-                layout: match c {
-                    Shape::Circle(_) => &circle_bind_group_layout,
-                    Shape::Rectangle(_) => &rect_bind_group_layout,
-                },
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: output_buffer.as_entire_binding(),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: c.create_buffer(&device, width, height).as_entire_binding(),
-                    },
-                ],
-            })
-        })
-        .collect();
-
-    let mut encoder =
-        device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-    {
-        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
-            label: None,
-            timestamp_writes: None,
-        });
-
-        let mut draw_shape =
-            |compute_pipeline, bounding_box_width, bounding_box_height, bind_group| {
-                cpass.set_pipeline(compute_pipeline);
-                cpass.set_bind_group(0, bind_group, &[]);
-                cpass.dispatch_workgroups(bounding_box_width, bounding_box_height, 1);
-            };
-
-        for (i, bind_group) in shape_bind_groups.iter().enumerate() {
-            let (_, _, width, height) = shapes[i].bounding_box();
-            draw_shape(
-                match &shapes[i] {
-                    Shape::Circle(_) => &circle_compute_pipeline,
-                    Shape::Rectangle(_) => &rect_compute_pipeline,
-                },
-                width,
-                height,
-                bind_group,
-            );
-        }
-    }
-    encoder.copy_buffer_to_buffer(&output_buffer, 0, &staging_buffer, 0, staging_buffer.size());
-
-    gpu_instance.queue.submit(Some(encoder.finish()));
+fn render_image(renderer: &mut dyn Renderer, sub_frames: &[Vec<Shape>]) -> RgbaImage {
+    let pixel_data = renderer.render_sub_frames(sub_frames);
 
-    let buffer_slice = staging_buffer.slice(..);
-    let (sender, receiver) = flume::bounded(1);
-    buffer_slice.map_async(wgpu::MapMode::Read, move |v| sender.send(v).unwrap());
-
-    device.poll(wgpu::Maintain::wait()).panic_on_timeout();
-
-    if let Ok(Ok(())) = receiver.recv_async().await {
-        let data = buffer_slice.get_mapped_range();
-        let result = bytemuck::cast_slice(&data).to_vec();
-
-        drop(data);
-        staging_buffer.unmap();
-
-        Some(result)
-    } else {
-        panic!("failed to run compute on gpu!")
-    }
+    RgbaImage::from_raw(renderer.width(), renderer.height(), pixel_data)
+        .expect("Failed to create image!")
 }
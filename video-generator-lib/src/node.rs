@@ -1,23 +1,119 @@
 use std::rc::Rc;
 
-use crate::{DerivedSignal, RectangleData, Shape, SignalRead};
+use crate::{
+    color::Color, Anchor, BlendMode, CustomData, CustomShader, DerivedSignal, Effect, EllipseData,
+    Fill, Font, ImageData, ImageFilter, ImageSource, LineCap, LineJoin, PolylineData,
+    RectangleData, RoundedRectangleData, Shape, SignalRead, Stroke, StrokeAlignment, TextAlign,
+    TextData,
+};
+
+/// Colours given as anything which converts to one, such as the `0xAARRGGBB` `u32`s used
+/// before there was a `Color`.
+fn colour_signal<'a, C: Into<Color> + 'a>(
+    colour: impl Into<DerivedSignal<'a, C>>,
+) -> DerivedSignal<'a, Color> {
+    let colour = colour.into();
+    DerivedSignal::new(move || colour.get().into())
+}
+
+/// A fill of the colour `colour` has at the time.
+fn solid<'a>(colour: DerivedSignal<'a, Color>) -> DerivedSignal<'a, Fill> {
+    DerivedSignal::new(move || Fill::Solid(colour.get()))
+}
+
+fn owned_stops(stops: &[(f32, impl Into<Color> + Copy)]) -> Vec<(f32, Color)> {
+    stops
+        .iter()
+        .map(|&(offset, colour)| (offset, colour.into()))
+        .collect()
+}
+
+/// A [`Fill::linear`] whose ends follow signals, for a node's `set_fill`.
+pub fn linear_gradient<'a>(
+    start_x: impl Into<DerivedSignal<'a, f32>>,
+    start_y: impl Into<DerivedSignal<'a, f32>>,
+    end_x: impl Into<DerivedSignal<'a, f32>>,
+    end_y: impl Into<DerivedSignal<'a, f32>>,
+    stops: &[(f32, impl Into<Color> + Copy)],
+) -> DerivedSignal<'a, Fill> {
+    let (start_x, start_y, end_x, end_y) =
+        (start_x.into(), start_y.into(), end_x.into(), end_y.into());
+    let stops = owned_stops(stops);
+    DerivedSignal::new(move || {
+        Fill::linear(
+            (start_x.get(), start_y.get()),
+            (end_x.get(), end_y.get()),
+            &stops,
+        )
+    })
+}
+
+/// A [`Fill::radial`] whose centre and radius follow signals.
+pub fn radial_gradient<'a>(
+    centre_x: impl Into<DerivedSignal<'a, f32>>,
+    centre_y: impl Into<DerivedSignal<'a, f32>>,
+    radius: impl Into<DerivedSignal<'a, f32>>,
+    stops: &[(f32, impl Into<Color> + Copy)],
+) -> DerivedSignal<'a, Fill> {
+    let (centre_x, centre_y, radius) = (centre_x.into(), centre_y.into(), radius.into());
+    let stops = owned_stops(stops);
+    DerivedSignal::new(move || Fill::radial((centre_x.get(), centre_y.get()), radius.get(), &stops))
+}
+
+/// A [`Fill::conic`] whose centre and starting angle follow signals.
+pub fn conic_gradient<'a>(
+    centre_x: impl Into<DerivedSignal<'a, f32>>,
+    centre_y: impl Into<DerivedSignal<'a, f32>>,
+    angle: impl Into<DerivedSignal<'a, f32>>,
+    stops: &[(f32, impl Into<Color> + Copy)],
+) -> DerivedSignal<'a, Fill> {
+    let (centre_x, centre_y, angle) = (centre_x.into(), centre_y.into(), angle.into());
+    let stops = owned_stops(stops);
+    DerivedSignal::new(move || Fill::conic((centre_x.get(), centre_y.get()), angle.get(), &stops))
+}
+
+/// The outline of a node, which isn't drawn while its width is zero.
+struct Outline<'a> {
+    width: DerivedSignal<'a, f32>,
+    colour: DerivedSignal<'a, Color>,
+    alignment: StrokeAlignment,
+}
+impl Outline<'_> {
+    fn to_stroke(&self) -> Option<Stroke> {
+        let width = self.width.get();
+        (width > 0.0).then(|| Stroke::new(width, self.colour.get()).with_alignment(self.alignment))
+    }
+}
+impl Default for Outline<'_> {
+    fn default() -> Self {
+        Self {
+            width: DerivedSignal::new(|| 0.0f32),
+            colour: DerivedSignal::new(|| Color::BLACK),
+            alignment: StrokeAlignment::Centre,
+        }
+    }
+}
 
 pub struct Circle<'a> {
     position: (DerivedSignal<'a, f32>, DerivedSignal<'a, f32>),
     radius: DerivedSignal<'a, f32>,
-    colour: DerivedSignal<'a, u32>,
+    fill: DerivedSignal<'a, Fill>,
+    blend_mode: BlendMode,
+    outline: Outline<'a>,
 }
 impl<'a> Circle<'a> {
-    pub fn new(
+    pub fn new<C: Into<Color> + 'a>(
         pos_x: impl Into<DerivedSignal<'a, f32>>,
         pos_y: impl Into<DerivedSignal<'a, f32>>,
         radius: impl Into<DerivedSignal<'a, f32>>,
-        colour: impl Into<DerivedSignal<'a, u32>>,
+        colour: impl Into<DerivedSignal<'a, C>>,
     ) -> Self {
         Self {
             position: (pos_x.into(), pos_y.into()),
             radius: radius.into(),
-            colour: colour.into(),
+            fill: solid(colour_signal(colour)),
+            blend_mode: BlendMode::Normal,
+            outline: Outline::default(),
         }
     }
 
@@ -36,8 +132,39 @@ impl<'a> Circle<'a> {
         self
     }
 
-    pub fn set_colour(&mut self, colour: impl Into<DerivedSignal<'a, u32>>) -> &mut Self {
-        self.colour = colour.into();
+    pub fn set_colour<C: Into<Color> + 'a>(
+        &mut self,
+        colour: impl Into<DerivedSignal<'a, C>>,
+    ) -> &mut Self {
+        self.fill = solid(colour_signal(colour));
+        self
+    }
+
+    pub fn set_fill(&mut self, fill: impl Into<DerivedSignal<'a, Fill>>) -> &mut Self {
+        self.fill = fill.into();
+        self
+    }
+
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) -> &mut Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    pub fn set_stroke_width(&mut self, width: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.outline.width = width.into();
+        self
+    }
+
+    pub fn set_stroke_colour<C: Into<Color> + 'a>(
+        &mut self,
+        colour: impl Into<DerivedSignal<'a, C>>,
+    ) -> &mut Self {
+        self.outline.colour = colour_signal(colour);
+        self
+    }
+
+    pub fn set_stroke_alignment(&mut self, alignment: StrokeAlignment) -> &mut Self {
+        self.outline.alignment = alignment;
         self
     }
 
@@ -45,7 +172,9 @@ impl<'a> Circle<'a> {
         Shape::Circle(crate::CircleData {
             position: (self.position.0.get(), self.position.1.get()),
             radius: self.radius.get(),
-            colour: self.colour.get(),
+            fill: self.fill.get(),
+            blend_mode: self.blend_mode,
+            stroke: self.outline.to_stroke(),
         })
     }
 }
@@ -54,7 +183,9 @@ impl Default for Circle<'_> {
         Self {
             position: (DerivedSignal::new(|| 0.0f32), DerivedSignal::new(|| 0.0f32)),
             radius: DerivedSignal::new(|| 0.0f32),
-            colour: DerivedSignal::new(|| 0xFF000000u32),
+            fill: DerivedSignal::new(Fill::default),
+            blend_mode: BlendMode::Normal,
+            outline: Outline::default(),
         }
     }
 }
@@ -62,20 +193,29 @@ impl Default for Circle<'_> {
 pub struct Rectangle<'a> {
     position: (DerivedSignal<'a, f32>, DerivedSignal<'a, f32>),
     size: (DerivedSignal<'a, f32>, DerivedSignal<'a, f32>),
-    colour: DerivedSignal<'a, u32>,
+    fill: DerivedSignal<'a, Fill>,
+    blend_mode: BlendMode,
+    outline: Outline<'a>,
+    /// Clockwise, in radians.
+    rotation: DerivedSignal<'a, f32>,
+    anchor: DerivedSignal<'a, Anchor>,
 }
 impl<'a> Rectangle<'a> {
-    pub fn new(
+    pub fn new<C: Into<Color> + 'a>(
         pos_x: impl Into<DerivedSignal<'a, f32>>,
         pos_y: impl Into<DerivedSignal<'a, f32>>,
         width: impl Into<DerivedSignal<'a, f32>>,
         height: impl Into<DerivedSignal<'a, f32>>,
-        colour: impl Into<DerivedSignal<'a, u32>>,
+        colour: impl Into<DerivedSignal<'a, C>>,
     ) -> Self {
         Self {
             position: (pos_x.into(), pos_y.into()),
             size: (width.into(), height.into()),
-            colour: colour.into(),
+            fill: solid(colour_signal(colour)),
+            blend_mode: BlendMode::Normal,
+            outline: Outline::default(),
+            rotation: DerivedSignal::new(|| 0.0f32),
+            anchor: DerivedSignal::new(|| Anchor::TopLeft),
         }
     }
 
@@ -99,16 +239,61 @@ impl<'a> Rectangle<'a> {
         self
     }
 
-    pub fn set_colour(&mut self, colour: impl Into<DerivedSignal<'a, u32>>) -> &mut Self {
-        self.colour = colour.into();
+    pub fn set_colour<C: Into<Color> + 'a>(
+        &mut self,
+        colour: impl Into<DerivedSignal<'a, C>>,
+    ) -> &mut Self {
+        self.fill = solid(colour_signal(colour));
+        self
+    }
+
+    pub fn set_fill(&mut self, fill: impl Into<DerivedSignal<'a, Fill>>) -> &mut Self {
+        self.fill = fill.into();
+        self
+    }
+
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) -> &mut Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    pub fn set_stroke_width(&mut self, width: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.outline.width = width.into();
+        self
+    }
+
+    pub fn set_stroke_colour<C: Into<Color> + 'a>(
+        &mut self,
+        colour: impl Into<DerivedSignal<'a, C>>,
+    ) -> &mut Self {
+        self.outline.colour = colour_signal(colour);
+        self
+    }
+
+    pub fn set_stroke_alignment(&mut self, alignment: StrokeAlignment) -> &mut Self {
+        self.outline.alignment = alignment;
+        self
+    }
+
+    pub fn set_rotation(&mut self, rotation: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.rotation = rotation.into();
+        self
+    }
+
+    pub fn set_anchor(&mut self, anchor: impl Into<DerivedSignal<'a, Anchor>>) -> &mut Self {
+        self.anchor = anchor.into();
         self
     }
 
     pub fn to_shape(&self) -> Shape {
         Shape::Rectangle(RectangleData {
             position: (self.position.0.get(), self.position.1.get()),
-            colour: self.colour.get(),
+            fill: self.fill.get(),
             size: (self.size.0.get(), self.size.1.get()),
+            blend_mode: self.blend_mode,
+            stroke: self.outline.to_stroke(),
+            rotation: self.rotation.get(),
+            anchor: self.anchor.get(),
         })
     }
 }
@@ -117,7 +302,662 @@ impl Default for Rectangle<'_> {
         Self {
             position: (DerivedSignal::new(|| 0.0f32), DerivedSignal::new(|| 0.0f32)),
             size: (DerivedSignal::new(|| 0.0f32), DerivedSignal::new(|| 0.0f32)),
-            colour: DerivedSignal::new(|| 0xFF000000u32),
+            fill: DerivedSignal::new(Fill::default),
+            blend_mode: BlendMode::Normal,
+            outline: Outline::default(),
+            rotation: DerivedSignal::new(|| 0.0f32),
+            anchor: DerivedSignal::new(|| Anchor::TopLeft),
+        }
+    }
+}
+
+pub struct Ellipse<'a> {
+    position: (DerivedSignal<'a, f32>, DerivedSignal<'a, f32>),
+    radii: (DerivedSignal<'a, f32>, DerivedSignal<'a, f32>),
+    fill: DerivedSignal<'a, Fill>,
+    blend_mode: BlendMode,
+}
+impl<'a> Ellipse<'a> {
+    pub fn new<C: Into<Color> + 'a>(
+        pos_x: impl Into<DerivedSignal<'a, f32>>,
+        pos_y: impl Into<DerivedSignal<'a, f32>>,
+        radius_x: impl Into<DerivedSignal<'a, f32>>,
+        radius_y: impl Into<DerivedSignal<'a, f32>>,
+        colour: impl Into<DerivedSignal<'a, C>>,
+    ) -> Self {
+        Self {
+            position: (pos_x.into(), pos_y.into()),
+            radii: (radius_x.into(), radius_y.into()),
+            fill: solid(colour_signal(colour)),
+            blend_mode: BlendMode::Normal,
+        }
+    }
+
+    pub fn set_pos_x(&mut self, x: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.position.0 = x.into();
+        self
+    }
+
+    pub fn set_pos_y(&mut self, y: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.position.1 = y.into();
+        self
+    }
+
+    pub fn set_radius_x(&mut self, radius: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.radii.0 = radius.into();
+        self
+    }
+
+    pub fn set_radius_y(&mut self, radius: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.radii.1 = radius.into();
+        self
+    }
+
+    pub fn set_colour<C: Into<Color> + 'a>(
+        &mut self,
+        colour: impl Into<DerivedSignal<'a, C>>,
+    ) -> &mut Self {
+        self.fill = solid(colour_signal(colour));
+        self
+    }
+
+    pub fn set_fill(&mut self, fill: impl Into<DerivedSignal<'a, Fill>>) -> &mut Self {
+        self.fill = fill.into();
+        self
+    }
+
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) -> &mut Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    pub fn to_shape(&self) -> Shape {
+        Shape::Ellipse(EllipseData {
+            position: (self.position.0.get(), self.position.1.get()),
+            radii: (self.radii.0.get(), self.radii.1.get()),
+            fill: self.fill.get(),
+            blend_mode: self.blend_mode,
+        })
+    }
+}
+impl Default for Ellipse<'_> {
+    fn default() -> Self {
+        Self {
+            position: (DerivedSignal::new(|| 0.0f32), DerivedSignal::new(|| 0.0f32)),
+            radii: (DerivedSignal::new(|| 0.0f32), DerivedSignal::new(|| 0.0f32)),
+            fill: DerivedSignal::new(Fill::default),
+            blend_mode: BlendMode::Normal,
+        }
+    }
+}
+
+pub struct RoundedRectangle<'a> {
+    position: (DerivedSignal<'a, f32>, DerivedSignal<'a, f32>),
+    size: (DerivedSignal<'a, f32>, DerivedSignal<'a, f32>),
+    /// Clockwise from the top-left.
+    corner_radii: DerivedSignal<'a, [f32; 4]>,
+    fill: DerivedSignal<'a, Fill>,
+    blend_mode: BlendMode,
+}
+impl<'a> RoundedRectangle<'a> {
+    pub fn new<C: Into<Color> + 'a>(
+        pos_x: impl Into<DerivedSignal<'a, f32>>,
+        pos_y: impl Into<DerivedSignal<'a, f32>>,
+        width: impl Into<DerivedSignal<'a, f32>>,
+        height: impl Into<DerivedSignal<'a, f32>>,
+        corner_radii: impl Into<DerivedSignal<'a, [f32; 4]>>,
+        colour: impl Into<DerivedSignal<'a, C>>,
+    ) -> Self {
+        Self {
+            position: (pos_x.into(), pos_y.into()),
+            size: (width.into(), height.into()),
+            corner_radii: corner_radii.into(),
+            fill: solid(colour_signal(colour)),
+            blend_mode: BlendMode::Normal,
+        }
+    }
+
+    pub fn set_pos_x(&mut self, x: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.position.0 = x.into();
+        self
+    }
+
+    pub fn set_pos_y(&mut self, y: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.position.1 = y.into();
+        self
+    }
+
+    pub fn set_width(&mut self, width: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.size.0 = width.into();
+        self
+    }
+
+    pub fn set_height(&mut self, height: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.size.1 = height.into();
+        self
+    }
+
+    pub fn set_corner_radii(
+        &mut self,
+        corner_radii: impl Into<DerivedSignal<'a, [f32; 4]>>,
+    ) -> &mut Self {
+        self.corner_radii = corner_radii.into();
+        self
+    }
+
+    pub fn set_colour<C: Into<Color> + 'a>(
+        &mut self,
+        colour: impl Into<DerivedSignal<'a, C>>,
+    ) -> &mut Self {
+        self.fill = solid(colour_signal(colour));
+        self
+    }
+
+    pub fn set_fill(&mut self, fill: impl Into<DerivedSignal<'a, Fill>>) -> &mut Self {
+        self.fill = fill.into();
+        self
+    }
+
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) -> &mut Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    pub fn to_shape(&self) -> Shape {
+        Shape::RoundedRectangle(RoundedRectangleData {
+            position: (self.position.0.get(), self.position.1.get()),
+            size: (self.size.0.get(), self.size.1.get()),
+            corner_radii: self.corner_radii.get(),
+            fill: self.fill.get(),
+            blend_mode: self.blend_mode,
+        })
+    }
+}
+impl Default for RoundedRectangle<'_> {
+    fn default() -> Self {
+        Self {
+            position: (DerivedSignal::new(|| 0.0f32), DerivedSignal::new(|| 0.0f32)),
+            size: (DerivedSignal::new(|| 0.0f32), DerivedSignal::new(|| 0.0f32)),
+            corner_radii: DerivedSignal::new(|| [0.0f32; 4]),
+            fill: DerivedSignal::new(Fill::default),
+            blend_mode: BlendMode::Normal,
+        }
+    }
+}
+
+pub struct Line<'a> {
+    start: (DerivedSignal<'a, f32>, DerivedSignal<'a, f32>),
+    end: (DerivedSignal<'a, f32>, DerivedSignal<'a, f32>),
+    width: DerivedSignal<'a, f32>,
+    fill: DerivedSignal<'a, Fill>,
+    blend_mode: BlendMode,
+    cap: LineCap,
+}
+impl<'a> Line<'a> {
+    pub fn new<C: Into<Color> + 'a>(
+        start_x: impl Into<DerivedSignal<'a, f32>>,
+        start_y: impl Into<DerivedSignal<'a, f32>>,
+        end_x: impl Into<DerivedSignal<'a, f32>>,
+        end_y: impl Into<DerivedSignal<'a, f32>>,
+        width: impl Into<DerivedSignal<'a, f32>>,
+        colour: impl Into<DerivedSignal<'a, C>>,
+    ) -> Self {
+        Self {
+            start: (start_x.into(), start_y.into()),
+            end: (end_x.into(), end_y.into()),
+            width: width.into(),
+            fill: solid(colour_signal(colour)),
+            blend_mode: BlendMode::Normal,
+            cap: LineCap::Butt,
+        }
+    }
+
+    pub fn set_start_x(&mut self, x: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.start.0 = x.into();
+        self
+    }
+
+    pub fn set_start_y(&mut self, y: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.start.1 = y.into();
+        self
+    }
+
+    pub fn set_end_x(&mut self, x: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.end.0 = x.into();
+        self
+    }
+
+    pub fn set_end_y(&mut self, y: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.end.1 = y.into();
+        self
+    }
+
+    pub fn set_width(&mut self, width: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.width = width.into();
+        self
+    }
+
+    pub fn set_colour<C: Into<Color> + 'a>(
+        &mut self,
+        colour: impl Into<DerivedSignal<'a, C>>,
+    ) -> &mut Self {
+        self.fill = solid(colour_signal(colour));
+        self
+    }
+
+    pub fn set_fill(&mut self, fill: impl Into<DerivedSignal<'a, Fill>>) -> &mut Self {
+        self.fill = fill.into();
+        self
+    }
+
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) -> &mut Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    pub fn set_cap(&mut self, cap: LineCap) -> &mut Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn to_shape(&self) -> Shape {
+        Shape::Polyline(
+            PolylineData::new(
+                vec![
+                    (self.start.0.get(), self.start.1.get()),
+                    (self.end.0.get(), self.end.1.get()),
+                ],
+                self.width.get(),
+                self.fill.get(),
+            )
+            .with_blend_mode(self.blend_mode)
+            .with_cap(self.cap),
+        )
+    }
+}
+impl Default for Line<'_> {
+    fn default() -> Self {
+        Self {
+            start: (DerivedSignal::new(|| 0.0f32), DerivedSignal::new(|| 0.0f32)),
+            end: (DerivedSignal::new(|| 0.0f32), DerivedSignal::new(|| 0.0f32)),
+            width: DerivedSignal::new(|| 1.0f32),
+            fill: DerivedSignal::new(Fill::default),
+            blend_mode: BlendMode::Normal,
+            cap: LineCap::Butt,
+        }
+    }
+}
+
+pub struct Polyline<'a> {
+    points: DerivedSignal<'a, Vec<(f32, f32)>>,
+    width: DerivedSignal<'a, f32>,
+    fill: DerivedSignal<'a, Fill>,
+    blend_mode: BlendMode,
+    cap: LineCap,
+    join: LineJoin,
+    miter_limit: f32,
+    closed: bool,
+}
+impl<'a> Polyline<'a> {
+    pub fn new<C: Into<Color> + 'a>(
+        points: impl Into<DerivedSignal<'a, Vec<(f32, f32)>>>,
+        width: impl Into<DerivedSignal<'a, f32>>,
+        colour: impl Into<DerivedSignal<'a, C>>,
+    ) -> Self {
+        Self {
+            points: points.into(),
+            width: width.into(),
+            fill: solid(colour_signal(colour)),
+            blend_mode: BlendMode::Normal,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            miter_limit: 4.0,
+            closed: false,
+        }
+    }
+
+    pub fn set_points(
+        &mut self,
+        points: impl Into<DerivedSignal<'a, Vec<(f32, f32)>>>,
+    ) -> &mut Self {
+        self.points = points.into();
+        self
+    }
+
+    pub fn set_width(&mut self, width: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.width = width.into();
+        self
+    }
+
+    pub fn set_colour<C: Into<Color> + 'a>(
+        &mut self,
+        colour: impl Into<DerivedSignal<'a, C>>,
+    ) -> &mut Self {
+        self.fill = solid(colour_signal(colour));
+        self
+    }
+
+    pub fn set_fill(&mut self, fill: impl Into<DerivedSignal<'a, Fill>>) -> &mut Self {
+        self.fill = fill.into();
+        self
+    }
+
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) -> &mut Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    pub fn set_cap(&mut self, cap: LineCap) -> &mut Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn set_join(&mut self, join: LineJoin) -> &mut Self {
+        self.join = join;
+        self
+    }
+
+    pub fn set_miter_limit(&mut self, miter_limit: f32) -> &mut Self {
+        self.miter_limit = miter_limit;
+        self
+    }
+
+    pub fn set_closed(&mut self, closed: bool) -> &mut Self {
+        self.closed = closed;
+        self
+    }
+
+    pub fn to_shape(&self) -> Shape {
+        Shape::Polyline(PolylineData {
+            points: self.points.get(),
+            width: self.width.get(),
+            fill: self.fill.get(),
+            blend_mode: self.blend_mode,
+            cap: self.cap,
+            join: self.join,
+            miter_limit: self.miter_limit,
+            closed: self.closed,
+        })
+    }
+}
+impl Default for Polyline<'_> {
+    fn default() -> Self {
+        Self {
+            points: DerivedSignal::new(Vec::new),
+            width: DerivedSignal::new(|| 1.0f32),
+            fill: DerivedSignal::new(Fill::default),
+            blend_mode: BlendMode::Normal,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            miter_limit: 4.0,
+            closed: false,
         }
     }
 }
+
+pub struct Image<'a> {
+    source: ImageSource,
+    position: (DerivedSignal<'a, f32>, DerivedSignal<'a, f32>),
+    size: (DerivedSignal<'a, f32>, DerivedSignal<'a, f32>),
+    tint: DerivedSignal<'a, Color>,
+    opacity: DerivedSignal<'a, f32>,
+    filter: ImageFilter,
+    blend_mode: BlendMode,
+}
+impl<'a> Image<'a> {
+    /// The image at its own size, until it's given another.
+    pub fn new(
+        source: ImageSource,
+        pos_x: impl Into<DerivedSignal<'a, f32>>,
+        pos_y: impl Into<DerivedSignal<'a, f32>>,
+    ) -> Self {
+        let (width, height) = (source.width() as f32, source.height() as f32);
+        Self {
+            source,
+            position: (pos_x.into(), pos_y.into()),
+            size: (
+                DerivedSignal::new(move || width),
+                DerivedSignal::new(move || height),
+            ),
+            tint: DerivedSignal::new(|| Color::WHITE),
+            opacity: DerivedSignal::new(|| 1.0f32),
+            filter: ImageFilter::default(),
+            blend_mode: BlendMode::Normal,
+        }
+    }
+
+    pub fn set_pos_x(&mut self, x: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.position.0 = x.into();
+        self
+    }
+
+    pub fn set_pos_y(&mut self, y: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.position.1 = y.into();
+        self
+    }
+
+    pub fn set_width(&mut self, width: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.size.0 = width.into();
+        self
+    }
+
+    pub fn set_height(&mut self, height: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.size.1 = height.into();
+        self
+    }
+
+    pub fn set_tint<C: Into<Color> + 'a>(
+        &mut self,
+        tint: impl Into<DerivedSignal<'a, C>>,
+    ) -> &mut Self {
+        self.tint = colour_signal(tint);
+        self
+    }
+
+    pub fn set_opacity(&mut self, opacity: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.opacity = opacity.into();
+        self
+    }
+
+    pub fn set_filter(&mut self, filter: ImageFilter) -> &mut Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) -> &mut Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    pub fn to_shape(&self) -> Shape {
+        Shape::Image(ImageData {
+            source: self.source.clone(),
+            position: (self.position.0.get(), self.position.1.get()),
+            size: (self.size.0.get(), self.size.1.get()),
+            region: (0, 0, self.source.width(), self.source.height()),
+            filter: self.filter,
+            tint: self.tint.get(),
+            opacity: self.opacity.get(),
+            blend_mode: self.blend_mode,
+        })
+    }
+}
+
+pub struct Text<'a> {
+    font: Font,
+    content: DerivedSignal<'a, String>,
+    size: DerivedSignal<'a, f32>,
+    position: (DerivedSignal<'a, f32>, DerivedSignal<'a, f32>),
+    colour: DerivedSignal<'a, Color>,
+    align: TextAlign,
+    line_height: f32,
+    blend_mode: BlendMode,
+}
+impl<'a> Text<'a> {
+    pub fn new<C: Into<Color> + 'a>(
+        font: Font,
+        content: impl Into<DerivedSignal<'a, String>>,
+        size: impl Into<DerivedSignal<'a, f32>>,
+        pos_x: impl Into<DerivedSignal<'a, f32>>,
+        pos_y: impl Into<DerivedSignal<'a, f32>>,
+        colour: impl Into<DerivedSignal<'a, C>>,
+    ) -> Self {
+        Self {
+            font,
+            content: content.into(),
+            size: size.into(),
+            position: (pos_x.into(), pos_y.into()),
+            colour: colour_signal(colour),
+            align: TextAlign::Left,
+            line_height: 1.2,
+            blend_mode: BlendMode::Normal,
+        }
+    }
+
+    pub fn set_content(&mut self, content: impl Into<DerivedSignal<'a, String>>) -> &mut Self {
+        self.content = content.into();
+        self
+    }
+
+    pub fn set_size(&mut self, size: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.size = size.into();
+        self
+    }
+
+    pub fn set_pos_x(&mut self, x: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.position.0 = x.into();
+        self
+    }
+
+    pub fn set_pos_y(&mut self, y: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.position.1 = y.into();
+        self
+    }
+
+    pub fn set_colour<C: Into<Color> + 'a>(
+        &mut self,
+        colour: impl Into<DerivedSignal<'a, C>>,
+    ) -> &mut Self {
+        self.colour = colour_signal(colour);
+        self
+    }
+
+    pub fn set_align(&mut self, align: TextAlign) -> &mut Self {
+        self.align = align;
+        self
+    }
+
+    pub fn set_line_height(&mut self, line_height: f32) -> &mut Self {
+        self.line_height = line_height;
+        self
+    }
+
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) -> &mut Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    pub fn to_shape(&self) -> Shape {
+        Shape::Text(TextData {
+            content: self.content.get(),
+            font: self.font.clone(),
+            size: self.size.get(),
+            position: (self.position.0.get(), self.position.1.get()),
+            colour: self.colour.get(),
+            align: self.align,
+            line_height: self.line_height,
+            blend_mode: self.blend_mode,
+        })
+    }
+}
+
+/// An effect whose parameters are recomputed every frame, such as a blur which grows.
+pub struct PostEffect<'a> {
+    effect: DerivedSignal<'a, Effect>,
+}
+impl<'a> PostEffect<'a> {
+    pub fn new(effect: impl Into<DerivedSignal<'a, Effect>>) -> Self {
+        Self {
+            effect: effect.into(),
+        }
+    }
+
+    pub fn set_effect(&mut self, effect: impl Into<DerivedSignal<'a, Effect>>) -> &mut Self {
+        self.effect = effect.into();
+        self
+    }
+
+    pub fn to_shape(&self) -> Shape {
+        Shape::Effect(self.effect.get())
+    }
+}
+
+/// A custom shape whose box and uniforms are recomputed every frame, such as a shader
+/// animated by a time uniform.
+pub struct Custom<'a, T> {
+    shader: CustomShader,
+    position: (DerivedSignal<'a, f32>, DerivedSignal<'a, f32>),
+    size: (DerivedSignal<'a, f32>, DerivedSignal<'a, f32>),
+    uniforms: DerivedSignal<'a, T>,
+    blend_mode: BlendMode,
+}
+impl<'a, T: bytemuck::Pod> Custom<'a, T> {
+    pub fn new(
+        shader: CustomShader,
+        pos_x: impl Into<DerivedSignal<'a, f32>>,
+        pos_y: impl Into<DerivedSignal<'a, f32>>,
+        width: impl Into<DerivedSignal<'a, f32>>,
+        height: impl Into<DerivedSignal<'a, f32>>,
+        uniforms: impl Into<DerivedSignal<'a, T>>,
+    ) -> Self {
+        Self {
+            shader,
+            position: (pos_x.into(), pos_y.into()),
+            size: (width.into(), height.into()),
+            uniforms: uniforms.into(),
+            blend_mode: BlendMode::Normal,
+        }
+    }
+
+    pub fn set_pos_x(&mut self, x: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.position.0 = x.into();
+        self
+    }
+
+    pub fn set_pos_y(&mut self, y: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.position.1 = y.into();
+        self
+    }
+
+    pub fn set_width(&mut self, width: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.size.0 = width.into();
+        self
+    }
+
+    pub fn set_height(&mut self, height: impl Into<DerivedSignal<'a, f32>>) -> &mut Self {
+        self.size.1 = height.into();
+        self
+    }
+
+    pub fn set_uniforms(&mut self, uniforms: impl Into<DerivedSignal<'a, T>>) -> &mut Self {
+        self.uniforms = uniforms.into();
+        self
+    }
+
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) -> &mut Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    pub fn to_shape(&self) -> Shape {
+        Shape::Custom(
+            CustomData::new(
+                self.shader.clone(),
+                (self.position.0.get(), self.position.1.get()),
+                (self.size.0.get(), self.size.1.get()),
+            )
+            .with_uniforms(&self.uniforms.get())
+            .with_blend_mode(self.blend_mode),
+        )
+    }
+}
@@ -0,0 +1,130 @@
+mod adapter;
+mod cpu;
+mod gpu;
+mod shaders;
+
+pub use adapter::{available_adapters, AdapterOptions, AdapterSummary, GpuError};
+pub use cpu::CpuRenderer;
+pub use gpu::GpuRenderer;
+pub(crate) use shaders::ShaderWatch;
+pub use shaders::{ShaderError, ShaderSources};
+
+use crate::{color::Color, shapes::Shape};
+
+/// A backend which draws a frame's shapes into an RGBA8 pixel buffer.
+///
+/// Implementations produce identical pixels for the same shapes,
+/// so scenes can be rendered on the GPU and checked on the CPU.
+/// There are two exceptions:
+/// - Custom shapes are left out by renderers which can't run WGSL,
+///   which [`Renderer::supports_custom_shapes`] tells.
+/// - [`GpuRenderer::set_rasterized`] multisamples the edges of shapes,
+///   which then differ from the analytic or supersampled edges drawn otherwise.
+pub trait Renderer {
+    fn width(&self) -> u32;
+    fn height(&self) -> u32;
+
+    fn canvas(&self) -> Canvas;
+    fn set_canvas(&mut self, canvas: Canvas);
+
+    fn antialiasing(&self) -> Antialiasing;
+    fn set_antialiasing(&mut self, antialiasing: Antialiasing);
+
+    /// Whether [`Shape::Custom`] shapes are drawn. Those which don't skip them,
+    /// drawing everything else as usual.
+    fn supports_custom_shapes(&self) -> bool;
+
+    /// Draws `shapes` in order and returns the frame as `width * height * 4` bytes.
+    fn render_frame(&mut self, shapes: &[Shape]) -> Vec<u8>;
+
+    /// Draws each sub-frame from the same starting canvas and returns their average,
+    /// which blurs whatever moves between them. One sub-frame renders like `render_frame`.
+    fn render_sub_frames(&mut self, sub_frames: &[Vec<Shape>]) -> Vec<u8>;
+
+    /// Renders every frame, each given as its sub-frames, and passes it to `save` along with its index.
+    ///
+    /// Backends may call `save` from several threads at once, and so not in order,
+    /// but each frame is always rendered as if the frames before it were drawn first.
+    fn render_frames(
+        &mut self,
+        frames: &mut dyn Iterator<Item = Vec<Vec<Shape>>>,
+        save: &(dyn Fn(usize, Vec<u8>) + Sync),
+    ) {
+        for (i, frame) in frames.enumerate() {
+            save(i, self.render_sub_frames(&frame));
+        }
+    }
+}
+
+/// What happens to the previous frame's pixels before a new frame is drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CanvasMode {
+    /// Every frame starts from the background colour.
+    Clear,
+    /// The previous frame is kept, for trails and feedback effects. The first starts from the background.
+    /// Each frame it is faded towards the background by `decay`, where 0.0 keeps it untouched
+    /// and 1.0 behaves like [`CanvasMode::Clear`].
+    Persist { decay: f32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Canvas {
+    pub background: Color,
+    pub mode: CanvasMode,
+}
+impl Canvas {
+    pub fn new(background: impl Into<Color>) -> Self {
+        Self {
+            background: background.into(),
+            mode: CanvasMode::Clear,
+        }
+    }
+
+    pub fn transparent() -> Self {
+        Self::new(Color::TRANSPARENT)
+    }
+
+    pub fn persist(background: impl Into<Color>, decay: f32) -> Self {
+        Self {
+            background: background.into(),
+            mode: CanvasMode::Persist { decay },
+        }
+    }
+
+    /// How far the previous frame is faded towards the background, from 0.0 to 1.0.
+    pub fn decay(&self) -> f32 {
+        match self.mode {
+            CanvasMode::Clear => 1.0,
+            CanvasMode::Persist { decay } => decay.clamp(0.0, 1.0),
+        }
+    }
+}
+impl Default for Canvas {
+    fn default() -> Self {
+        Self::transparent()
+    }
+}
+
+/// How the edges of curved shapes are smoothed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Antialiasing {
+    /// Hard edges, each pixel is either inside or outside. The fastest, for drafting.
+    #[default]
+    None,
+    /// Coverage from the signed distance to the edge, fading out over one pixel.
+    Analytic,
+    /// Coverage from an N×N grid of samples inside each pixel.
+    Supersample(u32),
+}
+impl Antialiasing {
+    pub const MAX_SAMPLES: u32 = 16;
+
+    /// The mode and sample count, as passed to the kernels.
+    pub fn to_uniforms(self) -> (u32, u32) {
+        match self {
+            Antialiasing::None => (0, 1),
+            Antialiasing::Analytic => (1, 1),
+            Antialiasing::Supersample(samples) => (2, samples.clamp(1, Self::MAX_SAMPLES)),
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use std::fmt;
+
+use super::ShaderError;
+
+/// Which adapter the GPU renderer runs on.
+///
+/// On a headless machine without a GPU, Mesa's software rasterizers (lavapipe for
+/// Vulkan and llvmpipe for GL) are only offered as a fallback adapter, so set
+/// `force_fallback_adapter` or pick one by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdapterOptions {
+    pub backends: wgpu::Backends,
+    pub power_preference: wgpu::PowerPreference,
+    /// Only accepts an adapter which runs on the CPU.
+    pub force_fallback_adapter: bool,
+    /// Picks the first adapter whose name contains this, ignoring case, instead of letting wgpu choose.
+    pub adapter_name: Option<String>,
+}
+impl Default for AdapterOptions {
+    fn default() -> Self {
+        Self {
+            backends: wgpu::Backends::all(),
+            power_preference: wgpu::PowerPreference::None,
+            force_fallback_adapter: false,
+            adapter_name: None,
+        }
+    }
+}
+impl AdapterOptions {
+    /// The defaults, overridden by the variables other wgpu programs read: `WGPU_BACKEND`
+    /// (such as `vulkan,gl`), `WGPU_POWER_PREF` (`low`, `high` or `none`) and `WGPU_ADAPTER_NAME`.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        Self {
+            backends: wgpu::util::backend_bits_from_env().unwrap_or(default.backends),
+            power_preference: wgpu::util::power_preference_from_env()
+                .unwrap_or(default.power_preference),
+            adapter_name: std::env::var("WGPU_ADAPTER_NAME").ok(),
+            ..default
+        }
+    }
+
+    pub fn with_backends(self, backends: wgpu::Backends) -> Self {
+        Self { backends, ..self }
+    }
+
+    pub fn with_power_preference(self, power_preference: wgpu::PowerPreference) -> Self {
+        Self {
+            power_preference,
+            ..self
+        }
+    }
+
+    pub fn with_fallback_adapter(self, force_fallback_adapter: bool) -> Self {
+        Self {
+            force_fallback_adapter,
+            ..self
+        }
+    }
+
+    pub fn with_adapter_name(self, adapter_name: Option<String>) -> Self {
+        Self {
+            adapter_name,
+            ..self
+        }
+    }
+
+    pub(crate) fn create_instance(&self) -> wgpu::Instance {
+        wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: self.backends,
+            ..Default::default()
+        })
+    }
+
+    /// Finds the adapter these options ask for, or explains why there isn't one.
+    pub(crate) async fn request_adapter(
+        &self,
+        instance: &wgpu::Instance,
+    ) -> Result<wgpu::Adapter, GpuError> {
+        let adapter = match &self.adapter_name {
+            Some(name) => find_adapter(instance, self, name),
+            None => {
+                instance
+                    .request_adapter(&wgpu::RequestAdapterOptions {
+                        power_preference: self.power_preference,
+                        force_fallback_adapter: self.force_fallback_adapter,
+                        compatible_surface: None,
+                    })
+                    .await
+            }
+        };
+        adapter.ok_or_else(|| GpuError::NoAdapter {
+            options: self.clone(),
+            available: available_adapters(self.backends),
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn find_adapter(
+    instance: &wgpu::Instance,
+    options: &AdapterOptions,
+    name: &str,
+) -> Option<wgpu::Adapter> {
+    let name = name.to_lowercase();
+    instance
+        .enumerate_adapters(options.backends)
+        .into_iter()
+        .find(|x| {
+            let info = x.get_info();
+            info.name.to_lowercase().contains(&name)
+                && (!options.force_fallback_adapter || info.device_type == wgpu::DeviceType::Cpu)
+        })
+}
+
+/// Browsers only ever offer one adapter, so its name isn't checked.
+#[cfg(target_arch = "wasm32")]
+fn find_adapter(
+    _instance: &wgpu::Instance,
+    _options: &AdapterOptions,
+    _name: &str,
+) -> Option<wgpu::Adapter> {
+    None
+}
+
+/// Every adapter on `backends`, for choosing one by name.
+/// Browsers don't list their adapters, so this is always empty on the web.
+pub fn available_adapters(backends: wgpu::Backends) -> Vec<wgpu::AdapterInfo> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends,
+            ..Default::default()
+        });
+        instance
+            .enumerate_adapters(backends)
+            .iter()
+            .map(|x| x.get_info())
+            .collect()
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = backends;
+        vec![]
+    }
+}
+
+/// Why the GPU renderer couldn't be set up.
+#[derive(Debug)]
+pub enum GpuError {
+    Shader(ShaderError),
+    /// No adapter matched the options. `available` lists those which exist.
+    NoAdapter {
+        options: AdapterOptions,
+        available: Vec<wgpu::AdapterInfo>,
+    },
+    /// The adapter was found but can't run the renderer, usually for lack of a limit it needs.
+    Device {
+        adapter: Box<wgpu::AdapterInfo>,
+        error: wgpu::RequestDeviceError,
+    },
+}
+impl fmt::Display for GpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpuError::Shader(error) => error.fmt(f),
+            GpuError::NoAdapter { options, available } => {
+                write!(f, "no GPU adapter matches {options:?}")?;
+                if available.is_empty() {
+                    write!(f, ", and none are available")
+                } else {
+                    write!(f, ". The available adapters are:")?;
+                    for info in available {
+                        write!(f, "\n  {}", AdapterSummary(info))?;
+                    }
+                    Ok(())
+                }
+            }
+            GpuError::Device { adapter, error } => {
+                write!(f, "couldn't use {}: {error}", AdapterSummary(adapter))
+            }
+        }
+    }
+}
+impl std::error::Error for GpuError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GpuError::Shader(error) => Some(error),
+            GpuError::NoAdapter { .. } => None,
+            GpuError::Device { error, .. } => Some(error),
+        }
+    }
+}
+impl From<ShaderError> for GpuError {
+    fn from(error: ShaderError) -> Self {
+        GpuError::Shader(error)
+    }
+}
+
+/// Shows an adapter in one line, such as `llvmpipe (LLVM 15.0.7, 256 bits) (Cpu, Vulkan)`.
+pub struct AdapterSummary<'a>(pub &'a wgpu::AdapterInfo);
+impl fmt::Display for AdapterSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({:?}, {:?})",
+            self.0.name, self.0.device_type, self.0.backend
+        )
+    }
+}
@@ -0,0 +1,901 @@
+use std::f32::consts::TAU;
+
+use super::{Antialiasing, Canvas, Renderer};
+use crate::color::Color;
+use crate::shapes::{
+    BlendMode, EffectPass, FillRule, FrameRecords, GradientRecord, GradientStop, ImageFilter,
+    ImageSource, LineCap, LineJoin, MaskMode, PassLayer, Shape, ShapeRecord, POLYGON_SAMPLE_COUNT,
+};
+
+/// Where in a pixel polygons are sampled when antialiasing, in the standard pattern for four samples.
+const POLYGON_SAMPLES: [(f32, f32); POLYGON_SAMPLE_COUNT as usize] = [
+    (0.375, 0.125),
+    (0.875, 0.375),
+    (0.125, 0.625),
+    (0.625, 0.875),
+];
+
+/// The fractional bits rasterizers snap vertices to.
+const SUBPIXEL_BITS: u32 = 8;
+
+/// A pure-Rust rasterizer which mirrors the compute kernels pixel for pixel.
+pub struct CpuRenderer {
+    width: u32,
+    height: u32,
+    canvas: Canvas,
+    antialiasing: Antialiasing,
+    pixels: Vec<u32>,
+    /// Whether `pixels` has been cleared to the background yet, which even a persisted canvas
+    /// is before its first frame.
+    cleared: bool,
+    /// The layers of the groups being drawn, innermost last, which shapes draw into instead of `pixels`.
+    layers: Vec<Vec<u32>>,
+    /// Where effects keep their intermediate results.
+    scratch_layers: Vec<Vec<u32>>,
+}
+impl CpuRenderer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            canvas: Canvas::default(),
+            antialiasing: Antialiasing::default(),
+            pixels: vec![0; width as usize * height as usize],
+            cleared: false,
+            layers: vec![],
+            scratch_layers: vec![],
+        }
+    }
+
+    fn prepare_canvas(&mut self) {
+        let decay = if self.cleared {
+            self.canvas.decay()
+        } else {
+            1.0
+        };
+        self.cleared = true;
+        if decay >= 1.0 {
+            self.pixels.fill(self.canvas.background.to_packed());
+        } else if decay > 0.0 {
+            let background = unpack_colour(self.canvas.background.to_packed());
+            for pixel in self.pixels.iter_mut() {
+                let old = unpack_colour(*pixel);
+                *pixel = pack_colour(std::array::from_fn(|i| {
+                    old[i] + (background[i] - old[i]) * decay
+                }));
+            }
+        }
+    }
+
+    fn draw_shapes(&mut self, shapes: &[Shape]) {
+        let records = FrameRecords::new(shapes, self.antialiasing, self.width, self.height);
+        for record in &records.shapes {
+            match record.kind {
+                ShapeRecord::GROUP_START | ShapeRecord::GROUP_MASK => self
+                    .layers
+                    .push(vec![0; self.width as usize * self.height as usize]),
+                ShapeRecord::GROUP_END => self.composite_group(record),
+                ShapeRecord::EFFECT => self.apply_effect(record, &records.weights),
+                // Custom shapes are drawn by WGSL, which only the GPU can run,
+                // as `supports_custom_shapes` tells.
+                ShapeRecord::CUSTOM => {}
+                _ => self.draw(record, &records),
+            }
+        }
+    }
+
+    /// The pixels shapes are drawn into, the innermost group's layer or the canvas.
+    fn target(&mut self) -> &mut [u32] {
+        self.layers.last_mut().unwrap_or(&mut self.pixels)
+    }
+
+    /// Composites the innermost group's layer through its mask onto the layer beneath, popping both.
+    fn composite_group(&mut self, record: &ShapeRecord) {
+        let [opacity, mask, _, _] = record.params[0];
+        let mask = mask as u32;
+        let mask_layer = (mask != 0).then(|| self.layers.pop().unwrap());
+        let layer = self.layers.pop().unwrap();
+        let [offset_x, offset_y, width, height] = record.clip;
+        let blend_mode = BlendMode::from_index(record.blend_mode);
+        let canvas_width = self.width as usize;
+        let target = self.target();
+        for pixel_y in offset_y..offset_y + height {
+            for pixel_x in offset_x..offset_x + width {
+                let id = pixel_y as usize * canvas_width + pixel_x as usize;
+                let mut coverage = opacity;
+                if let Some(mask_layer) = &mask_layer {
+                    let m = unpack_colour(mask_layer[id]);
+                    coverage *= if mask == MaskMode::Alpha as u32 + 1 {
+                        m[3]
+                    } else {
+                        luminance(m) * m[3]
+                    };
+                }
+                if coverage <= 0.0 {
+                    continue;
+                }
+                target[id] = composite(target[id], with_coverage(layer[id], coverage), blend_mode);
+            }
+        }
+    }
+
+    /// Runs the passes of the effect in `record` over the layer being drawn into.
+    fn apply_effect(&mut self, record: &ShapeRecord, weights: &[f32]) {
+        let (width, height) = (self.width, self.height);
+        let mut scratch = std::mem::take(&mut self.scratch_layers);
+        scratch.resize_with(2, || vec![0; width as usize * height as usize]);
+        let drawn = self.target();
+        let (first, second) = scratch.split_at_mut(1);
+        let (first, second) = (&mut first[0], &mut second[0]);
+        let run = |kind, source: &[u32], target: &mut [u32]| {
+            run_effect_pass(kind, record, weights, source, target, (width, height));
+        };
+        for pass in EffectPass::passes(record) {
+            match (pass.source, pass.target) {
+                (PassLayer::Drawn, PassLayer::Drawn) => run(pass.kind, &[], drawn),
+                (PassLayer::Drawn, PassLayer::Scratch(0)) => run(pass.kind, drawn, first),
+                (PassLayer::Scratch(0), PassLayer::Drawn) => run(pass.kind, first, drawn),
+                (PassLayer::Scratch(0), PassLayer::Scratch(1)) => run(pass.kind, first, second),
+                (PassLayer::Scratch(1), PassLayer::Scratch(0)) => run(pass.kind, second, first),
+                _ => {
+                    unreachable!("effects only pass between the drawn layer and two scratch layers")
+                }
+            }
+        }
+        self.scratch_layers = scratch;
+    }
+
+    fn draw(&mut self, record: &ShapeRecord, records: &FrameRecords) {
+        let [offset_x, offset_y, width, height] = record.clip;
+        let blend_mode = BlendMode::from_index(record.blend_mode);
+        for pixel_y in offset_y..offset_y + height {
+            for pixel_x in offset_x..offset_x + width {
+                let coverage = self.coverage(record, &records.points, (pixel_x, pixel_y));
+                if coverage <= 0.0 {
+                    continue;
+                }
+                let colour = if record.kind == ShapeRecord::IMAGE {
+                    let image = &records.images[record.params[1][2] as usize];
+                    image_colour(record, image, (pixel_x, pixel_y))
+                } else if record.gradient != 0 {
+                    gradient_colour(
+                        &records.gradients[record.gradient as usize - 1],
+                        &records.stops,
+                        (pixel_x as f32 + 0.5, pixel_y as f32 + 0.5),
+                    )
+                } else {
+                    record.colour
+                };
+                let width = self.width as usize;
+                let pixel = &mut self.target()[pixel_y as usize * width + pixel_x as usize];
+                *pixel = composite(*pixel, with_coverage(colour, coverage), blend_mode);
+            }
+        }
+    }
+
+    fn coverage(&self, record: &ShapeRecord, points: &[[f32; 2]], pixel: (u32, u32)) -> f32 {
+        match record.kind {
+            ShapeRecord::CIRCLE => self.circle_coverage(record, pixel),
+            ShapeRecord::RECTANGLE => 1.0,
+            ShapeRecord::ROTATED_RECTANGLE
+            | ShapeRecord::ELLIPSE
+            | ShapeRecord::ROUNDED_RECTANGLE
+            | ShapeRecord::POLYLINE
+            | ShapeRecord::PATH
+            | ShapeRecord::CIRCLE_OUTLINE
+            | ShapeRecord::RECTANGLE_OUTLINE
+            | ShapeRecord::IMAGE => self.distance_coverage(record, points, pixel),
+            ShapeRecord::POLYGON => self.polygon_coverage(record, points, pixel),
+            _ => 0.0,
+        }
+    }
+
+    fn circle_coverage(&self, record: &ShapeRecord, pixel: (u32, u32)) -> f32 {
+        let [centre_x, centre_y, radius, _] = record.params[0];
+        let (mode, samples) = self.antialiasing.to_uniforms();
+        if mode == 0 {
+            let x = (pixel.0 as i32 - record.origin[0]) as f32 - radius;
+            let y = (pixel.1 as i32 - record.origin[1]) as f32 - radius;
+            return if x * x + y * y <= radius * radius {
+                1.0
+            } else {
+                0.0
+            };
+        }
+
+        let pixel = (pixel.0 as f32, pixel.1 as f32);
+        if mode == 1 {
+            let x = pixel.0 + 0.5 - centre_x;
+            let y = pixel.1 + 0.5 - centre_y;
+            let distance = (x * x + y * y).sqrt() - radius;
+            return (0.5 - distance).clamp(0.0, 1.0);
+        }
+
+        let mut inside = 0;
+        for i in 0..samples {
+            for j in 0..samples {
+                let x = pixel.0 + (j as f32 + 0.5) / samples as f32 - centre_x;
+                let y = pixel.1 + (i as f32 + 0.5) / samples as f32 - centre_y;
+                if x * x + y * y <= radius * radius {
+                    inside += 1;
+                }
+            }
+        }
+        inside as f32 / (samples * samples) as f32
+    }
+
+    /// The fraction of samples inside a polygon's triangles, as the polygon render pipeline rasterizes them.
+    fn polygon_coverage(
+        &self,
+        record: &ShapeRecord,
+        points: &[[f32; 2]],
+        pixel: (u32, u32),
+    ) -> f32 {
+        let [first, count, _, _] = record.params[0];
+        let triangles = points[first as usize..(first + count) as usize].chunks_exact(3);
+        let samples: &[(f32, f32)] = if self.antialiasing == Antialiasing::None {
+            &[(0.5, 0.5)]
+        } else {
+            &POLYGON_SAMPLES
+        };
+        let covered = samples
+            .iter()
+            .filter(|(x, y)| {
+                let sample = (pixel.0 as f32 + x, pixel.1 as f32 + y);
+                triangles.clone().any(|x| triangle_contains(x, sample))
+            })
+            .count();
+        covered as f32 / samples.len() as f32
+    }
+
+    /// Coverage of shapes described by a signed distance, which is negative inside them.
+    fn distance_coverage(
+        &self,
+        record: &ShapeRecord,
+        points: &[[f32; 2]],
+        pixel: (u32, u32),
+    ) -> f32 {
+        let (mode, samples) = self.antialiasing.to_uniforms();
+        let pixel = (pixel.0 as f32, pixel.1 as f32);
+        if mode == 0 {
+            let distance = signed_distance(record, points, (pixel.0 + 0.5, pixel.1 + 0.5));
+            return if distance <= 0.0 { 1.0 } else { 0.0 };
+        }
+
+        if mode == 1 {
+            let distance = signed_distance(record, points, (pixel.0 + 0.5, pixel.1 + 0.5));
+            return (0.5 - distance).clamp(0.0, 1.0);
+        }
+
+        let mut inside = 0;
+        for i in 0..samples {
+            for j in 0..samples {
+                let x = pixel.0 + (j as f32 + 0.5) / samples as f32;
+                let y = pixel.1 + (i as f32 + 0.5) / samples as f32;
+                if signed_distance(record, points, (x, y)) <= 0.0 {
+                    inside += 1;
+                }
+            }
+        }
+        inside as f32 / (samples * samples) as f32
+    }
+}
+impl Renderer for CpuRenderer {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn canvas(&self) -> Canvas {
+        self.canvas
+    }
+
+    fn set_canvas(&mut self, canvas: Canvas) {
+        self.canvas = canvas;
+    }
+
+    fn antialiasing(&self) -> Antialiasing {
+        self.antialiasing
+    }
+
+    fn set_antialiasing(&mut self, antialiasing: Antialiasing) {
+        self.antialiasing = antialiasing;
+    }
+
+    fn supports_custom_shapes(&self) -> bool {
+        false
+    }
+
+    fn render_frame(&mut self, shapes: &[Shape]) -> Vec<u8> {
+        self.prepare_canvas();
+        self.draw_shapes(shapes);
+        bytemuck::cast_slice(&self.pixels).to_vec()
+    }
+
+    fn render_sub_frames(&mut self, sub_frames: &[Vec<Shape>]) -> Vec<u8> {
+        if sub_frames.len() <= 1 {
+            return self.render_frame(sub_frames.first().map_or(&[], Vec::as_slice));
+        }
+        self.prepare_canvas();
+        let canvas = self.pixels.clone();
+        let mut sums = vec![[0.0f32; 4]; self.pixels.len()];
+        for (i, shapes) in sub_frames.iter().enumerate() {
+            if i > 0 {
+                self.pixels.copy_from_slice(&canvas);
+            }
+            self.draw_shapes(shapes);
+            // Summed in linear light with alpha premultiplied, matching `accumulate_sub_frame`
+            // in shader-canvas.wgsl.
+            for (sum, &pixel) in sums.iter_mut().zip(&self.pixels) {
+                let premultiplied = premultiplied_linear(pixel);
+                for k in 0..4 {
+                    sum[k] += premultiplied[k];
+                }
+            }
+        }
+        let count = sub_frames.len() as f32;
+        for (pixel, sum) in self.pixels.iter_mut().zip(&sums) {
+            *pixel = from_premultiplied_linear(sum.map(|x| x / count));
+        }
+        bytemuck::cast_slice(&self.pixels).to_vec()
+    }
+}
+
+fn length(x: f32, y: f32) -> f32 {
+    (x * x + y * y).sqrt()
+}
+
+/// Whether a triangle covers a sample by the rasterizer's rules.
+/// Vertices are snapped to `SUBPIXEL_BITS`, and samples exactly on an edge are only covered by top and left edges.
+fn triangle_contains(triangle: &[[f32; 2]], sample: (f32, f32)) -> bool {
+    let snap = |x: f32| (x * (1 << SUBPIXEL_BITS) as f32).round() as i64;
+    let mut vertices = [0, 1, 2].map(|i| (snap(triangle[i][0]), snap(triangle[i][1])));
+    let sample = (snap(sample.0), snap(sample.1));
+    // Positive when `point` is to the right of the edge from `a` to `b`, on a canvas with y pointing down.
+    let edge = |a: (i64, i64), b: (i64, i64), point: (i64, i64)| {
+        (b.0 - a.0) * (point.1 - a.1) - (b.1 - a.1) * (point.0 - a.0)
+    };
+    let area = edge(vertices[0], vertices[1], vertices[2]);
+    if area == 0 {
+        return false;
+    }
+    // Clockwise on the canvas, so the inside is to the right of every edge.
+    if area < 0 {
+        vertices.swap(1, 2);
+    }
+    (0..3).all(|i| {
+        let (a, b) = (vertices[i], vertices[(i + 1) % 3]);
+        let distance = edge(a, b, sample);
+        let top_left = b.1 < a.1 || (b.1 == a.1 && b.0 > a.0);
+        distance > 0 || (distance == 0 && top_left)
+    })
+}
+
+/// The distance from `point` to the edge of a shape, negative inside it.
+fn signed_distance(record: &ShapeRecord, points: &[[f32; 2]], (x, y): (f32, f32)) -> f32 {
+    let [centre_x, centre_y, size_x, size_y] = record.params[0];
+    let (dx, dy) = (x - centre_x, y - centre_y);
+    match record.kind {
+        ShapeRecord::IMAGE => box_distance((dx, dy), (size_x, size_y), 0.0),
+        ShapeRecord::ROTATED_RECTANGLE => {
+            let [cos, sin, _, _] = record.params[1];
+            let (x, y) = (dx * cos + dy * sin, -dx * sin + dy * cos);
+            box_distance((x, y), (size_x, size_y), 0.0)
+        }
+        ShapeRecord::ELLIPSE => {
+            // An approximation which is exact on the edge, where it matters.
+            let k0 = length(dx / size_x, dy / size_y);
+            let k1 = length(dx / (size_x * size_x), dy / (size_y * size_y));
+            if k1 == 0.0 {
+                return -size_x.min(size_y);
+            }
+            k0 * (k0 - 1.0) / k1
+        }
+        ShapeRecord::ROUNDED_RECTANGLE => {
+            let [top_left, top_right, bottom_right, bottom_left] = record.params[1];
+            let radius = match (dx > 0.0, dy > 0.0) {
+                (false, false) => top_left,
+                (true, false) => top_right,
+                (true, true) => bottom_right,
+                (false, true) => bottom_left,
+            };
+            box_distance((dx, dy), (size_x, size_y), radius)
+        }
+        ShapeRecord::POLYLINE => polyline_distance(record, points, (x, y)),
+        ShapeRecord::PATH => path_distance(record, points, (x, y)),
+        ShapeRecord::CIRCLE_OUTLINE => {
+            let distance = length(dx, dy);
+            (distance - size_y).max(size_x - distance)
+        }
+        ShapeRecord::RECTANGLE_OUTLINE => {
+            let [cos, sin, inner_x, inner_y] = record.params[1];
+            let (x, y) = (dx * cos + dy * sin, -dx * sin + dy * cos);
+            box_distance((x, y), (size_x, size_y), 0.0).max(-box_distance(
+                (x, y),
+                (inner_x, inner_y),
+                0.0,
+            ))
+        }
+        _ => f32::INFINITY,
+    }
+}
+
+/// The unit vector from `start` towards `end`.
+fn unit_vector(start: [f32; 2], end: [f32; 2]) -> (f32, f32) {
+    let (dx, dy) = (end[0] - start[0], end[1] - start[1]);
+    let length = length(dx, dy);
+    (dx / length, dy / length)
+}
+
+fn polyline_distance(record: &ShapeRecord, points: &[[f32; 2]], point: (f32, f32)) -> f32 {
+    let [half_width, miter_limit, first, count] = record.params[0];
+    let (first, count) = (first as usize, count as usize);
+    let [cap, join, closed, _] = record.params[1].map(|x| x as u32);
+    let closed = closed != 0;
+    // Closed polylines have no caps.
+    let cap = if closed { LineCap::Butt as u32 } else { cap };
+    let points = &points[first..first + count];
+
+    let mut distance = f32::INFINITY;
+    let mut incoming = unit_vector(points[count - 1], points[0]);
+    let segments = if closed { count } else { count - 1 };
+    for i in 0..segments {
+        let (start, end) = (points[i], points[(i + 1) % count]);
+        let segment_length = length(end[0] - start[0], end[1] - start[1]);
+        let direction = unit_vector(start, end);
+
+        // Square caps extend the first and last segments.
+        let extend_start = if cap == LineCap::Square as u32 && i == 0 {
+            half_width
+        } else {
+            0.0
+        };
+        let extend_end = if cap == LineCap::Square as u32 && i + 2 == count {
+            half_width
+        } else {
+            0.0
+        };
+        let centre = (
+            (start[0] + end[0]) * 0.5 + direction.0 * (extend_end - extend_start) * 0.5,
+            (start[1] + end[1]) * 0.5 + direction.1 * (extend_end - extend_start) * 0.5,
+        );
+        let offset = (point.0 - centre.0, point.1 - centre.1);
+        let local = (
+            offset.0 * direction.0 + offset.1 * direction.1,
+            -offset.0 * direction.1 + offset.1 * direction.0,
+        );
+        let half_length = (segment_length + extend_start + extend_end) * 0.5;
+        distance = distance.min(box_distance(local, (half_length, half_width), 0.0));
+
+        if i > 0 || closed {
+            let offset = (point.0 - start[0], point.1 - start[1]);
+            distance = distance.min(join_distance(
+                offset,
+                incoming,
+                direction,
+                half_width,
+                join,
+                miter_limit,
+            ));
+        }
+        incoming = direction;
+    }
+
+    if cap == LineCap::Round as u32 {
+        for end in [points[0], points[count - 1]] {
+            distance = distance.min(length(point.0 - end[0], point.1 - end[1]) - half_width);
+        }
+    }
+    distance
+}
+
+/// The distance to the nearest edge of a path, negative inside it by its fill rule.
+fn path_distance(record: &ShapeRecord, points: &[[f32; 2]], point: (f32, f32)) -> f32 {
+    let [first, size, fill_rule, _] = record.params[0];
+    let points = &points[first as usize..(first + size) as usize];
+    let even_odd = fill_rule as u32 == FillRule::EvenOdd as u32;
+
+    let mut distance = f32::INFINITY;
+    let mut winding = 0i32;
+    let mut start = 0;
+    while start < points.len() {
+        let count = points[start][0] as usize;
+        let subpath = &points[start + 1..start + 1 + count];
+        for k in 0..count {
+            let (a, b) = (subpath[k], subpath[(k + 1) % count]);
+            let edge = (b[0] - a[0], b[1] - a[1]);
+            let offset = (point.0 - a[0], point.1 - a[1]);
+            let along = ((offset.0 * edge.0 + offset.1 * edge.1)
+                / (edge.0 * edge.0 + edge.1 * edge.1))
+                .clamp(0.0, 1.0);
+            distance = distance.min(length(offset.0 - edge.0 * along, offset.1 - edge.1 * along));
+
+            let cross = edge.0 * offset.1 - edge.1 * offset.0;
+            if a[1] <= point.1 && point.1 < b[1] && cross > 0.0 {
+                winding += 1;
+            } else if b[1] <= point.1 && point.1 < a[1] && cross < 0.0 {
+                winding -= 1;
+            }
+        }
+        start += count + 1;
+    }
+
+    let inside = if even_odd {
+        winding % 2 != 0
+    } else {
+        winding != 0
+    };
+    if inside {
+        -distance
+    } else {
+        distance
+    }
+}
+
+/// The distance to the corner filled in where a polyline turns, from `offset` relative to the vertex.
+fn join_distance(
+    offset: (f32, f32),
+    incoming: (f32, f32),
+    outgoing: (f32, f32),
+    half_width: f32,
+    join: u32,
+    miter_limit: f32,
+) -> f32 {
+    if join == LineJoin::Round as u32 {
+        return length(offset.0, offset.1) - half_width;
+    }
+    let turn = incoming.0 * outgoing.1 - incoming.1 * outgoing.0;
+    if turn == 0.0 {
+        return f32::INFINITY;
+    }
+    // The normals pointing away from the inside of the turn.
+    let side = -turn.signum();
+    let outer_in = (-incoming.1 * side, incoming.0 * side);
+    let outer_out = (-outgoing.1 * side, outgoing.0 * side);
+    let dot = |a: (f32, f32), b: (f32, f32)| a.0 * b.0 + a.1 * b.1;
+
+    // The miter lies past the end of the incoming segment, before the start of the outgoing one,
+    // and within both outer edges.
+    let distance = (-dot(offset, incoming))
+        .max(dot(offset, outgoing))
+        .max(dot(offset, outer_in).max(dot(offset, outer_out)) - half_width);
+
+    let cosine = dot(incoming, outgoing);
+    if join == LineJoin::Miter as u32 && (1.0 + cosine) * miter_limit * miter_limit >= 2.0 {
+        return distance;
+    }
+    // Bevels cut the miter off square to the bisector of the outer edges.
+    let bisector = (outer_in.0 + outer_out.0, outer_in.1 + outer_out.1);
+    let bisector_length = length(bisector.0, bisector.1);
+    let bisector = (bisector.0 / bisector_length, bisector.1 / bisector_length);
+    distance.max(dot(offset, bisector) - half_width * dot(outer_in, bisector))
+}
+
+/// The signed distance to a box centred on the origin with `radius` rounded corners.
+fn box_distance((x, y): (f32, f32), (half_width, half_height): (f32, f32), radius: f32) -> f32 {
+    let (x, y) = (
+        x.abs() - half_width + radius,
+        y.abs() - half_height + radius,
+    );
+    length(x.max(0.0), y.max(0.0)) + x.max(y).min(0.0) - radius
+}
+
+/// Splits a packed colour into its four bytes, lowest first, as floats from 0.0 to 1.0.
+fn unpack_colour(colour: u32) -> [f32; 4] {
+    colour.to_le_bytes().map(|x| x as f32 / 255.0)
+}
+
+fn pack_colour(colour: [f32; 4]) -> u32 {
+    u32::from_le_bytes(colour.map(|x| (x.clamp(0.0, 1.0) * 255.0 + 0.5).floor() as u8))
+}
+
+/// Rec. 709 relative luminance, from 0.0 for black to 1.0 for white.
+fn luminance(colour: [f32; 4]) -> f32 {
+    colour[0] * 0.2126 + colour[1] * 0.7152 + colour[2] * 0.0722
+}
+
+/// Scales the alpha of `colour` by how much of the pixel a shape covers.
+fn with_coverage(colour: u32, coverage: f32) -> u32 {
+    let alpha = ((colour >> 24) as f32 * coverage + 0.5).floor() as u32;
+    (colour & 0x00FFFFFF) | (alpha << 24)
+}
+
+/// Colours in linear light with alpha premultiplied, where mixing and averaging them matches
+/// how light adds up, as `premultiplied_linear` in shader-common.wgsl.
+fn premultiplied_linear(colour: u32) -> [f32; 4] {
+    let [red, green, blue, alpha] = Color::from_packed(colour).to_linear();
+    [red * alpha, green * alpha, blue * alpha, alpha]
+}
+
+fn from_premultiplied_linear([red, green, blue, alpha]: [f32; 4]) -> u32 {
+    if alpha <= 0.0 {
+        return 0;
+    }
+    Color::from_linear([red / alpha, green / alpha, blue / alpha, alpha]).to_packed()
+}
+
+/// Mixes two straight-alpha colours in linear light with their alpha premultiplied,
+/// so transparent stops don't darken.
+fn mix_colours(a: u32, b: u32, t: f32) -> u32 {
+    let (pa, pb) = (premultiplied_linear(a), premultiplied_linear(b));
+    from_premultiplied_linear(std::array::from_fn(|i| pa[i] + (pb[i] - pa[i]) * t))
+}
+
+/// The colour of `gradient` at `point`, padded with its end colours.
+fn gradient_colour(gradient: &GradientRecord, stops: &[GradientStop], (x, y): (f32, f32)) -> u32 {
+    if gradient.stop_count == 0 {
+        return 0;
+    }
+    let [start_x, start_y, z, w] = gradient.params;
+    let (dx, dy) = (x - start_x, y - start_y);
+    let t = match gradient.kind {
+        GradientRecord::LINEAR => {
+            let (direction_x, direction_y) = (z - start_x, w - start_y);
+            let length_squared = direction_x * direction_x + direction_y * direction_y;
+            if length_squared > 0.0 {
+                (dx * direction_x + dy * direction_y) / length_squared
+            } else {
+                0.0
+            }
+        }
+        GradientRecord::RADIAL => {
+            if z > 0.0 {
+                length(dx, dy) / z
+            } else {
+                1.0
+            }
+        }
+        GradientRecord::CONIC => {
+            // GPUs' `atan2` can differ in its last bits, so these may be a level off the kernels'.
+            let turns = (dy.atan2(dx) - z) / TAU;
+            turns - turns.floor()
+        }
+        _ => 0.0,
+    }
+    .clamp(0.0, 1.0);
+
+    let stops = &stops[gradient.first_stop as usize..][..gradient.stop_count as usize];
+    if t <= stops[0].offset {
+        return stops[0].colour;
+    }
+    for pair in stops.windows(2) {
+        let (previous, next) = (pair[0], pair[1]);
+        if t <= next.offset {
+            let fraction = (t - previous.offset) / (next.offset - previous.offset);
+            return mix_colours(previous.colour, next.colour, fraction);
+        }
+    }
+    stops[stops.len() - 1].colour
+}
+
+/// The colour of an image at the centre of `pixel`, tinted and faded.
+fn image_colour(record: &ShapeRecord, image: &ImageSource, (x, y): (u32, u32)) -> u32 {
+    let [centre_x, centre_y, half_width, half_height] = record.params[0];
+    let [opacity, filter, _, _] = record.params[1];
+    let [region_x, region_y, width, height] = record.params[2];
+    let u = region_x + (x as f32 + 0.5 - (centre_x - half_width)) / (2.0 * half_width) * width;
+    let v = region_y + (y as f32 + 0.5 - (centre_y - half_height)) / (2.0 * half_height) * height;
+    // Samples past the edges of the region repeat its edge pixels.
+    let texel = |x: f32, y: f32| {
+        image.pixel(
+            x.clamp(region_x, region_x + width - 1.0) as u32,
+            y.clamp(region_y, region_y + height - 1.0) as u32,
+        )
+    };
+
+    let colour = if filter == ImageFilter::Nearest as u32 as f32 {
+        unpack_colour(texel(u.floor(), v.floor()))
+    } else {
+        // Mixed with their alpha premultiplied, so transparent pixels don't darken their neighbours.
+        let (u, v) = (u - 0.5, v - 0.5);
+        let (left, top) = (u.floor(), v.floor());
+        let (fraction_x, fraction_y) = (u - left, v - top);
+        let [top_left, top_right, bottom_left, bottom_right] = [
+            (left, top),
+            (left + 1.0, top),
+            (left, top + 1.0),
+            (left + 1.0, top + 1.0),
+        ]
+        .map(|(x, y)| {
+            let c = unpack_colour(texel(x, y));
+            [c[0] * c[3], c[1] * c[3], c[2] * c[3], c[3]]
+        });
+        let mixed: [f32; 4] = std::array::from_fn(|i| {
+            let upper = top_left[i] + (top_right[i] - top_left[i]) * fraction_x;
+            let lower = bottom_left[i] + (bottom_right[i] - bottom_left[i]) * fraction_x;
+            upper + (lower - upper) * fraction_y
+        });
+        if mixed[3] <= 0.0 {
+            return 0;
+        }
+        [
+            mixed[0] / mixed[3],
+            mixed[1] / mixed[3],
+            mixed[2] / mixed[3],
+            mixed[3],
+        ]
+    };
+    let tint = unpack_colour(record.colour);
+    pack_colour([
+        colour[0] * tint[0],
+        colour[1] * tint[1],
+        colour[2] * tint[2],
+        colour[3] * tint[3] * opacity,
+    ])
+}
+
+/// Runs one pass of an effect over every pixel of `target`, matching `main` in shader-effects.wgsl.
+/// Passes which change pixels in place get an empty `source`.
+fn run_effect_pass(
+    kind: u32,
+    record: &ShapeRecord,
+    weights: &[f32],
+    source: &[u32],
+    target: &mut [u32],
+    (width, height): (u32, u32),
+) {
+    let [_, a, b, c] = record.params[0];
+    let [first_weight, weight_count, _, _] = record.params[1];
+    let weights = &weights[first_weight as usize..(first_weight + weight_count) as usize];
+    let size = (width as f32, height as f32);
+    let source_pixel = |x: i32, y: i32| {
+        let x = x.clamp(0, width as i32 - 1) as usize;
+        let y = y.clamp(0, height as i32 - 1) as usize;
+        unpack_colour(source[y * width as usize + x])
+    };
+    // One direction of a separable Gaussian blur, mixed with alpha premultiplied.
+    let blur = |(x, y): (i32, i32), (dx, dy): (i32, i32)| {
+        let reach = (weights.len() / 2) as i32;
+        let mut sum = [0.0f32; 4];
+        for (i, weight) in weights.iter().enumerate() {
+            let offset = i as i32 - reach;
+            let c = source_pixel(x + dx * offset, y + dy * offset);
+            let premultiplied = [c[0] * c[3], c[1] * c[3], c[2] * c[3], c[3]];
+            for k in 0..4 {
+                sum[k] += premultiplied[k] * weight;
+            }
+        }
+        if sum[3] <= 0.0 {
+            return 0;
+        }
+        pack_colour([sum[0] / sum[3], sum[1] / sum[3], sum[2] / sum[3], sum[3]])
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            let id = (y * width + x) as usize;
+            let pixel = (x as i32, y as i32);
+            target[id] = match kind {
+                EffectPass::BLUR_HORIZONTAL => blur(pixel, (1, 0)),
+                EffectPass::BLUR_VERTICAL => blur(pixel, (0, 1)),
+                EffectPass::BRIGHT => {
+                    // Fades in from the threshold to white, so the glow doesn't start with a hard edge.
+                    let c = unpack_colour(source[id]);
+                    let brightness = ((luminance(c) - a) / (1.0 - a).max(0.0001)).clamp(0.0, 1.0);
+                    pack_colour([c[0], c[1], c[2], c[3] * brightness])
+                }
+                EffectPass::ADD_GLOW => composite(
+                    target[id],
+                    with_coverage(source[id], b),
+                    BlendMode::Additive,
+                ),
+                EffectPass::VIGNETTE => {
+                    let offset_x = ((x as f32 + 0.5) / size.0 - 0.5) * 2.0;
+                    let offset_y = ((y as f32 + 0.5) / size.1 - 0.5) * 2.0;
+                    let distance = (offset_x * offset_x + offset_y * offset_y).sqrt();
+                    let t = ((distance - b) / c.max(0.000001)).clamp(0.0, 1.0);
+                    let darkness = a * t * t * (3.0 - 2.0 * t);
+                    let colour = unpack_colour(target[id]);
+                    pack_colour([
+                        colour[0] * (1.0 - darkness),
+                        colour[1] * (1.0 - darkness),
+                        colour[2] * (1.0 - darkness),
+                        colour[3],
+                    ])
+                }
+                EffectPass::FILM_GRAIN => {
+                    let noise = (hash(x ^ hash(y ^ hash(b as u32))) >> 8) as f32 / 16777216.0;
+                    let grain = (noise * 2.0 - 1.0) * a;
+                    let colour = unpack_colour(target[id]);
+                    pack_colour([
+                        colour[0] + grain,
+                        colour[1] + grain,
+                        colour[2] + grain,
+                        colour[3],
+                    ])
+                }
+                EffectPass::CHROMATIC_ABERRATION => {
+                    let (centre_x, centre_y) = (size.0 * 0.5, size.1 * 0.5);
+                    let (position_x, position_y) = (x as f32 + 0.5, y as f32 + 0.5);
+                    let shift_x = (position_x - centre_x) / centre_x * a;
+                    let shift_y = (position_y - centre_y) / centre_y * a;
+                    let red = source_pixel(
+                        (position_x + shift_x).floor() as i32,
+                        (position_y + shift_y).floor() as i32,
+                    );
+                    let green = source_pixel(pixel.0, pixel.1);
+                    let blue = source_pixel(
+                        (position_x - shift_x).floor() as i32,
+                        (position_y - shift_y).floor() as i32,
+                    );
+                    let alpha = red[3].max(green[3]).max(blue[3]);
+                    if alpha <= 0.0 {
+                        0
+                    } else {
+                        pack_colour([
+                            red[0] * red[3] / alpha,
+                            green[1] * green[3] / alpha,
+                            blue[2] * blue[3] / alpha,
+                            alpha,
+                        ])
+                    }
+                }
+                EffectPass::COLOUR_ADJUST => {
+                    let colour = unpack_colour(target[id]);
+                    let contrasted: [f32; 4] =
+                        std::array::from_fn(|i| (colour[i] - 0.5) * b + 0.5 + a);
+                    let grey = luminance(contrasted);
+                    pack_colour([
+                        grey + (contrasted[0] - grey) * c,
+                        grey + (contrasted[1] - grey) * c,
+                        grey + (contrasted[2] - grey) * c,
+                        colour[3],
+                    ])
+                }
+                EffectPass::COPY => source[id],
+                _ => target[id],
+            };
+        }
+    }
+}
+
+/// A well mixed hash of 32 bits.
+fn hash(x: u32) -> u32 {
+    let mut h = x;
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7feb352d);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846ca68b);
+    h ^= h >> 16;
+    h
+}
+
+fn blend_channel(blend_mode: BlendMode, backdrop: f32, source: f32) -> f32 {
+    match blend_mode {
+        BlendMode::Normal => source,
+        BlendMode::Multiply => backdrop * source,
+        BlendMode::Screen => backdrop + source - backdrop * source,
+        BlendMode::Additive => (backdrop + source).min(1.0),
+        BlendMode::Darken => backdrop.min(source),
+        BlendMode::Lighten => backdrop.max(source),
+    }
+}
+
+/// Source-over compositing of straight-alpha colours in linear light,
+/// matching `composite` in shader-common.wgsl.
+fn composite(backdrop: u32, source: u32, blend_mode: BlendMode) -> u32 {
+    let source_alpha = source >> 24;
+    if source_alpha == 0 {
+        return backdrop;
+    }
+    if source_alpha == 0xFF && blend_mode == BlendMode::Normal {
+        return source;
+    }
+
+    let cb = Color::from_packed(backdrop).to_linear();
+    let cs = Color::from_packed(source).to_linear();
+    let alpha = cs[3] + cb[3] * (1.0 - cs[3]);
+    if alpha <= 0.0 {
+        return 0;
+    }
+    let mut result = [0.0; 4];
+    for i in 0..3 {
+        let mixed = (1.0 - cb[3]) * cs[i] + cb[3] * blend_channel(blend_mode, cb[i], cs[i]);
+        result[i] = (cs[3] * mixed + cb[3] * (1.0 - cs[3]) * cb[i]) / alpha;
+    }
+    result[3] = alpha;
+    Color::from_linear(result).to_packed()
+}
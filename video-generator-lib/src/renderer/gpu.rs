@@ -0,0 +1,1602 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    ops::Range,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+};
+
+use wgpu::{util::DeviceExt as _, Buffer, ComputePipeline, TextureView};
+
+use super::{
+    AdapterOptions, Antialiasing, Canvas, GpuError, Renderer, ShaderError, ShaderSources,
+    ShaderWatch,
+};
+use crate::shapes::{
+    BlendMode, CustomData, CustomShader, EffectPass, FrameRecords, GpuInstance, GradientRecord,
+    GradientStop, ImageSource, PassLayer, RasterPipelines, Shape, ShapeRecord,
+    POLYGON_SAMPLE_COUNT, RASTER_SAMPLE_COUNT,
+};
+
+/// The side of the square tiles the batched kernel splits the canvas into.
+/// Matches `TILE_SIZE` and the workgroup size of `main_tiled` in shader.wgsl.
+pub const TILE_SIZE: u32 = 8;
+
+pub struct GpuRenderer {
+    gpu_instance: GpuInstance,
+    canvas: Canvas,
+    antialiasing: Antialiasing,
+    batched: bool,
+    rasterized: bool,
+    /// A ring of buffers the frames in flight are read back through.
+    staging_buffers: Vec<Buffer>,
+    output_buffer: Buffer,
+    /// Whether `output_buffer` has been cleared to the background yet, which even a persisted
+    /// canvas is before its first frame.
+    output_cleared: AtomicBool,
+    /// The coverage of the polygon being drawn, which the compute kernels read.
+    coverage_texture: TextureView,
+    /// Where polygons are rasterized when antialiasing, before being resolved into `coverage_texture`.
+    multisampled_coverage_texture: TextureView,
+    /// Every image drawn so far by its id, uploaded once and kept until no shape can draw it again.
+    image_textures: Mutex<HashMap<u64, ImageTexture>>,
+    /// Bound in place of an image by kernels which don't draw one.
+    placeholder_image_texture: TextureView,
+    /// The kernel of every custom shader drawn so far by its id, compiled once and kept like images.
+    /// Shaders which don't validate have none, and their shapes aren't drawn.
+    custom_pipelines: Mutex<HashMap<u64, (CustomShader, Option<ComputePipeline>)>>,
+    /// The directory shaders are reloaded from when they change, if any.
+    shader_watch: Option<ShaderWatch>,
+    /// Errors from reloaded and custom shaders not yet taken by `take_shader_errors`.
+    shader_errors: Mutex<Vec<ShaderError>>,
+    /// Where shader errors go instead, if anywhere.
+    shader_error_handler: Option<ShaderErrorHandler>,
+    /// The layers of nested groups and their masks, created as deeper nesting needs them.
+    layer_buffers: Mutex<Vec<Buffer>>,
+    /// Where effects keep their intermediate results.
+    scratch_buffers: [Buffer; 2],
+    /// The canvas each sub-frame of a motion blurred frame starts from, and the sum of those drawn,
+    /// created the first time one is rendered.
+    sub_frame_buffers: OnceLock<(Buffer, Buffer)>,
+    /// The multisampled target shapes are rasterized into and the one it's resolved into,
+    /// created the first time shapes are rasterized.
+    raster_textures: OnceLock<(TextureView, TextureView)>,
+}
+
+type ShaderErrorHandler = Box<dyn Fn(ShaderError) + Send + Sync>;
+
+/// An image uploaded for drawing, and how much it was scaled by across and down to fit.
+struct ImageTexture {
+    source: ImageSource,
+    view: TextureView,
+    scale: (f32, f32),
+}
+
+/// The buffers a frame's records are uploaded to.
+struct FrameBuffers {
+    shapes: Buffer,
+    points: Buffer,
+    gradients: Buffer,
+    stops: Buffer,
+    weights: Buffer,
+}
+impl GpuRenderer {
+    /// Creates a renderer on the default adapter with the built-in shaders,
+    /// panicking if there's no adapter it can run on.
+    pub async fn new(width: u32, height: u32) -> Self {
+        Self::with_options(
+            width,
+            height,
+            &ShaderSources::built_in(),
+            &AdapterOptions::default(),
+        )
+        .await
+        .unwrap_or_else(|error| panic!("Couldn't set up the GPU renderer: {error}"))
+    }
+
+    /// Creates a renderer on the default adapter with `sources`, such as those loaded from files.
+    pub async fn with_shaders(
+        width: u32,
+        height: u32,
+        sources: &ShaderSources,
+    ) -> Result<Self, GpuError> {
+        Self::with_options(width, height, sources, &AdapterOptions::default()).await
+    }
+
+    /// Creates a renderer with `sources` on the adapter `options` ask for.
+    pub async fn with_options(
+        width: u32,
+        height: u32,
+        sources: &ShaderSources,
+        options: &AdapterOptions,
+    ) -> Result<Self, GpuError> {
+        Ok(Self::from_instance(
+            GpuInstance::new(width, height, sources, options).await?,
+        ))
+    }
+
+    pub fn from_instance(gpu_instance: GpuInstance) -> Self {
+        let size = (std::mem::size_of::<u8>() as u64
+            * gpu_instance.width as u64
+            * gpu_instance.height as u64
+            * 4) as wgpu::BufferAddress;
+
+        let output_buffer = gpu_instance.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Output Buffer"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let coverage_texture = |sample_count, usage| {
+            gpu_instance
+                .device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Coverage Texture"),
+                    size: wgpu::Extent3d {
+                        width: gpu_instance.width,
+                        height: gpu_instance.height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: GpuInstance::COVERAGE_FORMAT,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT | usage,
+                    view_formats: &[],
+                })
+                .create_view(&Default::default())
+        };
+        let multisampled_coverage_texture =
+            coverage_texture(POLYGON_SAMPLE_COUNT, wgpu::TextureUsages::empty());
+        let coverage_texture = coverage_texture(1, wgpu::TextureUsages::TEXTURE_BINDING);
+        let (placeholder_image_texture, _) = create_image_texture(
+            &gpu_instance,
+            &ImageSource::from_rgba(image::RgbaImage::new(1, 1)),
+        );
+
+        let scratch_buffers = [0, 1].map(|_| {
+            gpu_instance.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Scratch Buffer"),
+                size,
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        });
+
+        let mut renderer = Self {
+            gpu_instance,
+            canvas: Canvas::default(),
+            antialiasing: Antialiasing::default(),
+            batched: true,
+            rasterized: false,
+            staging_buffers: vec![],
+            output_buffer,
+            output_cleared: AtomicBool::new(false),
+            coverage_texture,
+            multisampled_coverage_texture,
+            image_textures: Mutex::default(),
+            placeholder_image_texture,
+            custom_pipelines: Mutex::default(),
+            shader_watch: None,
+            shader_errors: Mutex::default(),
+            shader_error_handler: None,
+            layer_buffers: Mutex::default(),
+            scratch_buffers,
+            sub_frame_buffers: OnceLock::new(),
+            raster_textures: OnceLock::new(),
+        };
+        renderer.set_frames_in_flight(3);
+        renderer
+    }
+
+    pub fn gpu_instance(&self) -> &GpuInstance {
+        &self.gpu_instance
+    }
+
+    /// How many frames `render_frames` keeps queued on the GPU while earlier ones are read back.
+    pub fn set_frames_in_flight(&mut self, frames_in_flight: usize) -> &mut Self {
+        let size = self.output_buffer.size();
+        self.staging_buffers
+            .resize_with(frames_in_flight.max(1), || {
+                self.gpu_instance
+                    .device
+                    .create_buffer(&wgpu::BufferDescriptor {
+                        label: Some("Staging Buffer"),
+                        size,
+                        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                        mapped_at_creation: false,
+                    })
+            });
+        self
+    }
+
+    /// Whether all shapes of a frame are drawn in one tiled dispatch, which is the default,
+    /// or with one dispatch per shape.
+    pub fn set_batched(&mut self, batched: bool) -> &mut Self {
+        self.batched = batched;
+        self
+    }
+
+    /// Whether shapes are drawn as instanced quads by a render pipeline instead of by the kernels,
+    /// to compare the two. Antialiasing in any mode multisamples their edges with
+    /// `RASTER_SAMPLE_COUNT` samples per pixel.
+    ///
+    /// Only shapes blended normally are rasterized, since hardware blending can't do the other modes,
+    /// and polygons, groups, effects and custom shapes are drawn as before. Adapters which can't
+    /// shade each sample, which [`GpuInstance::raster_pipelines`] tells, draw everything with the kernels.
+    pub fn set_rasterized(&mut self, rasterized: bool) -> &mut Self {
+        self.rasterized = rasterized;
+        self
+    }
+
+    /// Recompiles every kernel from `sources`, keeping the old ones if they're invalid.
+    /// Custom shaders are compiled again the next time they're drawn.
+    pub fn reload_shaders(&mut self, sources: &ShaderSources) -> Result<(), ShaderError> {
+        self.gpu_instance.reload_shaders(sources)?;
+        self.custom_pipelines.lock().unwrap().clear();
+        Ok(())
+    }
+
+    /// Reloads the shaders from `directory`, named as [`ShaderSources::load`] expects, whenever
+    /// one of them changes. Changes are looked for before each frame, or each call to
+    /// `render_frames`. If they don't validate, the previous shaders are kept and the error is
+    /// reported as [`GpuRenderer::take_shader_errors`] describes.
+    pub fn watch_shaders(&mut self, directory: impl Into<PathBuf>) -> &mut Self {
+        self.shader_watch = Some(ShaderWatch::new(directory.into()));
+        self
+    }
+
+    /// The errors from watched shaders which failed to reload, and from custom shaders which
+    /// failed to compile and whose shapes were left out, since this was last called.
+    /// Each custom shader is only reported once.
+    pub fn take_shader_errors(&self) -> Vec<ShaderError> {
+        std::mem::take(&mut self.shader_errors.lock().unwrap())
+    }
+
+    /// Passes shader errors to `handler` as they happen, instead of keeping them
+    /// for [`GpuRenderer::take_shader_errors`]. It may be called from any thread.
+    pub fn set_shader_error_handler(
+        &mut self,
+        handler: impl Fn(ShaderError) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.shader_error_handler = Some(Box::new(handler));
+        self
+    }
+
+    fn report_shader_error(&self, error: ShaderError) {
+        match &self.shader_error_handler {
+            Some(handler) => handler(error),
+            None => self.shader_errors.lock().unwrap().push(error),
+        }
+    }
+
+    fn reload_changed_shaders(&mut self) {
+        let Some(watch) = &mut self.shader_watch else {
+            return;
+        };
+        if !watch.changed() {
+            return;
+        }
+        let directory = watch.directory.clone();
+        if let Err(error) = ShaderSources::load(&directory).and_then(|x| self.reload_shaders(&x)) {
+            self.report_shader_error(error);
+        }
+    }
+
+    fn create_uniform_buffer(&self, tiles_x: u32, shape_index: u32) -> Buffer {
+        let (antialiasing, samples) = self.antialiasing.to_uniforms();
+        self.gpu_instance
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Shape Uniform Buffer"),
+                contents: bytemuck::cast_slice(&[
+                    self.gpu_instance.width,
+                    self.gpu_instance.height,
+                    antialiasing,
+                    samples,
+                    tiles_x,
+                    shape_index,
+                    0,
+                    0,
+                ]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            })
+    }
+
+    fn create_shape_bind_group(
+        &self,
+        pipeline: &wgpu::ComputePipeline,
+        target: &Buffer,
+        uniform_buffer: &Buffer,
+        buffers: &FrameBuffers,
+        tile_buffer: Option<&Buffer>,
+        image_texture: &TextureView,
+    ) -> wgpu::BindGroup {
+        let mut entries = vec![
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: target.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: uniform_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: buffers.shapes.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 4,
+                resource: buffers.points.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 5,
+                resource: wgpu::BindingResource::TextureView(&self.coverage_texture),
+            },
+            wgpu::BindGroupEntry {
+                binding: 6,
+                resource: buffers.gradients.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 7,
+                resource: buffers.stops.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 8,
+                resource: wgpu::BindingResource::TextureView(image_texture),
+            },
+        ];
+        if let Some(tile_buffer) = tile_buffer {
+            entries.push(wgpu::BindGroupEntry {
+                binding: 3,
+                resource: tile_buffer.as_entire_binding(),
+            });
+        }
+        self.gpu_instance
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &pipeline.get_bind_group_layout(0),
+                entries: &entries,
+            })
+    }
+
+    /// Draws each of `records[range]` into `target` with its own dispatch over its clipped box,
+    /// with `image_texture` bound for any images among them.
+    fn draw_per_shape(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &Buffer,
+        records: &[ShapeRecord],
+        range: Range<usize>,
+        buffers: &FrameBuffers,
+        image_texture: &TextureView,
+    ) {
+        let pipeline = &self.gpu_instance.shape_compute_pipeline;
+        let bind_groups: Vec<_> = range
+            .clone()
+            .map(|i| {
+                let uniform_buffer = self.create_uniform_buffer(0, i as u32);
+                self.create_shape_bind_group(
+                    pipeline,
+                    target,
+                    &uniform_buffer,
+                    buffers,
+                    None,
+                    image_texture,
+                )
+            })
+            .collect();
+
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: None,
+            timestamp_writes: None,
+        });
+        cpass.set_pipeline(pipeline);
+        for (record, bind_group) in records[range].iter().zip(&bind_groups) {
+            let [_, _, width, height] = record.clip;
+            cpass.set_bind_group(0, bind_group, &[]);
+            cpass.dispatch_workgroups(width.div_ceil(8), height.div_ceil(8), 1);
+        }
+    }
+
+    /// Draws all of `records[range]` into `target` in one dispatch, with one workgroup per tile of the canvas.
+    fn draw_batched(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &Buffer,
+        records: &[ShapeRecord],
+        range: Range<usize>,
+        buffers: &FrameBuffers,
+        image_texture: &TextureView,
+    ) {
+        let (device, pipeline) = (
+            &self.gpu_instance.device,
+            &self.gpu_instance.batch_compute_pipeline,
+        );
+        let tiles_x = self.gpu_instance.width.div_ceil(TILE_SIZE);
+        let tiles_y = self.gpu_instance.height.div_ceil(TILE_SIZE);
+        let tile_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Tile Buffer"),
+            contents: bytemuck::cast_slice(&bin_tiles(records, range, tiles_x, tiles_y)),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let uniform_buffer = self.create_uniform_buffer(tiles_x, 0);
+        let bind_group = self.create_shape_bind_group(
+            pipeline,
+            target,
+            &uniform_buffer,
+            buffers,
+            Some(&tile_buffer),
+            image_texture,
+        );
+
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: None,
+            timestamp_writes: None,
+        });
+        cpass.set_pipeline(pipeline);
+        cpass.set_bind_group(0, &bind_group, &[]);
+        cpass.dispatch_workgroups(tiles_x, tiles_y, 1);
+    }
+
+    /// Draws `records[range]` into `target` with the kernels, batched or not.
+    fn draw_run(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &Buffer,
+        records: &[ShapeRecord],
+        range: Range<usize>,
+        buffers: &FrameBuffers,
+        image_texture: &TextureView,
+    ) {
+        if self.batched {
+            self.draw_batched(encoder, target, records, range, buffers, image_texture);
+        } else {
+            self.draw_per_shape(encoder, target, records, range, buffers, image_texture);
+        }
+    }
+
+    /// Draws `records[range]` into `target` through the raster pipelines, leaving shapes
+    /// with other blend modes than `Normal` to the kernels, keeping the draw order.
+    fn draw_rasterized(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &Buffer,
+        records: &[ShapeRecord],
+        range: Range<usize>,
+        buffers: &FrameBuffers,
+        image_texture: &TextureView,
+    ) {
+        let Some(pipelines) = &self.gpu_instance.raster_pipelines else {
+            self.draw_run(encoder, target, records, range, buffers, image_texture);
+            return;
+        };
+        let normal = |record: &ShapeRecord| record.blend_mode == BlendMode::Normal as u32;
+        let mut start = range.start;
+        while start < range.end {
+            let rasterized = normal(&records[start]);
+            let end = records[start..range.end]
+                .iter()
+                .position(|x| normal(x) != rasterized)
+                .map_or(range.end, |x| start + x);
+            if rasterized {
+                self.rasterize(
+                    encoder,
+                    pipelines,
+                    target,
+                    start..end,
+                    buffers,
+                    image_texture,
+                );
+            } else {
+                self.draw_run(encoder, target, records, start..end, buffers, image_texture);
+            }
+            start = end;
+        }
+    }
+
+    /// Draws the canvas in `target` into a render target, composites the shapes `range` over it
+    /// as instanced quads, then stores the result back into `target`.
+    fn rasterize(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        pipelines: &RasterPipelines,
+        target: &Buffer,
+        range: Range<usize>,
+        buffers: &FrameBuffers,
+        image_texture: &TextureView,
+    ) {
+        let gpu_instance = &self.gpu_instance;
+        let device = &gpu_instance.device;
+        let (multisampled_texture, resolved_texture) = self.raster_textures.get_or_init(|| {
+            let texture = |sample_count, usage| {
+                device
+                    .create_texture(&wgpu::TextureDescriptor {
+                        label: Some("Raster Texture"),
+                        size: wgpu::Extent3d {
+                            width: gpu_instance.width,
+                            height: gpu_instance.height,
+                            depth_or_array_layers: 1,
+                        },
+                        mip_level_count: 1,
+                        sample_count,
+                        dimension: wgpu::TextureDimension::D2,
+                        format: GpuInstance::RASTER_FORMAT,
+                        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | usage,
+                        view_formats: &[],
+                    })
+                    .create_view(&Default::default())
+            };
+            (
+                texture(RASTER_SAMPLE_COUNT, wgpu::TextureUsages::empty()),
+                texture(1, wgpu::TextureUsages::TEXTURE_BINDING),
+            )
+        });
+        let (canvas_pipeline, shape_pipeline, view, resolve_target) =
+            if self.antialiasing == Antialiasing::None {
+                (
+                    &pipelines.canvas_render_pipeline,
+                    &pipelines.shape_render_pipeline,
+                    resolved_texture,
+                    None,
+                )
+            } else {
+                (
+                    &pipelines.canvas_multisample_render_pipeline,
+                    &pipelines.shape_multisample_render_pipeline,
+                    multisampled_texture,
+                    Some(resolved_texture),
+                )
+            };
+
+        // Instances count from the first shape of the range, passed in place of the shape index.
+        let uniform_buffer = self.create_uniform_buffer(0, range.start as u32);
+        let bind_group = |pipeline: &wgpu::RenderPipeline, entries: &[wgpu::BindGroupEntry]| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &pipeline.get_bind_group_layout(0),
+                entries,
+            })
+        };
+        let canvas_bind_group = bind_group(
+            canvas_pipeline,
+            &[
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 9,
+                    resource: target.as_entire_binding(),
+                },
+            ],
+        );
+        let shape_bind_group = bind_group(
+            shape_pipeline,
+            &[
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: buffers.shapes.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: buffers.points.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 6,
+                    resource: buffers.gradients.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 7,
+                    resource: buffers.stops.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 8,
+                    resource: wgpu::BindingResource::TextureView(image_texture),
+                },
+            ],
+        );
+        let store_pipeline = &pipelines.store_compute_pipeline;
+        let store_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &store_pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: target.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 10,
+                    resource: wgpu::BindingResource::TextureView(resolved_texture),
+                },
+            ],
+        });
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            rpass.set_pipeline(canvas_pipeline);
+            rpass.set_bind_group(0, &canvas_bind_group, &[]);
+            rpass.draw(0..3, 0..1);
+            rpass.set_pipeline(shape_pipeline);
+            rpass.set_bind_group(0, &shape_bind_group, &[]);
+            rpass.draw(0..4, 0..range.len() as u32);
+        }
+
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: None,
+            timestamp_writes: None,
+        });
+        cpass.set_pipeline(store_pipeline);
+        cpass.set_bind_group(0, &store_bind_group, &[]);
+        cpass.dispatch_workgroups(
+            gpu_instance.width.div_ceil(8),
+            gpu_instance.height.div_ceil(8),
+            1,
+        );
+    }
+
+    /// Rasterizes the coverage of the polygon `records[index]` with a render pass,
+    /// then composites it into `target` like any other shape.
+    fn draw_polygon(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &Buffer,
+        records: &[ShapeRecord],
+        index: usize,
+        buffers: &FrameBuffers,
+    ) {
+        let gpu_instance = &self.gpu_instance;
+        let [first, count, _, _] = records[index].params[0];
+        let (pipeline, view, resolve_target) = if self.antialiasing == Antialiasing::None {
+            (
+                &gpu_instance.polygon_render_pipeline,
+                &self.coverage_texture,
+                None,
+            )
+        } else {
+            (
+                &gpu_instance.polygon_multisample_render_pipeline,
+                &self.multisampled_coverage_texture,
+                Some(&self.coverage_texture),
+            )
+        };
+        let uniform_buffer =
+            gpu_instance
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Polygon Uniform Buffer"),
+                    contents: bytemuck::cast_slice(&[gpu_instance.width, gpu_instance.height]),
+                    usage: wgpu::BufferUsages::UNIFORM,
+                });
+        let bind_group = gpu_instance
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &pipeline.get_bind_group_layout(0),
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                }],
+            });
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            rpass.set_pipeline(pipeline);
+            rpass.set_bind_group(0, &bind_group, &[]);
+            rpass.set_vertex_buffer(0, buffers.points.slice(..));
+            rpass.draw(first as u32..(first + count) as u32, 0..1);
+        }
+        self.draw_per_shape(
+            encoder,
+            target,
+            records,
+            index..index + 1,
+            buffers,
+            &self.placeholder_image_texture,
+        );
+    }
+
+    /// Composites a group's `layer` through its `mask`, if it has one, onto `target` over the group's clipped box.
+    fn composite_group(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        record: &ShapeRecord,
+        target: &Buffer,
+        layer: &Buffer,
+        mask: Option<&Buffer>,
+    ) {
+        let gpu_instance = &self.gpu_instance;
+        let [opacity, mask_mode, _, _] = record.params[0];
+        let [x, y, width, height] = record.clip;
+        let uniform_buffer =
+            gpu_instance
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Group Uniform Buffer"),
+                    contents: bytemuck::cast_slice(&[
+                        gpu_instance.width,
+                        record.blend_mode,
+                        mask_mode as u32,
+                        bytemuck::cast(opacity),
+                        x,
+                        y,
+                        width,
+                        height,
+                    ]),
+                    usage: wgpu::BufferUsages::UNIFORM,
+                });
+        let pipeline = &gpu_instance.group_compute_pipeline;
+        let bind_group = gpu_instance
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &pipeline.get_bind_group_layout(0),
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: target.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: layer.as_entire_binding(),
+                    },
+                    // Groups without a mask never read it, but something has to be bound.
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: mask.unwrap_or(layer).as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 4,
+                        resource: uniform_buffer.as_entire_binding(),
+                    },
+                ],
+            });
+
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: None,
+            timestamp_writes: None,
+        });
+        cpass.set_pipeline(pipeline);
+        cpass.set_bind_group(0, &bind_group, &[]);
+        cpass.dispatch_workgroups(width.div_ceil(8), height.div_ceil(8), 1);
+    }
+
+    /// Runs the kernel of a custom shape over its clipped box.
+    fn draw_custom(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        record: &ShapeRecord,
+        target: &Buffer,
+        custom: &CustomData,
+        pipeline: &ComputePipeline,
+    ) {
+        let gpu_instance = &self.gpu_instance;
+        let [position_x, position_y, size_x, size_y] = record.params[0];
+        let region_buffer =
+            gpu_instance
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Custom Region Buffer"),
+                    contents: bytemuck::cast_slice(&[
+                        [
+                            gpu_instance.width,
+                            gpu_instance.height,
+                            record.blend_mode,
+                            0,
+                        ],
+                        record.clip,
+                        [position_x, position_y, size_x, size_y].map(f32::to_bits),
+                    ]),
+                    usage: wgpu::BufferUsages::UNIFORM,
+                });
+        // Uniform buffers are bound in whole 16 byte rows, and can't be empty.
+        let mut uniforms = custom.uniforms.clone();
+        uniforms.resize(uniforms.len().div_ceil(16).max(1) * 16, 0);
+        let uniform_buffer =
+            gpu_instance
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Custom Uniform Buffer"),
+                    contents: &uniforms,
+                    usage: wgpu::BufferUsages::UNIFORM,
+                });
+        let bind_group = gpu_instance
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &pipeline.get_bind_group_layout(0),
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: target.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: region_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: uniform_buffer.as_entire_binding(),
+                    },
+                ],
+            });
+
+        let [_, _, width, height] = record.clip;
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: None,
+            timestamp_writes: None,
+        });
+        cpass.set_pipeline(pipeline);
+        cpass.set_bind_group(0, &bind_group, &[]);
+        cpass.dispatch_workgroups(width.div_ceil(8), height.div_ceil(8), 1);
+    }
+
+    /// Runs the passes of the effect in `record` over `target`.
+    fn apply_effect(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        record: &ShapeRecord,
+        target: &Buffer,
+        buffers: &FrameBuffers,
+    ) {
+        let gpu_instance = &self.gpu_instance;
+        let [_, a, b, c] = record.params[0];
+        let [first_weight, weight_count, _, _] = record.params[1];
+        let pipeline = &gpu_instance.effect_compute_pipeline;
+        for pass in EffectPass::passes(record) {
+            let layer = |layer| match layer {
+                PassLayer::Drawn => target,
+                PassLayer::Scratch(i) => &self.scratch_buffers[i],
+            };
+            let (source, pass_target) = (layer(pass.source), layer(pass.target));
+            if pass.kind == EffectPass::COPY {
+                encoder.copy_buffer_to_buffer(source, 0, pass_target, 0, source.size());
+                continue;
+            }
+            // Passes in place read their target, but a source still has to be bound.
+            let source = if pass.source == pass.target {
+                &self.scratch_buffers[0]
+            } else {
+                source
+            };
+
+            let uniform_buffer =
+                gpu_instance
+                    .device
+                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Effect Uniform Buffer"),
+                        contents: bytemuck::cast_slice(&[
+                            gpu_instance.width,
+                            gpu_instance.height,
+                            pass.kind,
+                            first_weight as u32,
+                            weight_count as u32,
+                            0,
+                            0,
+                            0,
+                            0,
+                            bytemuck::cast(a),
+                            bytemuck::cast(b),
+                            bytemuck::cast(c),
+                        ]),
+                        usage: wgpu::BufferUsages::UNIFORM,
+                    });
+            let bind_group = gpu_instance
+                .device
+                .create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: None,
+                    layout: &pipeline.get_bind_group_layout(0),
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: pass_target.as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: uniform_buffer.as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: source.as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 3,
+                            resource: buffers.weights.as_entire_binding(),
+                        },
+                    ],
+                });
+
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: None,
+                timestamp_writes: None,
+            });
+            cpass.set_pipeline(pipeline);
+            cpass.set_bind_group(0, &bind_group, &[]);
+            cpass.dispatch_workgroups(
+                gpu_instance.width.div_ceil(8),
+                gpu_instance.height.div_ceil(8),
+                1,
+            );
+        }
+    }
+
+    /// Encodes drawing `shapes` onto the output buffer.
+    fn encode_shapes(&self, encoder: &mut wgpu::CommandEncoder, shapes: &[Shape]) {
+        let (gpu_instance, output_buffer) = (&self.gpu_instance, &self.output_buffer);
+        let device = &gpu_instance.device;
+
+        // Shapes entirely off the canvas are culled before anything is dispatched.
+        let FrameRecords {
+            shapes: mut records,
+            mut points,
+            mut gradients,
+            mut stops,
+            images,
+            mut weights,
+            custom_shapes,
+        } = FrameRecords::new(
+            shapes,
+            self.antialiasing,
+            gpu_instance.width,
+            gpu_instance.height,
+        );
+
+        // Images are uploaded the first time they're drawn, and let go once nothing else holds them.
+        let mut image_textures = self.image_textures.lock().unwrap();
+        image_textures.retain(|_, x| !x.source.is_unique());
+        for image in &images {
+            image_textures.entry(image.id()).or_insert_with(|| {
+                let (view, scale) = create_image_texture(gpu_instance, image);
+                ImageTexture {
+                    source: image.clone(),
+                    view,
+                    scale,
+                }
+            });
+        }
+        // The regions of images scaled down to fit are scaled with them.
+        for record in records.iter_mut().filter(|x| x.kind == ShapeRecord::IMAGE) {
+            let (scale_x, scale_y) =
+                image_textures[&images[record.params[1][2] as usize].id()].scale;
+            let region = &mut record.params[2];
+            *region = [
+                region[0] * scale_x,
+                region[1] * scale_y,
+                region[2] * scale_x,
+                region[3] * scale_y,
+            ];
+        }
+        let mut custom_pipelines = self.custom_pipelines.lock().unwrap();
+        custom_pipelines.retain(|_, (shader, _)| !shader.is_unique());
+        for custom in &custom_shapes {
+            custom_pipelines
+                .entry(custom.shader.id())
+                .or_insert_with(|| {
+                    let pipeline = create_custom_pipeline(gpu_instance, &custom.shader)
+                        .map_err(|error| self.report_shader_error(error))
+                        .ok();
+                    (custom.shader.clone(), pipeline)
+                });
+        }
+
+        if !records.is_empty() {
+            // Bindings can't be empty, so frames without points or gradients get placeholders.
+            if points.is_empty() {
+                points.push([0.0; 2]);
+            }
+            if gradients.is_empty() {
+                gradients.push(GradientRecord::default());
+            }
+            if stops.is_empty() {
+                stops.push(GradientStop::default());
+            }
+            if weights.is_empty() {
+                weights.push(0.0);
+            }
+            let buffers = FrameBuffers {
+                shapes: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Shape Buffer"),
+                    contents: bytemuck::cast_slice(&records),
+                    usage: wgpu::BufferUsages::STORAGE,
+                }),
+                points: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Point Buffer"),
+                    contents: bytemuck::cast_slice(&points),
+                    usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX,
+                }),
+                gradients: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Gradient Buffer"),
+                    contents: bytemuck::cast_slice(&gradients),
+                    usage: wgpu::BufferUsages::STORAGE,
+                }),
+                stops: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Gradient Stop Buffer"),
+                    contents: bytemuck::cast_slice(&stops),
+                    usage: wgpu::BufferUsages::STORAGE,
+                }),
+                weights: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Weight Buffer"),
+                    contents: bytemuck::cast_slice(&weights),
+                    usage: wgpu::BufferUsages::STORAGE,
+                }),
+            };
+
+            // Each nested group draws into a layer of its own, and so does its mask.
+            let mut layer_buffers = self.layer_buffers.lock().unwrap();
+            let depth = max_group_depth(&records);
+            while layer_buffers.len() < depth {
+                layer_buffers.push(device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Layer Buffer"),
+                    size: output_buffer.size(),
+                    usage: wgpu::BufferUsages::STORAGE
+                        | wgpu::BufferUsages::COPY_DST
+                        | wgpu::BufferUsages::COPY_SRC,
+                    mapped_at_creation: false,
+                }));
+            }
+            let mut targets = vec![output_buffer];
+
+            // Polygons are rasterized between the runs of other shapes, keeping the draw order,
+            // and groups' layers are started and composited and effects run between them too.
+            // Runs also end where they'd need another image bound, so each draws from one.
+            let mut start = 0;
+            while start < records.len() {
+                let mut image = None;
+                let end = records[start..]
+                    .iter()
+                    .position(|x| match x.kind {
+                        ShapeRecord::POLYGON
+                        | ShapeRecord::GROUP_START
+                        | ShapeRecord::GROUP_MASK
+                        | ShapeRecord::GROUP_END
+                        | ShapeRecord::EFFECT
+                        | ShapeRecord::CUSTOM => true,
+                        ShapeRecord::IMAGE => {
+                            let index = x.params[1][2] as usize;
+                            *image.get_or_insert(index) != index
+                        }
+                        _ => false,
+                    })
+                    .map_or(records.len(), |x| start + x);
+                let image_texture = image.map_or(&self.placeholder_image_texture, |x| {
+                    &image_textures[&images[x].id()].view
+                });
+                let target = *targets.last().unwrap();
+                if start < end {
+                    if self.rasterized {
+                        self.draw_rasterized(
+                            encoder,
+                            target,
+                            &records,
+                            start..end,
+                            &buffers,
+                            image_texture,
+                        );
+                    } else {
+                        self.draw_run(
+                            encoder,
+                            target,
+                            &records,
+                            start..end,
+                            &buffers,
+                            image_texture,
+                        );
+                    }
+                }
+                start = end + 1;
+                match records.get(end) {
+                    Some(record) if record.kind == ShapeRecord::POLYGON => {
+                        self.draw_polygon(encoder, target, &records, end, &buffers);
+                    }
+                    Some(record)
+                        if matches!(
+                            record.kind,
+                            ShapeRecord::GROUP_START | ShapeRecord::GROUP_MASK
+                        ) =>
+                    {
+                        let layer = &layer_buffers[targets.len() - 1];
+                        encoder.clear_buffer(layer, 0, None);
+                        targets.push(layer);
+                    }
+                    Some(record) if record.kind == ShapeRecord::GROUP_END => {
+                        let mask = (record.params[0][1] != 0.0).then(|| targets.pop().unwrap());
+                        let layer = targets.pop().unwrap();
+                        let target = *targets.last().unwrap();
+                        self.composite_group(encoder, record, target, layer, mask);
+                    }
+                    Some(record) if record.kind == ShapeRecord::EFFECT => {
+                        self.apply_effect(encoder, record, target, &buffers);
+                    }
+                    Some(record) if record.kind == ShapeRecord::CUSTOM => {
+                        let custom = &custom_shapes[record.params[1][0] as usize];
+                        if let Some(pipeline) = &custom_pipelines[&custom.shader.id()].1 {
+                            self.draw_custom(encoder, record, target, custom, pipeline);
+                        }
+                    }
+                    _ => start = end,
+                }
+            }
+        }
+    }
+
+    /// Encodes and submits a frame, which is copied into `staging_buffer` once drawn.
+    /// Frames of several sub-frames are drawn from the same canvas, summed and averaged.
+    fn submit_frame(
+        &self,
+        sub_frames: &[&[Shape]],
+        staging_buffer: &Buffer,
+    ) -> wgpu::SubmissionIndex {
+        let (gpu_instance, output_buffer) = (&self.gpu_instance, &self.output_buffer);
+        let mut encoder = gpu_instance
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        self.prepare_canvas(&mut encoder);
+        if let [shapes] = sub_frames {
+            self.encode_shapes(&mut encoder, shapes);
+        } else if !sub_frames.is_empty() {
+            let (canvas_buffer, accumulation_buffer) = self.sub_frame_buffers.get_or_init(|| {
+                let device = &gpu_instance.device;
+                let size = output_buffer.size();
+                (
+                    device.create_buffer(&wgpu::BufferDescriptor {
+                        label: Some("Canvas Buffer"),
+                        size,
+                        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+                        mapped_at_creation: false,
+                    }),
+                    device.create_buffer(&wgpu::BufferDescriptor {
+                        label: Some("Accumulation Buffer"),
+                        size: size * 4,
+                        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+                        mapped_at_creation: false,
+                    }),
+                )
+            });
+            encoder.copy_buffer_to_buffer(output_buffer, 0, canvas_buffer, 0, output_buffer.size());
+            encoder.clear_buffer(accumulation_buffer, 0, None);
+            for (i, shapes) in sub_frames.iter().enumerate() {
+                if i > 0 {
+                    encoder.copy_buffer_to_buffer(
+                        canvas_buffer,
+                        0,
+                        output_buffer,
+                        0,
+                        output_buffer.size(),
+                    );
+                }
+                self.encode_shapes(&mut encoder, shapes);
+                self.encode_sub_frame_pass(
+                    &mut encoder,
+                    &gpu_instance.accumulate_compute_pipeline,
+                    accumulation_buffer,
+                    sub_frames.len(),
+                );
+            }
+            self.encode_sub_frame_pass(
+                &mut encoder,
+                &gpu_instance.resolve_compute_pipeline,
+                accumulation_buffer,
+                sub_frames.len(),
+            );
+        }
+        encoder.copy_buffer_to_buffer(output_buffer, 0, staging_buffer, 0, staging_buffer.size());
+
+        gpu_instance.queue.submit(Some(encoder.finish()))
+    }
+
+    /// Sums the output into `accumulation_buffer`, or resolves the sum of `count` sub-frames into it,
+    /// depending on `pipeline`.
+    fn encode_sub_frame_pass(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        pipeline: &wgpu::ComputePipeline,
+        accumulation_buffer: &Buffer,
+        count: usize,
+    ) {
+        let gpu_instance = &self.gpu_instance;
+        let uniform_buffer =
+            gpu_instance
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Sub-frame Uniform Buffer"),
+                    contents: bytemuck::cast_slice(&[
+                        gpu_instance.width,
+                        gpu_instance.height,
+                        count as u32,
+                        0,
+                    ]),
+                    usage: wgpu::BufferUsages::UNIFORM,
+                });
+        let bind_group = gpu_instance
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &pipeline.get_bind_group_layout(0),
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: self.output_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 5,
+                        resource: uniform_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 6,
+                        resource: accumulation_buffer.as_entire_binding(),
+                    },
+                ],
+            });
+
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: None,
+            timestamp_writes: None,
+        });
+        cpass.set_pipeline(pipeline);
+        cpass.set_bind_group(0, &bind_group, &[]);
+        cpass.dispatch_workgroups(
+            gpu_instance.width.div_ceil(8),
+            gpu_instance.height.div_ceil(8),
+            1,
+        );
+    }
+
+    /// Waits for a submitted frame and copies it out of `staging_buffer`.
+    fn read_back(
+        &self,
+        staging_buffer: &Buffer,
+        submission_index: wgpu::SubmissionIndex,
+    ) -> Vec<u8> {
+        let buffer_slice = staging_buffer.slice(..);
+        let (sender, receiver) = flume::bounded(1);
+        buffer_slice.map_async(wgpu::MapMode::Read, move |v| sender.send(v).unwrap());
+
+        self.gpu_instance
+            .device
+            .poll(wgpu::Maintain::WaitForSubmissionIndex(submission_index))
+            .panic_on_timeout();
+
+        if let Ok(Ok(())) = receiver.recv() {
+            let data = buffer_slice.get_mapped_range();
+            let result = bytemuck::cast_slice(&data).to_vec();
+
+            drop(data);
+            staging_buffer.unmap();
+
+            result
+        } else {
+            panic!("failed to run compute on gpu!")
+        }
+    }
+
+    /// Clears or fades the output buffer according to the canvas, before any shape is drawn.
+    fn prepare_canvas(&self, encoder: &mut wgpu::CommandEncoder) {
+        let decay = if self.output_cleared.swap(true, Ordering::Relaxed) {
+            self.canvas.decay()
+        } else {
+            1.0
+        };
+        if decay <= 0.0 {
+            return;
+        }
+
+        let gpu_instance = &self.gpu_instance;
+        let uniform_buffer =
+            gpu_instance
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Canvas Uniform Buffer"),
+                    contents: bytemuck::cast_slice(&[
+                        gpu_instance.width,
+                        gpu_instance.height,
+                        self.canvas.background.to_packed(),
+                        bytemuck::cast(decay),
+                    ]),
+                    usage: wgpu::BufferUsages::UNIFORM,
+                });
+        let bind_group = gpu_instance
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &gpu_instance
+                    .canvas_compute_pipeline
+                    .get_bind_group_layout(0),
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: self.output_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: uniform_buffer.as_entire_binding(),
+                    },
+                ],
+            });
+
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: None,
+            timestamp_writes: None,
+        });
+        cpass.set_pipeline(&gpu_instance.canvas_compute_pipeline);
+        cpass.set_bind_group(0, &bind_group, &[]);
+        cpass.dispatch_workgroups(
+            gpu_instance.width.div_ceil(8),
+            gpu_instance.height.div_ceil(8),
+            1,
+        );
+    }
+}
+impl Renderer for GpuRenderer {
+    fn width(&self) -> u32 {
+        self.gpu_instance.width
+    }
+
+    fn height(&self) -> u32 {
+        self.gpu_instance.height
+    }
+
+    fn canvas(&self) -> Canvas {
+        self.canvas
+    }
+
+    fn set_canvas(&mut self, canvas: Canvas) {
+        self.canvas = canvas;
+    }
+
+    fn antialiasing(&self) -> Antialiasing {
+        self.antialiasing
+    }
+
+    fn set_antialiasing(&mut self, antialiasing: Antialiasing) {
+        self.antialiasing = antialiasing;
+    }
+
+    fn supports_custom_shapes(&self) -> bool {
+        true
+    }
+
+    fn render_frame(&mut self, shapes: &[Shape]) -> Vec<u8> {
+        self.reload_changed_shaders();
+        let submission_index = self.submit_frame(&[shapes], &self.staging_buffers[0]);
+        self.read_back(&self.staging_buffers[0], submission_index)
+    }
+
+    fn render_sub_frames(&mut self, sub_frames: &[Vec<Shape>]) -> Vec<u8> {
+        self.reload_changed_shaders();
+        let sub_frames: Vec<_> = sub_frames.iter().map(Vec::as_slice).collect();
+        let submission_index = self.submit_frame(&sub_frames, &self.staging_buffers[0]);
+        self.read_back(&self.staging_buffers[0], submission_index)
+    }
+
+    /// Keeps several frames in flight: while the GPU draws a frame, a readback thread maps
+    /// the earlier ones in order and hands them to worker threads for `save`.
+    fn render_frames(
+        &mut self,
+        frames: &mut dyn Iterator<Item = Vec<Vec<Shape>>>,
+        save: &(dyn Fn(usize, Vec<u8>) + Sync),
+    ) {
+        self.reload_changed_shaders();
+        let this = &*self;
+        let frames_in_flight = this.staging_buffers.len();
+        let (free_sender, free_receiver) = flume::bounded(frames_in_flight);
+        let (submitted_sender, submitted_receiver) = flume::bounded(frames_in_flight);
+        let (pixels_sender, pixels_receiver) = flume::bounded(frames_in_flight);
+        for slot in 0..frames_in_flight {
+            free_sender.send(slot).unwrap();
+        }
+
+        std::thread::scope(|scope| {
+            scope.spawn(move || {
+                for (i, slot, submission_index) in submitted_receiver {
+                    let pixels = this.read_back(&this.staging_buffers[slot], submission_index);
+                    // The main thread may already be gone if it panicked.
+                    let _ = free_sender.send(slot);
+                    pixels_sender.send((i, pixels)).unwrap();
+                }
+            });
+            for _ in 0..frames_in_flight {
+                let pixels_receiver = pixels_receiver.clone();
+                scope.spawn(move || {
+                    for (i, pixels) in pixels_receiver {
+                        save(i, pixels);
+                    }
+                });
+            }
+
+            for (i, frame) in frames.enumerate() {
+                let slot = free_receiver.recv().unwrap();
+                let sub_frames: Vec<_> = frame.iter().map(Vec::as_slice).collect();
+                let submission_index = this.submit_frame(&sub_frames, &this.staging_buffers[slot]);
+                submitted_sender.send((i, slot, submission_index)).unwrap();
+            }
+            drop(submitted_sender);
+        });
+    }
+}
+
+/// Lists the shapes of `records[range]` overlapping each tile of the canvas, in draw order.
+///
+/// The result starts with a start and length pair for every tile, row by row,
+/// which index into the shape indices that follow them.
+fn bin_tiles(records: &[ShapeRecord], range: Range<usize>, tiles_x: u32, tiles_y: u32) -> Vec<u32> {
+    let mut bins = vec![vec![]; (tiles_x * tiles_y) as usize];
+    for (i, record) in range.clone().zip(&records[range]) {
+        let [x, y, width, height] = record.clip;
+        for tile_y in y / TILE_SIZE..=(y + height - 1) / TILE_SIZE {
+            for tile_x in x / TILE_SIZE..=(x + width - 1) / TILE_SIZE {
+                bins[(tile_y * tiles_x + tile_x) as usize].push(i as u32);
+            }
+        }
+    }
+
+    let header_length = bins.len() * 2;
+    let mut tiles = Vec::with_capacity(header_length + bins.iter().map(Vec::len).sum::<usize>());
+    let mut start = header_length as u32;
+    for bin in &bins {
+        tiles.extend([start, bin.len() as u32]);
+        start += bin.len() as u32;
+    }
+    tiles.extend(bins.into_iter().flatten());
+    tiles
+}
+
+/// How many layers the most deeply nested group of `records` needs, counting masks.
+fn max_group_depth(records: &[ShapeRecord]) -> usize {
+    let (mut depth, mut max_depth) = (0, 0);
+    for record in records {
+        match record.kind {
+            ShapeRecord::GROUP_START | ShapeRecord::GROUP_MASK => {
+                depth += 1;
+                max_depth = max_depth.max(depth);
+            }
+            ShapeRecord::GROUP_END if record.params[0][1] != 0.0 => depth -= 2,
+            ShapeRecord::GROUP_END => depth -= 1,
+            _ => {}
+        }
+    }
+    max_depth
+}
+
+/// Validates and compiles the kernel of a custom shader after the declarations it's written against.
+/// Every custom kernel binds the same three buffers, whether or not it uses its uniforms.
+fn create_custom_pipeline(
+    gpu_instance: &GpuInstance,
+    shader: &CustomShader,
+) -> Result<ComputePipeline, ShaderError> {
+    let device = &gpu_instance.device;
+    let sources = &gpu_instance.shaders;
+    sources.validate_custom(&format!("<custom shader {}>", shader.id()), shader.source())?;
+    let source = sources.custom_module(shader.source());
+    let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Custom Shader"),
+        source: wgpu::ShaderSource::Wgsl(Cow::Owned(source)),
+    });
+    let buffer_entry = |binding, ty| wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Buffer {
+            ty,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    };
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: None,
+        bind_group_layouts: &[
+            &device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[
+                    buffer_entry(0, wgpu::BufferBindingType::Storage { read_only: false }),
+                    buffer_entry(1, wgpu::BufferBindingType::Uniform),
+                    buffer_entry(2, wgpu::BufferBindingType::Uniform),
+                ],
+            }),
+        ],
+        push_constant_ranges: &[],
+    });
+    Ok(
+        device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Custom Pipeline"),
+            layout: Some(&layout),
+            module: &module,
+            entry_point: "main",
+            compilation_options: Default::default(),
+            cache: None,
+        }),
+    )
+}
+
+/// Uploads `image`, scaled down if it's bigger than the device's textures can be,
+/// and returns how much it was scaled by across and down.
+fn create_image_texture(
+    gpu_instance: &GpuInstance,
+    image: &ImageSource,
+) -> (TextureView, (f32, f32)) {
+    let limit = gpu_instance.device.limits().max_texture_dimension_2d;
+    let scaled;
+    let pixels = if image.width() > limit || image.height() > limit {
+        let scale = limit as f64 / image.width().max(image.height()) as f64;
+        let (width, height) = (
+            ((image.width() as f64 * scale) as u32).clamp(1, limit),
+            ((image.height() as f64 * scale) as u32).clamp(1, limit),
+        );
+        scaled = image::imageops::resize(
+            image.pixels(),
+            width,
+            height,
+            image::imageops::FilterType::Triangle,
+        );
+        &scaled
+    } else {
+        image.pixels()
+    };
+    let scale = (
+        pixels.width() as f32 / image.width() as f32,
+        pixels.height() as f32 / image.height() as f32,
+    );
+    let view = gpu_instance
+        .device
+        .create_texture_with_data(
+            &gpu_instance.queue,
+            &wgpu::TextureDescriptor {
+                label: Some("Image Texture"),
+                size: wgpu::Extent3d {
+                    width: pixels.width(),
+                    height: pixels.height(),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Uint,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            pixels,
+        )
+        .create_view(&Default::default());
+    (view, scale)
+}
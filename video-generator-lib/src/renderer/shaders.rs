@@ -0,0 +1,280 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Why shaders couldn't be loaded.
+#[derive(Debug)]
+pub enum ShaderError {
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    /// WGSL which doesn't parse, or breaks WGSL's rules.
+    /// `line` and `column` start at 1 within `file`, and are 0 for errors naga can't place.
+    Invalid {
+        file: String,
+        line: u32,
+        column: u32,
+        message: String,
+    },
+}
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShaderError::Io { path, error } => {
+                write!(f, "couldn't read {}: {error}", path.display())
+            }
+            ShaderError::Invalid {
+                file,
+                line,
+                column,
+                message,
+            } => write!(f, "{file}:{line}:{column}: {message}"),
+        }
+    }
+}
+impl std::error::Error for ShaderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ShaderError::Io { error, .. } => Some(error),
+            ShaderError::Invalid { .. } => None,
+        }
+    }
+}
+
+/// The WGSL the GPU renderer's kernels are compiled from, one string per file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderSources {
+    /// Helpers which every shader but `polygon` is compiled after.
+    pub common: String,
+    pub shape: String,
+    pub canvas: String,
+    pub polygon: String,
+    pub effect: String,
+    /// Declarations custom shaders are compiled after, following `common`.
+    pub custom: String,
+    /// Compiled after `common` and `shape`, whose functions it draws shapes with.
+    pub raster: String,
+}
+impl ShaderSources {
+    pub const COMMON_FILE: &'static str = "shader-common.wgsl";
+    pub const SHAPE_FILE: &'static str = "shader.wgsl";
+    pub const CANVAS_FILE: &'static str = "shader-canvas.wgsl";
+    pub const POLYGON_FILE: &'static str = "shader-polygon.wgsl";
+    pub const EFFECT_FILE: &'static str = "shader-effects.wgsl";
+    pub const CUSTOM_FILE: &'static str = "shader-custom.wgsl";
+    pub const RASTER_FILE: &'static str = "shader-raster.wgsl";
+
+    /// The shaders as the library was built with them.
+    pub fn built_in() -> Self {
+        Self {
+            common: include_str!("../shader-common.wgsl").into(),
+            shape: include_str!("../shader.wgsl").into(),
+            canvas: include_str!("../shader-canvas.wgsl").into(),
+            polygon: include_str!("../shader-polygon.wgsl").into(),
+            effect: include_str!("../shader-effects.wgsl").into(),
+            custom: include_str!("../shader-custom.wgsl").into(),
+            raster: include_str!("../shader-raster.wgsl").into(),
+        }
+    }
+
+    /// Reads the shaders from files named as in the library's `src` directory, and validates them.
+    pub fn load(directory: impl AsRef<Path>) -> Result<Self, ShaderError> {
+        let directory = directory.as_ref();
+        let read = |name| {
+            let path = directory.join(name);
+            std::fs::read_to_string(&path).map_err(|error| ShaderError::Io { path, error })
+        };
+        let sources = Self {
+            common: read(Self::COMMON_FILE)?,
+            shape: read(Self::SHAPE_FILE)?,
+            canvas: read(Self::CANVAS_FILE)?,
+            polygon: read(Self::POLYGON_FILE)?,
+            effect: read(Self::EFFECT_FILE)?,
+            custom: read(Self::CUSTOM_FILE)?,
+            raster: read(Self::RASTER_FILE)?,
+        };
+        sources.validate()?;
+        Ok(sources)
+    }
+
+    /// Checks every shader parses and validates, so pipelines can be created from them without panicking.
+    pub fn validate(&self) -> Result<(), ShaderError> {
+        validate(&[
+            (Self::COMMON_FILE, &self.common),
+            (Self::SHAPE_FILE, &self.shape),
+        ])?;
+        validate(&[
+            (Self::COMMON_FILE, &self.common),
+            (Self::CANVAS_FILE, &self.canvas),
+        ])?;
+        validate(&[(Self::POLYGON_FILE, &self.polygon)])?;
+        validate(&[
+            (Self::COMMON_FILE, &self.common),
+            (Self::EFFECT_FILE, &self.effect),
+        ])?;
+        // The custom declarations only need a kernel to be compiled.
+        validate(&[
+            (Self::COMMON_FILE, &self.common),
+            (Self::CUSTOM_FILE, &self.custom),
+        ])?;
+        validate(&[
+            (Self::COMMON_FILE, &self.common),
+            (Self::SHAPE_FILE, &self.shape),
+            (Self::RASTER_FILE, &self.raster),
+        ])?;
+        Ok(())
+    }
+
+    pub fn shape_module(&self) -> String {
+        self.common.clone() + &self.shape
+    }
+
+    pub fn canvas_module(&self) -> String {
+        self.common.clone() + &self.canvas
+    }
+
+    pub fn polygon_module(&self) -> String {
+        self.polygon.clone()
+    }
+
+    pub fn effect_module(&self) -> String {
+        self.common.clone() + &self.effect
+    }
+
+    pub fn raster_module(&self) -> String {
+        self.common.clone() + &self.shape + &self.raster
+    }
+
+    /// A custom shader's source after the declarations it's written against.
+    pub fn custom_module(&self, source: &str) -> String {
+        self.common.clone() + &self.custom + source
+    }
+
+    /// Checks a custom shader, whose errors are reported against `name`.
+    pub fn validate_custom(&self, name: &str, source: &str) -> Result<(), ShaderError> {
+        let module = validate(&[
+            (Self::COMMON_FILE, &self.common),
+            (Self::CUSTOM_FILE, &self.custom),
+            (name, source),
+        ])?;
+        let has_main = module
+            .entry_points
+            .iter()
+            .any(|x| x.name == "main" && x.stage == naga::ShaderStage::Compute);
+        if !has_main {
+            return Err(ShaderError::Invalid {
+                file: name.to_string(),
+                line: 0,
+                column: 0,
+                message: "there's no compute entry point named `main`".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+impl Default for ShaderSources {
+    fn default() -> Self {
+        Self::built_in()
+    }
+}
+
+/// Parses and validates the files of one shader module, joined in order,
+/// placing any error in the file it's in.
+fn validate(files: &[(&str, &str)]) -> Result<naga::Module, ShaderError> {
+    let source: String = files.iter().map(|(_, x)| *x).collect();
+    let (location, message) = match naga::front::wgsl::parse_str(&source) {
+        Ok(module) => {
+            let mut validator = naga::valid::Validator::new(
+                naga::valid::ValidationFlags::all(),
+                naga::valid::Capabilities::default(),
+            );
+            match validator.validate(&module) {
+                Ok(_) => return Ok(module),
+                Err(error) => {
+                    // The outermost error only names the function, so its causes are included.
+                    let mut message = error.as_inner().to_string();
+                    let mut cause = std::error::Error::source(error.as_inner());
+                    while let Some(inner) = cause {
+                        message += &format!(": {inner}");
+                        cause = inner.source();
+                    }
+                    (error.location(&source), message)
+                }
+            }
+        }
+        Err(error) => (error.location(&source), error.message().to_string()),
+    };
+
+    let Some(location) = location else {
+        return Err(ShaderError::Invalid {
+            file: files.last().map_or("", |x| x.0).to_string(),
+            line: 0,
+            column: 0,
+            message,
+        });
+    };
+    let mut line = location.line_number;
+    for (i, (file, text)) in files.iter().enumerate() {
+        // Files are joined without separators, so a file not ending in a newline shares a line with the next.
+        let lines = text.matches('\n').count() as u32;
+        if line <= lines || i == files.len() - 1 {
+            return Err(ShaderError::Invalid {
+                file: file.to_string(),
+                line,
+                column: location.line_position,
+                message,
+            });
+        }
+        line -= lines;
+    }
+    unreachable!("shader modules have at least one file")
+}
+
+/// The shader files in a directory, and when each was last changed,
+/// so the GPU renderer can reload them as they're edited.
+#[derive(Debug, Clone)]
+pub(crate) struct ShaderWatch {
+    pub directory: PathBuf,
+    modified: Vec<Option<SystemTime>>,
+}
+impl ShaderWatch {
+    const FILES: [&'static str; 7] = [
+        ShaderSources::COMMON_FILE,
+        ShaderSources::SHAPE_FILE,
+        ShaderSources::CANVAS_FILE,
+        ShaderSources::POLYGON_FILE,
+        ShaderSources::EFFECT_FILE,
+        ShaderSources::CUSTOM_FILE,
+        ShaderSources::RASTER_FILE,
+    ];
+
+    pub fn new(directory: PathBuf) -> Self {
+        let modified = Self::modified(&directory);
+        Self {
+            directory,
+            modified,
+        }
+    }
+
+    fn modified(directory: &Path) -> Vec<Option<SystemTime>> {
+        Self::FILES
+            .iter()
+            .map(|x| {
+                std::fs::metadata(directory.join(x))
+                    .and_then(|x| x.modified())
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// Whether any file has changed since the last call.
+    pub fn changed(&mut self) -> bool {
+        let modified = Self::modified(&self.directory);
+        let changed = modified != self.modified;
+        self.modified = modified;
+        changed
+    }
+}
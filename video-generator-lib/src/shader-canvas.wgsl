@@ -0,0 +1,117 @@
+@group(0)
+@binding(0)
+var<storage, read_write> v_indices_output: array<u32>;
+
+struct Uniforms {
+    width: u32,
+    height: u32,
+    background: u32,
+    decay: f32,
+}
+
+@group(0)
+@binding(1)
+var<uniform> uniforms: Uniforms;
+
+@compute
+@workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (global_id.x >= uniforms.width || global_id.y >= uniforms.height) {
+        return;
+    }
+    let id: u32 = global_id.y*uniforms.width + global_id.x;
+
+    if (uniforms.decay >= 1.0) {
+        v_indices_output[id] = uniforms.background;
+    } else {
+        let old = unpack_colour(v_indices_output[id]);
+        let background = unpack_colour(uniforms.background);
+        v_indices_output[id] = pack_colour(old + (background - old) * uniforms.decay);
+    }
+}
+
+// Follows `MaskMode` in shapes/group.rs, offset by one so 0 is no mask.
+const MASK_NONE: u32 = 0u;
+const MASK_ALPHA: u32 = 1u;
+const MASK_LUMINANCE: u32 = 2u;
+
+struct GroupUniforms {
+    width: u32,
+    blend_mode: u32,
+    mask: u32,
+    opacity: f32,
+    // The box composited: x, y, width and height.
+    clip: vec4<u32>,
+}
+
+@group(0)
+@binding(2)
+var<storage, read> layer: array<u32>;
+
+@group(0)
+@binding(3)
+var<storage, read> mask: array<u32>;
+
+@group(0)
+@binding(4)
+var<uniform> group: GroupUniforms;
+
+// Composites a group's layer through its mask onto the layer beneath, dispatched over the clipped box.
+@compute
+@workgroup_size(8, 8)
+fn composite_group(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (global_id.x >= group.clip.z || global_id.y >= group.clip.w) {
+        return;
+    }
+    let id: u32 = (group.clip.y + global_id.y)*group.width + group.clip.x + global_id.x;
+
+    var coverage = group.opacity;
+    if (group.mask == MASK_ALPHA) {
+        coverage *= unpack_colour(mask[id]).a;
+    } else if (group.mask == MASK_LUMINANCE) {
+        let m = unpack_colour(mask[id]);
+        coverage *= luminance(m.rgb) * m.a;
+    }
+    if (coverage <= 0.0) {
+        return;
+    }
+    v_indices_output[id] = composite(v_indices_output[id], with_coverage(layer[id], coverage), group.blend_mode);
+}
+
+struct SubFrameUniforms {
+    width: u32,
+    height: u32,
+    count: u32,
+    _padding: u32,
+}
+
+@group(0)
+@binding(5)
+var<uniform> sub_frames: SubFrameUniforms;
+
+// The sum of the sub-frames drawn so far, in linear light with alpha premultiplied.
+@group(0)
+@binding(6)
+var<storage, read_write> accumulation: array<vec4<f32>>;
+
+// Adds the sub-frame just drawn to the sum.
+@compute
+@workgroup_size(8, 8)
+fn accumulate_sub_frame(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (global_id.x >= sub_frames.width || global_id.y >= sub_frames.height) {
+        return;
+    }
+    let id: u32 = global_id.y*sub_frames.width + global_id.x;
+    accumulation[id] += premultiplied_linear(v_indices_output[id]);
+}
+
+// Replaces the output with the average of the sub-frames.
+@compute
+@workgroup_size(8, 8)
+fn resolve_sub_frames(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (global_id.x >= sub_frames.width || global_id.y >= sub_frames.height) {
+        return;
+    }
+    let id: u32 = global_id.y*sub_frames.width + global_id.x;
+    v_indices_output[id] = from_premultiplied_linear(accumulation[id] / f32(sub_frames.count));
+}
@@ -0,0 +1,92 @@
+// Helpers shared by every kernel. Each shader is compiled with this file prepended.
+
+fn unpack_colour(colour: u32) -> vec4<f32> {
+    return vec4<f32>(
+        f32(colour & 0xFFu),
+        f32((colour >> 8u) & 0xFFu),
+        f32((colour >> 16u) & 0xFFu),
+        f32(colour >> 24u),
+    ) / 255.0;
+}
+
+fn pack_colour(colour: vec4<f32>) -> u32 {
+    let c = vec4<u32>(floor(clamp(colour, vec4<f32>(0.0), vec4<f32>(1.0)) * 255.0 + 0.5));
+    return c.x | (c.y << 8u) | (c.z << 16u) | (c.w << 24u);
+}
+
+// The sRGB transfer function, matching color.rs.
+fn srgb_to_linear(rgb: vec3<f32>) -> vec3<f32> {
+    return select(pow((rgb + 0.055) / 1.055, vec3<f32>(2.4)), rgb / 12.92, rgb <= vec3<f32>(0.04045));
+}
+
+fn linear_to_srgb(rgb: vec3<f32>) -> vec3<f32> {
+    return select(1.055 * pow(rgb, vec3<f32>(1.0 / 2.4)) - 0.055, rgb * 12.92, rgb <= vec3<f32>(0.0031308));
+}
+
+// Colours in linear light with alpha premultiplied, where mixing and averaging them
+// matches how light adds up.
+fn premultiplied_linear(colour: u32) -> vec4<f32> {
+    let c = unpack_colour(colour);
+    return vec4<f32>(srgb_to_linear(c.rgb)*c.a, c.a);
+}
+
+fn from_premultiplied_linear(colour: vec4<f32>) -> u32 {
+    if (colour.a <= 0.0) {
+        return 0u;
+    }
+    return pack_colour(vec4<f32>(linear_to_srgb(colour.rgb / colour.a), colour.a));
+}
+
+// Scales the alpha of `colour` by how much of the pixel a shape covers.
+fn with_coverage(colour: u32, coverage: f32) -> u32 {
+    let alpha = u32(floor(f32(colour >> 24u) * coverage + 0.5));
+    return (colour & 0x00FFFFFFu) | (alpha << 24u);
+}
+
+// Rec. 709 relative luminance, from 0.0 for black to 1.0 for white.
+fn luminance(rgb: vec3<f32>) -> f32 {
+    return rgb.r * 0.2126 + rgb.g * 0.7152 + rgb.b * 0.0722;
+}
+
+// Follows the order of `BlendMode` in shapes.rs.
+const BLEND_NORMAL: u32 = 0u;
+const BLEND_MULTIPLY: u32 = 1u;
+const BLEND_SCREEN: u32 = 2u;
+const BLEND_ADDITIVE: u32 = 3u;
+const BLEND_DARKEN: u32 = 4u;
+const BLEND_LIGHTEN: u32 = 5u;
+
+fn blend_channels(mode: u32, backdrop: vec3<f32>, source: vec3<f32>) -> vec3<f32> {
+    switch mode {
+        case BLEND_MULTIPLY: { return backdrop * source; }
+        case BLEND_SCREEN: { return backdrop + source - backdrop * source; }
+        case BLEND_ADDITIVE: { return min(backdrop + source, vec3<f32>(1.0)); }
+        case BLEND_DARKEN: { return min(backdrop, source); }
+        case BLEND_LIGHTEN: { return max(backdrop, source); }
+        default: { return source; }
+    }
+}
+
+// Source-over compositing of straight-alpha colours, with the colour channels mixed by `mode`
+// in linear light.
+fn composite(backdrop: u32, source: u32, mode: u32) -> u32 {
+    let source_alpha = source >> 24u;
+    if (source_alpha == 0u) {
+        return backdrop;
+    }
+    if (source_alpha == 0xFFu && mode == BLEND_NORMAL) {
+        return source;
+    }
+
+    let cb = unpack_colour(backdrop);
+    let cs = unpack_colour(source);
+    let alpha = cs.a + cb.a * (1.0 - cs.a);
+    if (alpha <= 0.0) {
+        return 0u;
+    }
+    let backdrop_rgb = srgb_to_linear(cb.rgb);
+    let source_rgb = srgb_to_linear(cs.rgb);
+    let mixed = (1.0 - cb.a) * source_rgb + cb.a * blend_channels(mode, backdrop_rgb, source_rgb);
+    let rgb = (cs.a * mixed + cb.a * (1.0 - cs.a) * backdrop_rgb) / alpha;
+    return pack_colour(vec4<f32>(linear_to_srgb(rgb), alpha));
+}
@@ -0,0 +1,45 @@
+// Declarations for the kernels of custom shapes, which are compiled after shader-common.wgsl
+// and this file. Each defines `main`, run with a workgroup size of 8 by 8 over the shape's
+// clipped box, and may declare its own uniforms at binding 2.
+
+@group(0)
+@binding(0)
+var<storage, read_write> v_indices_output: array<u32>;
+
+struct Region {
+    // The canvas's size.
+    width: u32,
+    height: u32,
+    blend_mode: u32,
+    _padding: u32,
+    // The clipped box on the canvas: x, y, width, height.
+    clip: vec4<u32>,
+    // The shape's top left corner and size, which may be partly off the canvas.
+    position: vec2<f32>,
+    size: vec2<f32>,
+}
+
+@group(0)
+@binding(1)
+var<uniform> region: Region;
+
+// Whether the invocation has a pixel of the clipped box to draw, since whole workgroups run.
+fn in_region(global_id: vec3<u32>) -> bool {
+    return global_id.x < region.clip.z && global_id.y < region.clip.w;
+}
+
+// The canvas pixel the invocation draws.
+fn region_pixel(global_id: vec3<u32>) -> vec2<u32> {
+    return region.clip.xy + global_id.xy;
+}
+
+// Where the centre of `pixel` is in the shape, from (0, 0) at its top left to (1, 1) at its bottom right.
+fn region_uv(pixel: vec2<u32>) -> vec2<f32> {
+    return (vec2<f32>(pixel) + 0.5 - region.position) / region.size;
+}
+
+// Composites a straight-alpha, sRGB encoded colour onto `pixel` with the shape's blend mode.
+fn draw_pixel(pixel: vec2<u32>, colour: vec4<f32>) {
+    let id = pixel.y * region.width + pixel.x;
+    v_indices_output[id] = composite(v_indices_output[id], pack_colour(colour), region.blend_mode);
+}
@@ -0,0 +1,137 @@
+// Follows the kinds of `EffectPass` in shapes/effect.rs.
+const PASS_BLUR_HORIZONTAL: u32 = 0u;
+const PASS_BLUR_VERTICAL: u32 = 1u;
+const PASS_BRIGHT: u32 = 2u;
+const PASS_ADD_GLOW: u32 = 3u;
+const PASS_VIGNETTE: u32 = 4u;
+const PASS_FILM_GRAIN: u32 = 5u;
+const PASS_CHROMATIC_ABERRATION: u32 = 6u;
+const PASS_COLOUR_ADJUST: u32 = 7u;
+
+@group(0)
+@binding(0)
+var<storage, read_write> target_pixels: array<u32>;
+
+struct Uniforms {
+    width: u32,
+    height: u32,
+    pass_kind: u32,
+    first_weight: u32,
+    weight_count: u32,
+    _padding_0: u32,
+    _padding_1: u32,
+    _padding_2: u32,
+    // The effect's own parameters, see `Effect::to_records`.
+    params: vec4<f32>,
+}
+
+@group(0)
+@binding(1)
+var<uniform> uniforms: Uniforms;
+
+// What passes which don't change pixels in place read from.
+@group(0)
+@binding(2)
+var<storage, read> source_pixels: array<u32>;
+
+@group(0)
+@binding(3)
+var<storage, read> weights: array<f32>;
+
+fn source_pixel(x: i32, y: i32) -> vec4<f32> {
+    let clamped = clamp(vec2<i32>(x, y), vec2<i32>(0), vec2<i32>(i32(uniforms.width) - 1, i32(uniforms.height) - 1));
+    return unpack_colour(source_pixels[u32(clamped.y)*uniforms.width + u32(clamped.x)]);
+}
+
+// One direction of a separable Gaussian blur, mixed with alpha premultiplied.
+fn blur(pixel: vec2<i32>, direction: vec2<i32>) -> u32 {
+    let reach = i32(uniforms.weight_count / 2u);
+    var sum = vec4<f32>(0.0);
+    for (var i = 0; i < i32(uniforms.weight_count); i++) {
+        let position = pixel + direction * (i - reach);
+        let c = source_pixel(position.x, position.y);
+        sum += vec4<f32>(c.rgb * c.a, c.a) * weights[uniforms.first_weight + u32(i)];
+    }
+    if (sum.a <= 0.0) {
+        return 0u;
+    }
+    return pack_colour(vec4<f32>(sum.rgb / sum.a, sum.a));
+}
+
+// A well mixed hash of 32 bits.
+fn hash(x: u32) -> u32 {
+    var h = x;
+    h ^= h >> 16u;
+    h *= 0x7feb352du;
+    h ^= h >> 15u;
+    h *= 0x846ca68bu;
+    h ^= h >> 16u;
+    return h;
+}
+
+@compute
+@workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (global_id.x >= uniforms.width || global_id.y >= uniforms.height) {
+        return;
+    }
+    let id: u32 = global_id.y*uniforms.width + global_id.x;
+    let pixel = vec2<i32>(global_id.xy);
+    let size = vec2<f32>(f32(uniforms.width), f32(uniforms.height));
+    let params = uniforms.params;
+
+    switch uniforms.pass_kind {
+        case PASS_BLUR_HORIZONTAL: {
+            target_pixels[id] = blur(pixel, vec2<i32>(1, 0));
+        }
+        case PASS_BLUR_VERTICAL: {
+            target_pixels[id] = blur(pixel, vec2<i32>(0, 1));
+        }
+        case PASS_BRIGHT: {
+            // Fades in from the threshold to white, so the glow doesn't start with a hard edge.
+            let c = unpack_colour(source_pixels[id]);
+            let brightness = clamp((luminance(c.rgb) - params.y) / max(1.0 - params.y, 0.0001), 0.0, 1.0);
+            target_pixels[id] = pack_colour(vec4<f32>(c.rgb, c.a * brightness));
+        }
+        case PASS_ADD_GLOW: {
+            target_pixels[id] = composite(target_pixels[id], with_coverage(source_pixels[id], params.z), BLEND_ADDITIVE);
+        }
+        case PASS_VIGNETTE: {
+            let offset = ((vec2<f32>(global_id.xy) + 0.5) / size - 0.5) * 2.0;
+            let distance = sqrt(offset.x * offset.x + offset.y * offset.y);
+            let t = clamp((distance - params.z) / max(params.w, 0.000001), 0.0, 1.0);
+            let darkness = params.y * t * t * (3.0 - 2.0 * t);
+            let c = unpack_colour(target_pixels[id]);
+            target_pixels[id] = pack_colour(vec4<f32>(c.rgb * (1.0 - darkness), c.a));
+        }
+        case PASS_FILM_GRAIN: {
+            let noise = f32(hash(global_id.x ^ hash(global_id.y ^ hash(u32(params.z)))) >> 8u) / 16777216.0;
+            let c = unpack_colour(target_pixels[id]);
+            target_pixels[id] = pack_colour(vec4<f32>(c.rgb + (noise * 2.0 - 1.0) * params.y, c.a));
+        }
+        case PASS_CHROMATIC_ABERRATION: {
+            let centre = size * 0.5;
+            let position = vec2<f32>(global_id.xy) + 0.5;
+            let shift = (position - centre) / centre * params.y;
+            let red_position = vec2<i32>(floor(position + shift));
+            let blue_position = vec2<i32>(floor(position - shift));
+            let red = source_pixel(red_position.x, red_position.y);
+            let green = source_pixel(pixel.x, pixel.y);
+            let blue = source_pixel(blue_position.x, blue_position.y);
+            let alpha = max(max(red.a, green.a), blue.a);
+            if (alpha <= 0.0) {
+                target_pixels[id] = 0u;
+            } else {
+                let rgb = vec3<f32>(red.r * red.a, green.g * green.a, blue.b * blue.a) / alpha;
+                target_pixels[id] = pack_colour(vec4<f32>(rgb, alpha));
+            }
+        }
+        case PASS_COLOUR_ADJUST: {
+            let c = unpack_colour(target_pixels[id]);
+            let contrasted = (c.rgb - 0.5) * params.z + 0.5 + params.y;
+            let grey = luminance(contrasted);
+            target_pixels[id] = pack_colour(vec4<f32>(grey + (contrasted - grey) * params.w, c.a));
+        }
+        default: {}
+    }
+}
@@ -0,0 +1,21 @@
+struct Uniforms {
+    width: u32,
+    height: u32,
+}
+
+@group(0)
+@binding(0)
+var<uniform> uniforms: Uniforms;
+
+// Maps a position in pixels onto clip space.
+@vertex
+fn vertex(@location(0) position: vec2<f32>) -> @builtin(position) vec4<f32> {
+    let clip = position / vec2<f32>(f32(uniforms.width), f32(uniforms.height))*2.0 - 1.0;
+    return vec4<f32>(clip.x, -clip.y, 0.0, 1.0);
+}
+
+// Marks every covered sample, so resolving leaves the fraction of each pixel covered.
+@fragment
+fn fragment() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0);
+}
@@ -0,0 +1,103 @@
+// Draws shapes as instanced quads through a render pipeline, compiled after shader-common.wgsl and
+// shader.wgsl. Each quad covers a shape's clipped box, and its fragments are shaded once per sample
+// and discarded outside the shape, so multisampling antialiases its edges.
+//
+// The render target holds linear, premultiplied colours, which hardware blending composites
+// source-over just as `composite` does in linear light. Each run starts by drawing the canvas
+// into the target and ends by storing the resolved target back into it.
+
+// The canvas as the kernels left it, drawn into the render target by `canvas_fragment`.
+@group(0)
+@binding(9)
+var<storage, read> canvas: array<u32>;
+
+// The resolved render target, stored back into the canvas by `store_raster`.
+@group(0)
+@binding(10)
+var raster_texture: texture_2d<f32>;
+
+struct RasterVertex {
+    @builtin(position) position: vec4<f32>,
+    // Where on the canvas the sample is, in pixels.
+    @location(0) @interpolate(linear, sample) point: vec2<f32>,
+    @location(1) @interpolate(flat) shape_index: u32,
+}
+
+// A pixel's position on the canvas in clip space.
+fn clip_position(point: vec2<f32>) -> vec4<f32> {
+    let size = vec2<f32>(f32(uniforms.width), f32(uniforms.height));
+    return vec4<f32>(point.x / size.x*2.0 - 1.0, 1.0 - point.y / size.y*2.0, 0.0, 1.0);
+}
+
+// One corner of the quad over a shape's clipped box, drawn as a strip of four vertices.
+// Instances count from the first shape of the run, `uniforms.shape_index`.
+@vertex
+fn raster_vertex(
+    @builtin(vertex_index) vertex_index: u32,
+    @builtin(instance_index) instance_index: u32,
+) -> RasterVertex {
+    let shape_index = uniforms.shape_index + instance_index;
+    let clip = vec4<f32>(shapes[shape_index].clip);
+    let corner = vec2<f32>(f32(vertex_index & 1u), f32(vertex_index >> 1u));
+    let point = clip.xy + corner*clip.zw;
+
+    var output: RasterVertex;
+    output.position = clip_position(point);
+    output.point = point;
+    output.shape_index = shape_index;
+    return output;
+}
+
+// The shape's colour at one sample, with colours taken at the centre of the pixel like the kernels do.
+@fragment
+fn raster_fragment(input: RasterVertex) -> @location(0) vec4<f32> {
+    let shape = shapes[input.shape_index];
+    var inside = true;
+    switch shape.kind {
+        case SHAPE_CIRCLE: {
+            // Hard-edged circles are offset from their centre by the kernels, so ask them.
+            if (uniforms.antialiasing == 0u) {
+                inside = circle_coverage(shape, vec2<u32>(input.point)) > 0.0;
+            } else {
+                inside = length(input.point - shape.params[0].xy) <= shape.params[0].z;
+            }
+        }
+        case SHAPE_RECTANGLE: {}
+        default: { inside = signed_distance(shape, input.point) <= 0.0; }
+    }
+    if (!inside) {
+        discard;
+    }
+
+    let pixel = vec2<u32>(input.point);
+    var colour = shape.colour;
+    if (shape.kind == SHAPE_IMAGE) {
+        colour = image_colour(shape, pixel);
+    } else if (shape.gradient != 0u) {
+        colour = gradient_colour(gradients[shape.gradient - 1u], vec2<f32>(pixel) + 0.5);
+    }
+    return premultiplied_linear(colour);
+}
+
+// A triangle covering the whole canvas.
+@vertex
+fn canvas_vertex(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    let corner = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    return vec4<f32>(corner.x*2.0 - 1.0, 1.0 - corner.y*2.0, 0.0, 1.0);
+}
+
+@fragment
+fn canvas_fragment(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let pixel = vec2<u32>(position.xy);
+    return premultiplied_linear(canvas[pixel.y*uniforms.width + pixel.x]);
+}
+
+@compute
+@workgroup_size(8, 8)
+fn store_raster(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (global_id.x >= uniforms.width || global_id.y >= uniforms.height) {
+        return;
+    }
+    let colour = textureLoad(raster_texture, global_id.xy, 0);
+    v_indices_output[global_id.y*uniforms.width + global_id.x] = from_premultiplied_linear(colour);
+}
@@ -3,26 +3,505 @@
 var<storage, read_write> v_indices_output: array<u32>;
 
 struct Uniforms {
-    radius: f32,
     width: u32,
-    offset_x: u32,
-    offset_y: u32,
-    colour: u32,
+    height: u32,
+    antialiasing: u32,
+    samples: u32,
+    // Tiles per row, for `main_tiled`.
+    tiles_x: u32,
+    // The shape drawn by `main`.
+    shape_index: u32,
 }
 
 @group(0)
 @binding(1)
 var<uniform> uniforms: Uniforms;
 
+// Laid out like `ShapeRecord` in shapes.rs.
+struct Shape {
+    kind: u32,
+    colour: u32,
+    blend_mode: u32,
+    // 0 to fill with `colour`, otherwise one more than the index of the gradient to fill with.
+    gradient: u32,
+    // The clipped box on the canvas: x, y, width, height.
+    clip: vec4<u32>,
+    // The unclipped corner of the bounding box, which may be off the canvas.
+    origin: vec2<i32>,
+    params: array<vec4<f32>, 3>,
+}
+
+@group(0)
+@binding(2)
+var<storage, read> shapes: array<Shape>;
+
+// For each tile, the start and length of its shape indices, followed by the indices themselves.
+@group(0)
+@binding(3)
+var<storage, read> tiles: array<u32>;
+
+// The points of shapes with any number of them, such as polylines, which refer to them by index.
+@group(0)
+@binding(4)
+var<storage, read> points: array<vec2<f32>>;
+
+// The coverage of the polygon being drawn, rasterized by the polygon render pipeline.
+@group(0)
+@binding(5)
+var coverage_texture: texture_2d<f32>;
+
+// Laid out like `GradientRecord` in shapes/fill.rs.
+struct Gradient {
+    kind: u32,
+    first_stop: u32,
+    stop_count: u32,
+    params: vec4<f32>,
+}
+
+struct GradientStop {
+    offset: f32,
+    colour: u32,
+}
+
+@group(0)
+@binding(6)
+var<storage, read> gradients: array<Gradient>;
+
+@group(0)
+@binding(7)
+var<storage, read> stops: array<GradientStop>;
+
+// The picture of the image being drawn, one byte per channel.
+@group(0)
+@binding(8)
+var image_texture: texture_2d<u32>;
+
+const TILE_SIZE: u32 = 8u;
+
+const SHAPE_CIRCLE: u32 = 0u;
+const SHAPE_RECTANGLE: u32 = 1u;
+const SHAPE_ROTATED_RECTANGLE: u32 = 2u;
+const SHAPE_ELLIPSE: u32 = 3u;
+const SHAPE_ROUNDED_RECTANGLE: u32 = 4u;
+const SHAPE_POLYLINE: u32 = 5u;
+const SHAPE_PATH: u32 = 6u;
+const SHAPE_POLYGON: u32 = 7u;
+const SHAPE_CIRCLE_OUTLINE: u32 = 8u;
+const SHAPE_RECTANGLE_OUTLINE: u32 = 9u;
+const SHAPE_IMAGE: u32 = 10u;
+
+// Follows the order of `ImageFilter` in shapes/sprite.rs.
+const IMAGE_NEAREST: f32 = 0.0;
+
+// Follows the kinds of `GradientRecord` in shapes/fill.rs.
+const GRADIENT_LINEAR: u32 = 0u;
+const GRADIENT_RADIAL: u32 = 1u;
+const GRADIENT_CONIC: u32 = 2u;
+
+const TAU: f32 = 6.283185307179586;
+
+const CAP_BUTT: u32 = 0u;
+const CAP_ROUND: u32 = 1u;
+const CAP_SQUARE: u32 = 2u;
+
+const JOIN_MITER: u32 = 0u;
+const JOIN_ROUND: u32 = 1u;
+const JOIN_BEVEL: u32 = 2u;
+
+const FILL_NON_ZERO: u32 = 0u;
+const FILL_EVEN_ODD: u32 = 1u;
+
+fn circle_coverage(shape: Shape, pixel: vec2<u32>) -> f32 {
+    let centre = shape.params[0].xy;
+    let radius = shape.params[0].z;
+
+    if (uniforms.antialiasing == 0u) {
+        let x: f32 = f32(i32(pixel.x) - shape.origin.x) - radius;
+        let y: f32 = f32(i32(pixel.y) - shape.origin.y) - radius;
+        return select(0.0, 1.0, x*x + y*y <= radius*radius);
+    }
+
+    if (uniforms.antialiasing == 1u) {
+        let distance = length(vec2<f32>(pixel) + 0.5 - centre) - radius;
+        return clamp(0.5 - distance, 0.0, 1.0);
+    }
+
+    var inside = 0u;
+    for (var i = 0u; i < uniforms.samples; i++) {
+        for (var j = 0u; j < uniforms.samples; j++) {
+            let sample = (vec2<f32>(f32(j), f32(i)) + 0.5) / f32(uniforms.samples);
+            let offset = vec2<f32>(pixel) + sample - centre;
+            if (dot(offset, offset) <= radius*radius) {
+                inside++;
+            }
+        }
+    }
+    return f32(inside) / f32(uniforms.samples*uniforms.samples);
+}
+
+// The signed distance to a box centred on the origin with `radius` rounded corners.
+fn box_distance(point: vec2<f32>, half_size: vec2<f32>, radius: f32) -> f32 {
+    let q = abs(point) - half_size + radius;
+    return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+}
+
+// The unit vector from `start` towards `end`.
+fn unit_vector(start: vec2<f32>, end: vec2<f32>) -> vec2<f32> {
+    return (end - start) / length(end - start);
+}
+
+// The distance to the corner filled in where a polyline turns, from `offset` relative to the vertex.
+fn join_distance(
+    offset: vec2<f32>,
+    incoming: vec2<f32>,
+    outgoing: vec2<f32>,
+    half_width: f32,
+    join: u32,
+    miter_limit: f32,
+) -> f32 {
+    if (join == JOIN_ROUND) {
+        return length(offset) - half_width;
+    }
+    let turn = incoming.x*outgoing.y - incoming.y*outgoing.x;
+    if (turn == 0.0) {
+        return 1e30;
+    }
+    // The normals pointing away from the inside of the turn.
+    let side = -sign(turn);
+    let outer_in = vec2<f32>(-incoming.y*side, incoming.x*side);
+    let outer_out = vec2<f32>(-outgoing.y*side, outgoing.x*side);
+
+    // The miter lies past the end of the incoming segment, before the start of the outgoing one,
+    // and within both outer edges.
+    let distance = max(
+        max(-dot(offset, incoming), dot(offset, outgoing)),
+        max(dot(offset, outer_in), dot(offset, outer_out)) - half_width,
+    );
+
+    let cosine = dot(incoming, outgoing);
+    if (join == JOIN_MITER && (1.0 + cosine)*miter_limit*miter_limit >= 2.0) {
+        return distance;
+    }
+    // Bevels cut the miter off square to the bisector of the outer edges.
+    let bisector = (outer_in + outer_out) / length(outer_in + outer_out);
+    return max(distance, dot(offset, bisector) - half_width*dot(outer_in, bisector));
+}
+
+fn polyline_distance(shape: Shape, point: vec2<f32>) -> f32 {
+    let half_width = shape.params[0].x;
+    let miter_limit = shape.params[0].y;
+    let first = u32(shape.params[0].z);
+    let count = u32(shape.params[0].w);
+    let join = u32(shape.params[1].y);
+    let closed = u32(shape.params[1].z) != 0u;
+    // Closed polylines have no caps.
+    let cap = select(u32(shape.params[1].x), CAP_BUTT, closed);
+
+    var distance = 1e30;
+    var incoming = unit_vector(points[first + count - 1u], points[first]);
+    let segments = select(count - 1u, count, closed);
+    for (var i = 0u; i < segments; i++) {
+        let start = points[first + i];
+        let end = points[first + (i + 1u) % count];
+        let segment_length = length(end - start);
+        let direction = unit_vector(start, end);
+
+        // Square caps extend the first and last segments.
+        let extend_start = select(0.0, half_width, cap == CAP_SQUARE && i == 0u);
+        let extend_end = select(0.0, half_width, cap == CAP_SQUARE && i + 2u == count);
+        let centre = (start + end)*0.5 + direction*(extend_end - extend_start)*0.5;
+        let offset = point - centre;
+        let local = vec2<f32>(
+            offset.x*direction.x + offset.y*direction.y,
+            -offset.x*direction.y + offset.y*direction.x,
+        );
+        let half_length = (segment_length + extend_start + extend_end)*0.5;
+        distance = min(distance, box_distance(local, vec2<f32>(half_length, half_width), 0.0));
+
+        if (i > 0u || closed) {
+            distance = min(
+                distance,
+                join_distance(point - start, incoming, direction, half_width, join, miter_limit),
+            );
+        }
+        incoming = direction;
+    }
+
+    if (cap == CAP_ROUND) {
+        distance = min(distance, length(point - points[first]) - half_width);
+        distance = min(distance, length(point - points[first + count - 1u]) - half_width);
+    }
+    return distance;
+}
+
+// The distance to the nearest edge of a path, negative inside it by its fill rule.
+// Its points are subpaths, each its point count followed by its points.
+fn path_distance(shape: Shape, point: vec2<f32>) -> f32 {
+    let first = u32(shape.params[0].x);
+    let end = first + u32(shape.params[0].y);
+    let even_odd = u32(shape.params[0].z) == FILL_EVEN_ODD;
+
+    var distance = 1e30;
+    var winding = 0i;
+    var start = first;
+    while (start < end) {
+        let count = u32(points[start].x);
+        for (var k = 0u; k < count; k++) {
+            let a = points[start + 1u + k];
+            let b = points[start + 1u + (k + 1u) % count];
+            let edge = b - a;
+            let offset = point - a;
+            let along = clamp(dot(offset, edge) / dot(edge, edge), 0.0, 1.0);
+            distance = min(distance, length(offset - edge*along));
+
+            let cross = edge.x*offset.y - edge.y*offset.x;
+            if (a.y <= point.y && point.y < b.y && cross > 0.0) {
+                winding++;
+            } else if (b.y <= point.y && point.y < a.y && cross < 0.0) {
+                winding--;
+            }
+        }
+        start += count + 1u;
+    }
+
+    let inside = select(winding != 0i, winding % 2i != 0i, even_odd);
+    return select(distance, -distance, inside);
+}
+
+// The distance from `point` to the edge of a shape, negative inside it.
+fn signed_distance(shape: Shape, point: vec2<f32>) -> f32 {
+    let offset = point - shape.params[0].xy;
+    let size = shape.params[0].zw;
+    switch shape.kind {
+        case SHAPE_IMAGE: { return box_distance(offset, size, 0.0); }
+        case SHAPE_ROTATED_RECTANGLE: {
+            let cos = shape.params[1].x;
+            let sin = shape.params[1].y;
+            let local = vec2<f32>(offset.x*cos + offset.y*sin, -offset.x*sin + offset.y*cos);
+            return box_distance(local, size, 0.0);
+        }
+        case SHAPE_ELLIPSE: {
+            // An approximation which is exact on the edge, where it matters.
+            let k0 = length(offset / size);
+            let k1 = length(offset / (size*size));
+            if (k1 == 0.0) {
+                return -min(size.x, size.y);
+            }
+            return k0*(k0 - 1.0) / k1;
+        }
+        case SHAPE_ROUNDED_RECTANGLE: {
+            let radii = shape.params[1];
+            var radius = select(radii.x, radii.y, offset.x > 0.0);
+            if (offset.y > 0.0) {
+                radius = select(radii.w, radii.z, offset.x > 0.0);
+            }
+            return box_distance(offset, size, radius);
+        }
+        case SHAPE_POLYLINE: { return polyline_distance(shape, point); }
+        case SHAPE_PATH: { return path_distance(shape, point); }
+        case SHAPE_CIRCLE_OUTLINE: {
+            let distance = length(offset);
+            return max(distance - size.y, size.x - distance);
+        }
+        case SHAPE_RECTANGLE_OUTLINE: {
+            let cos = shape.params[1].x;
+            let sin = shape.params[1].y;
+            let local = vec2<f32>(offset.x*cos + offset.y*sin, -offset.x*sin + offset.y*cos);
+            return max(box_distance(local, size, 0.0), -box_distance(local, shape.params[1].zw, 0.0));
+        }
+        default: { return 1e30; }
+    }
+}
+
+// Coverage of shapes described by a signed distance, which is negative inside them.
+fn distance_coverage(shape: Shape, pixel: vec2<u32>) -> f32 {
+    if (uniforms.antialiasing == 0u) {
+        return select(0.0, 1.0, signed_distance(shape, vec2<f32>(pixel) + 0.5) <= 0.0);
+    }
+
+    if (uniforms.antialiasing == 1u) {
+        return clamp(0.5 - signed_distance(shape, vec2<f32>(pixel) + 0.5), 0.0, 1.0);
+    }
+
+    var inside = 0u;
+    for (var i = 0u; i < uniforms.samples; i++) {
+        for (var j = 0u; j < uniforms.samples; j++) {
+            let sample = (vec2<f32>(f32(j), f32(i)) + 0.5) / f32(uniforms.samples);
+            if (signed_distance(shape, vec2<f32>(pixel) + sample) <= 0.0) {
+                inside++;
+            }
+        }
+    }
+    return f32(inside) / f32(uniforms.samples*uniforms.samples);
+}
+
+fn coverage(shape: Shape, pixel: vec2<u32>) -> f32 {
+    switch shape.kind {
+        case SHAPE_CIRCLE: { return circle_coverage(shape, pixel); }
+        case SHAPE_RECTANGLE: { return 1.0; }
+        case SHAPE_ROTATED_RECTANGLE, SHAPE_ELLIPSE, SHAPE_ROUNDED_RECTANGLE, SHAPE_POLYLINE,
+            SHAPE_PATH, SHAPE_CIRCLE_OUTLINE, SHAPE_RECTANGLE_OUTLINE, SHAPE_IMAGE: {
+            return distance_coverage(shape, pixel);
+        }
+        case SHAPE_POLYGON: { return textureLoad(coverage_texture, pixel, 0).r; }
+        default: { return 0.0; }
+    }
+}
+
+// Mixes two straight-alpha colours in linear light with their alpha premultiplied,
+// so transparent stops don't darken.
+fn mix_colours(a: u32, b: u32, t: f32) -> u32 {
+    let pa = premultiplied_linear(a);
+    let pb = premultiplied_linear(b);
+    return from_premultiplied_linear(pa + (pb - pa)*t);
+}
+
+// The colour of `gradient` at `point`, padded with its end colours.
+fn gradient_colour(gradient: Gradient, point: vec2<f32>) -> u32 {
+    if (gradient.stop_count == 0u) {
+        return 0u;
+    }
+    let params = gradient.params;
+    let offset = point - params.xy;
+    var t = 0.0;
+    switch gradient.kind {
+        case GRADIENT_LINEAR: {
+            let direction = params.zw - params.xy;
+            let length_squared = direction.x*direction.x + direction.y*direction.y;
+            if (length_squared > 0.0) {
+                t = (offset.x*direction.x + offset.y*direction.y) / length_squared;
+            }
+        }
+        case GRADIENT_RADIAL: {
+            t = 1.0;
+            if (params.z > 0.0) {
+                t = length(offset) / params.z;
+            }
+        }
+        case GRADIENT_CONIC: {
+            let turns = (atan2(offset.y, offset.x) - params.z) / TAU;
+            t = turns - floor(turns);
+        }
+        default: {}
+    }
+    t = clamp(t, 0.0, 1.0);
+
+    var previous = stops[gradient.first_stop];
+    if (t <= previous.offset) {
+        return previous.colour;
+    }
+    for (var i = 1u; i < gradient.stop_count; i++) {
+        let next = stops[gradient.first_stop + i];
+        if (t <= next.offset) {
+            let fraction = (t - previous.offset) / (next.offset - previous.offset);
+            return mix_colours(previous.colour, next.colour, fraction);
+        }
+        previous = next;
+    }
+    return previous.colour;
+}
+
+// A pixel of the image, with samples past the edges of `region` repeating its edge pixels.
+fn image_texel(region: vec4<f32>, x: f32, y: f32) -> u32 {
+    let texel = vec2<i32>(clamp(vec2<f32>(x, y), region.xy, region.xy + region.zw - 1.0));
+    let c = textureLoad(image_texture, texel, 0);
+    return c.x | (c.y << 8u) | (c.z << 16u) | (c.w << 24u);
+}
+
+// The colour of an image at the centre of `pixel`, tinted and faded.
+fn image_colour(shape: Shape, pixel: vec2<u32>) -> u32 {
+    let centre = shape.params[0].xy;
+    let half_size = shape.params[0].zw;
+    let opacity = shape.params[1].x;
+    let region = shape.params[2];
+    let uv = region.xy + (vec2<f32>(pixel) + 0.5 - (centre - half_size)) / (2.0*half_size) * region.zw;
+
+    var colour: vec4<f32>;
+    if (shape.params[1].y == IMAGE_NEAREST) {
+        colour = unpack_colour(image_texel(region, floor(uv.x), floor(uv.y)));
+    } else {
+        // Mixed with their alpha premultiplied, so transparent pixels don't darken their neighbours.
+        let position = uv - 0.5;
+        let corner = floor(position);
+        let fraction = position - corner;
+        let c00 = unpack_colour(image_texel(region, corner.x, corner.y));
+        let c10 = unpack_colour(image_texel(region, corner.x + 1.0, corner.y));
+        let c01 = unpack_colour(image_texel(region, corner.x, corner.y + 1.0));
+        let c11 = unpack_colour(image_texel(region, corner.x + 1.0, corner.y + 1.0));
+        let top_left = vec4<f32>(c00.rgb*c00.a, c00.a);
+        let top_right = vec4<f32>(c10.rgb*c10.a, c10.a);
+        let bottom_left = vec4<f32>(c01.rgb*c01.a, c01.a);
+        let bottom_right = vec4<f32>(c11.rgb*c11.a, c11.a);
+        let upper = top_left + (top_right - top_left)*fraction.x;
+        let lower = bottom_left + (bottom_right - bottom_left)*fraction.x;
+        let mixed = upper + (lower - upper)*fraction.y;
+        if (mixed.a <= 0.0) {
+            return 0u;
+        }
+        colour = vec4<f32>(mixed.rgb / mixed.a, mixed.a);
+    }
+    let tint = unpack_colour(shape.colour);
+    return pack_colour(vec4<f32>(colour.rgb*tint.rgb, colour.a*tint.a*opacity));
+}
+
+// Composites `shape` over `backdrop`, the current colour of `pixel`.
+fn draw(shape: Shape, pixel: vec2<u32>, backdrop: u32) -> u32 {
+    if (any(pixel < shape.clip.xy) || any(pixel >= shape.clip.xy + shape.clip.zw)) {
+        return backdrop;
+    }
+    let coverage = coverage(shape, pixel);
+    if (coverage <= 0.0) {
+        return backdrop;
+    }
+    var colour = shape.colour;
+    if (shape.kind == SHAPE_IMAGE) {
+        colour = image_colour(shape, pixel);
+    } else if (shape.gradient != 0u) {
+        colour = gradient_colour(gradients[shape.gradient - 1u], vec2<f32>(pixel) + 0.5);
+    }
+    return composite(backdrop, with_coverage(colour, coverage), shape.blend_mode);
+}
+
+// Draws one shape over its clipped box.
 @compute
-@workgroup_size(1)
+@workgroup_size(8, 8)
 fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
-    let global_id_offset: vec2<u32> = vec2<u32>(global_id.x+uniforms.offset_x, global_id.y+uniforms.offset_y);
-    let id: u32 = global_id_offset.y*uniforms.width + global_id_offset.x;
-    let x: f32 = f32(global_id.x) - uniforms.radius;
-    let y: f32 = f32(global_id.y) - uniforms.radius;
+    let shape = shapes[uniforms.shape_index];
+    if (global_id.x >= shape.clip.z || global_id.y >= shape.clip.w) {
+        return;
+    }
+    let pixel = shape.clip.xy + global_id.xy;
+    let id: u32 = pixel.y*uniforms.width + pixel.x;
+
+    v_indices_output[id] = draw(shape, pixel, v_indices_output[id]);
+}
+
+// Draws every shape of a frame, one workgroup per tile, each pixel walking its tile's shapes in order.
+@compute
+@workgroup_size(TILE_SIZE, TILE_SIZE)
+fn main_tiled(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+) {
+    if (global_id.x >= uniforms.width || global_id.y >= uniforms.height) {
+        return;
+    }
+    let tile = workgroup_id.y*uniforms.tiles_x + workgroup_id.x;
+    let start = tiles[tile*2u];
+    let count = tiles[tile*2u + 1u];
+    if (count == 0u) {
+        return;
+    }
+    let id: u32 = global_id.y*uniforms.width + global_id.x;
 
-    if (x*x + y*y <= uniforms.radius*uniforms.radius) {
-        v_indices_output[id] = uniforms.colour;
+    var colour = v_indices_output[id];
+    for (var i = 0u; i < count; i++) {
+        let index = tiles[start + i];
+        // Most shapes in a tile miss most of its pixels, so check the box before loading the rest.
+        let clip = shapes[index].clip;
+        if (all(global_id.xy >= clip.xy) && all(global_id.xy < clip.xy + clip.zw)) {
+            colour = draw(shapes[index], global_id.xy, colour);
+        }
     }
+    v_indices_output[id] = colour;
 }
@@ -1,94 +1,1483 @@
-use std::{borrow::Cow, path::Path};
+use std::borrow::Cow;
 
-use wgpu::{util::DeviceExt as _, Adapter, Buffer, ComputePipeline, Device, Queue};
+use wgpu::{ComputePipeline, Device, Queue, RenderPipeline};
+
+use crate::{
+    color::Color,
+    renderer::{AdapterOptions, Antialiasing, GpuError, ShaderError, ShaderSources},
+};
+
+mod custom;
+mod effect;
+mod fill;
+mod group;
+mod sprite;
+mod svg;
+mod text;
+pub use custom::{CustomData, CustomShader};
+pub use effect::Effect;
+pub(crate) use effect::{EffectPass, PassLayer};
+pub use fill::{Fill, GradientRecord, GradientStop};
+pub use group::{GroupData, Mask, MaskMode};
+pub use sprite::{ImageData, ImageFilter, ImageSource};
+pub use svg::ParsePathError;
+pub use text::{Font, LoadFontError, TextAlign, TextData};
+
+/// How a shape's colour is mixed with the pixels already drawn underneath it.
+///
+/// Every mode is composited source-over, so the shape's alpha is always honoured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    Additive,
+    Darken,
+    Lighten,
+}
+impl BlendMode {
+    /// The inverse of `blend_mode as u32`, falling back to `Normal` for unknown modes.
+    pub fn from_index(index: u32) -> Self {
+        match index {
+            1 => BlendMode::Multiply,
+            2 => BlendMode::Screen,
+            3 => BlendMode::Additive,
+            4 => BlendMode::Darken,
+            5 => BlendMode::Lighten,
+            _ => BlendMode::Normal,
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct CircleData {
     pub position: (f32, f32),
     pub radius: f32,
-    pub colour: u32,
+    pub fill: Fill,
+    pub blend_mode: BlendMode,
+    /// An outline drawn over the fill.
+    pub stroke: Option<Stroke>,
 }
 impl CircleData {
-    pub fn new(position: (f32, f32), radius: f32, colour: u32) -> Self {
+    pub fn new(position: (f32, f32), radius: f32, fill: impl Into<Fill>) -> Self {
         Self {
             position,
             radius,
-            colour,
+            fill: fill.into(),
+            blend_mode: BlendMode::Normal,
+            stroke: None,
         }
     }
 
-    pub fn new_shape(position: (f32, f32), radius: f32, colour: u32) -> Shape {
-        Shape::Circle(Self {
-            position,
-            radius,
-            colour,
+    pub fn new_shape(position: (f32, f32), radius: f32, fill: impl Into<Fill>) -> Shape {
+        Shape::Circle(Self::new(position, radius, fill))
+    }
+
+    pub fn with_blend_mode(self, blend_mode: BlendMode) -> Self {
+        Self { blend_mode, ..self }
+    }
+
+    pub fn with_stroke(self, stroke: Option<Stroke>) -> Self {
+        Self { stroke, ..self }
+    }
+
+    pub fn to_record(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+    ) -> Option<ShapeRecord> {
+        let (origin_x, origin_y, _, _) = self.bounding_box();
+        Some(ShapeRecord {
+            kind: ShapeRecord::CIRCLE,
+            blend_mode: self.blend_mode as u32,
+            clip: clip_box(self.covered_box(antialiasing), width, height)?.into(),
+            origin: [origin_x, origin_y],
+            params: [
+                [self.position.0, self.position.1, self.radius, 0.0],
+                [0.0; 4],
+                [0.0; 4],
+            ],
+            ..Default::default()
         })
     }
 
-    pub fn create_buffer(&self, device: &Device, width: u32, height: u32) -> Buffer {
-        let (x, y, _, _) = self.bounding_box();
-        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Circle Uniform Buffer"),
-            contents: bytemuck::cast_slice(&[
-                bytemuck::cast(self.radius),
-                width,
-                x,
-                y,
-                self.colour,
-            ]),
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    /// The record for the outline, drawn over the fill's.
+    pub fn stroke_record(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+    ) -> Option<ShapeRecord> {
+        let stroke = self.stroke.filter(|x| x.width > 0.0)?;
+        let (inner, outer) = stroke.edges(self.radius);
+        let (origin_x, origin_y, _, _) = self.bounding_box();
+        // Hard-edged circles are drawn around a point half a pixel off their centre, so their outline is too.
+        let (centre_x, centre_y) = if antialiasing == Antialiasing::None {
+            (
+                origin_x as f32 + self.radius + 0.5,
+                origin_y as f32 + self.radius + 0.5,
+            )
+        } else {
+            self.position
+        };
+        Some(ShapeRecord {
+            kind: ShapeRecord::CIRCLE_OUTLINE,
+            colour: stroke.colour.to_packed(),
+            blend_mode: self.blend_mode as u32,
+            clip: self.stroke_box(antialiasing, width, height)?.into(),
+            origin: [origin_x, origin_y],
+            params: [[centre_x, centre_y, inner, outer], [0.0; 4], [0.0; 4]],
+            ..Default::default()
         })
     }
 
-    pub fn bounding_box(&self) -> (u32, u32, u32, u32) {
+    /// The on-canvas pixels the outline may touch.
+    fn stroke_box(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+    ) -> Option<(u32, u32, u32, u32)> {
+        let stroke = self.stroke.filter(|x| x.width > 0.0)?;
+        let (_, outer) = stroke.edges(self.radius);
+        let (x, y) = self.position;
+        // Past the outer edge, for the half pixel hard-edged outlines are shifted by.
+        let margin = if antialiasing == Antialiasing::None {
+            0.5
+        } else {
+            1.0
+        };
+        let corners = [(x - outer, y - outer), (x + outer, y + outer)];
+        clip_box(box_around(&corners, margin), width, height)
+    }
+
+    /// The hard-edged square around the circle, which may lie partly or entirely off the canvas.
+    pub fn bounding_box(&self) -> (i32, i32, u32, u32) {
         (
-            (self.position.0 - self.radius).floor() as u32,
-            (self.position.1 - self.radius).floor() as u32,
+            (self.position.0 - self.radius).floor() as i32,
+            (self.position.1 - self.radius).floor() as i32,
             (self.radius * 2.0).floor() as u32,
             (self.radius * 2.0).floor() as u32,
         )
     }
+
+    /// The pixels which may be touched with `antialiasing`.
+    /// Smoothed edges spill up to a pixel past the hard-edged bounding box.
+    pub fn covered_box(&self, antialiasing: Antialiasing) -> (i32, i32, u32, u32) {
+        if antialiasing == Antialiasing::None {
+            return self.bounding_box();
+        }
+        let (x, y) = (
+            (self.position.0 - self.radius - 1.0).floor(),
+            (self.position.1 - self.radius - 1.0).floor(),
+        );
+        (
+            x as i32,
+            y as i32,
+            ((self.position.0 + self.radius + 1.0).ceil() - x) as u32,
+            ((self.position.1 + self.radius + 1.0).ceil() - y) as u32,
+        )
+    }
+
+    /// The on-canvas pixels the circle or its outline may touch.
+    pub fn clipped_box(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+    ) -> Option<(u32, u32, u32, u32)> {
+        union_box(
+            clip_box(self.covered_box(antialiasing), width, height),
+            self.stroke_box(antialiasing, width, height),
+        )
+    }
+}
+
+/// The point of a rectangle which sits at its position and which it rotates around.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Anchor {
+    #[default]
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Centre,
+    /// A point given as fractions of the width and height, from the top-left corner.
+    Custom(f32, f32),
+}
+impl Anchor {
+    /// The anchor as fractions of the width and height, from the top-left corner.
+    pub fn fraction(&self) -> (f32, f32) {
+        match *self {
+            Anchor::TopLeft => (0.0, 0.0),
+            Anchor::TopRight => (1.0, 0.0),
+            Anchor::BottomLeft => (0.0, 1.0),
+            Anchor::BottomRight => (1.0, 1.0),
+            Anchor::Centre => (0.5, 0.5),
+            Anchor::Custom(x, y) => (x, y),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct RectangleData {
+    /// Where the anchor point of the rectangle sits.
     pub position: (f32, f32),
     pub size: (f32, f32),
-    pub colour: u32,
+    pub fill: Fill,
+    pub blend_mode: BlendMode,
+    /// Clockwise rotation around the anchor, in radians.
+    pub rotation: f32,
+    pub anchor: Anchor,
+    /// An outline drawn over the fill. Its corners are always sharp.
+    pub stroke: Option<Stroke>,
 }
 impl RectangleData {
-    pub fn new(position: (f32, f32), size: (f32, f32), colour: u32) -> Self {
+    pub fn new(position: (f32, f32), size: (f32, f32), fill: impl Into<Fill>) -> Self {
         Self {
             position,
             size,
-            colour,
+            fill: fill.into(),
+            blend_mode: BlendMode::Normal,
+            rotation: 0.0,
+            anchor: Anchor::TopLeft,
+            stroke: None,
+        }
+    }
+
+    pub fn new_shape(position: (f32, f32), size: (f32, f32), fill: impl Into<Fill>) -> Shape {
+        Shape::Rectangle(Self::new(position, size, fill))
+    }
+
+    pub fn with_blend_mode(self, blend_mode: BlendMode) -> Self {
+        Self { blend_mode, ..self }
+    }
+
+    pub fn with_rotation(self, rotation: f32) -> Self {
+        Self { rotation, ..self }
+    }
+
+    pub fn with_anchor(self, anchor: Anchor) -> Self {
+        Self { anchor, ..self }
+    }
+
+    pub fn with_stroke(self, stroke: Option<Stroke>) -> Self {
+        Self { stroke, ..self }
+    }
+
+    /// The top-left corner before rotation.
+    pub fn top_left(&self) -> (f32, f32) {
+        let (x, y) = self.anchor.fraction();
+        (
+            self.position.0 - x * self.size.0,
+            self.position.1 - y * self.size.1,
+        )
+    }
+
+    pub fn centre(&self) -> (f32, f32) {
+        self.rotate((
+            self.top_left().0 + self.size.0 / 2.0,
+            self.top_left().1 + self.size.1 / 2.0,
+        ))
+    }
+
+    /// The four corners after rotation, clockwise from the top-left.
+    pub fn corners(&self) -> [(f32, f32); 4] {
+        let (x, y) = self.top_left();
+        let (width, height) = self.size;
+        [
+            (x, y),
+            (x + width, y),
+            (x + width, y + height),
+            (x, y + height),
+        ]
+        .map(|corner| self.rotate(corner))
+    }
+
+    /// Rotates a point around the anchor.
+    fn rotate(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let (sin, cos) = self.rotation.sin_cos();
+        let (dx, dy) = (x - self.position.0, y - self.position.1);
+        (
+            self.position.0 + dx * cos - dy * sin,
+            self.position.1 + dx * sin + dy * cos,
+        )
+    }
+
+    pub fn to_record(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+    ) -> Option<ShapeRecord> {
+        let (origin_x, origin_y, _, _) = self.bounding_box();
+        let clip = clip_box(self.covered_box(antialiasing), width, height)?.into();
+        if self.rotation == 0.0 {
+            return Some(ShapeRecord {
+                kind: ShapeRecord::RECTANGLE,
+                blend_mode: self.blend_mode as u32,
+                clip,
+                origin: [origin_x, origin_y],
+                ..Default::default()
+            });
+        }
+
+        let (centre_x, centre_y) = self.centre();
+        let (sin, cos) = self.rotation.sin_cos();
+        Some(ShapeRecord {
+            kind: ShapeRecord::ROTATED_RECTANGLE,
+            blend_mode: self.blend_mode as u32,
+            clip,
+            origin: [origin_x, origin_y],
+            params: [
+                [centre_x, centre_y, self.size.0 / 2.0, self.size.1 / 2.0],
+                [cos, sin, 0.0, 0.0],
+                [0.0; 4],
+            ],
+            ..Default::default()
+        })
+    }
+
+    /// The record for the outline, drawn over the fill's.
+    pub fn stroke_record(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+    ) -> Option<ShapeRecord> {
+        let stroke = self.stroke.filter(|x| x.width > 0.0)?;
+        let (centre, half_size, (cos, sin)) = self.outline_frame();
+        let (inner, outer) = stroke.edges(0.0);
+        let (origin_x, origin_y, _, _) = self.bounding_box();
+        Some(ShapeRecord {
+            kind: ShapeRecord::RECTANGLE_OUTLINE,
+            colour: stroke.colour.to_packed(),
+            blend_mode: self.blend_mode as u32,
+            clip: self.stroke_box(antialiasing, width, height)?.into(),
+            origin: [origin_x, origin_y],
+            params: [
+                [centre.0, centre.1, half_size.0 + outer, half_size.1 + outer],
+                [cos, sin, half_size.0 + inner, half_size.1 + inner],
+                [0.0; 4],
+            ],
+            ..Default::default()
+        })
+    }
+
+    /// The centre, half size, and cosine and sine of the rotation the outline is drawn around.
+    /// Unrotated rectangles are filled in whole pixels, so their outlines follow those pixels.
+    fn outline_frame(&self) -> ((f32, f32), (f32, f32), (f32, f32)) {
+        if self.rotation == 0.0 {
+            let (x, y, width, height) = self.bounding_box();
+            let half_size = (width as f32 / 2.0, height as f32 / 2.0);
+            return (
+                (x as f32 + half_size.0, y as f32 + half_size.1),
+                half_size,
+                (1.0, 0.0),
+            );
+        }
+        let (sin, cos) = self.rotation.sin_cos();
+        (
+            self.centre(),
+            (self.size.0 / 2.0, self.size.1 / 2.0),
+            (cos, sin),
+        )
+    }
+
+    /// The on-canvas pixels the outline may touch.
+    fn stroke_box(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+    ) -> Option<(u32, u32, u32, u32)> {
+        let stroke = self.stroke.filter(|x| x.width > 0.0)?;
+        let ((x, y), (half_width, half_height), (cos, sin)) = self.outline_frame();
+        let (_, outer) = stroke.edges(0.0);
+        let (half_width, half_height) = (half_width + outer, half_height + outer);
+        let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].map(|(i, j)| {
+            let (dx, dy) = (i * half_width, j * half_height);
+            (x + dx * cos - dy * sin, y + dx * sin + dy * cos)
+        });
+        let margin = if antialiasing == Antialiasing::None {
+            0.0
+        } else {
+            1.0
+        };
+        clip_box(box_around(&corners, margin), width, height)
+    }
+
+    /// The pixels the rectangle covers, which may lie partly or entirely off the canvas.
+    /// Unrotated rectangles cover whole pixels from their floored corner,
+    /// rotated ones every pixel whose centre could be inside them.
+    pub fn bounding_box(&self) -> (i32, i32, u32, u32) {
+        if self.rotation == 0.0 {
+            let (x, y) = self.top_left();
+            return (
+                x.floor() as i32,
+                y.floor() as i32,
+                self.size.0.floor() as u32,
+                self.size.1.floor() as u32,
+            );
         }
+        box_around(&self.corners(), 0.0)
     }
 
-    pub fn new_shape(position: (f32, f32), size: (f32, f32), colour: u32) -> Shape {
-        Shape::Rectangle(Self {
+    /// The pixels which may be touched with `antialiasing`.
+    /// Only rotated rectangles have edges to smooth.
+    pub fn covered_box(&self, antialiasing: Antialiasing) -> (i32, i32, u32, u32) {
+        if self.rotation == 0.0 || antialiasing == Antialiasing::None {
+            return self.bounding_box();
+        }
+        box_around(&self.corners(), 1.0)
+    }
+
+    /// The on-canvas pixels the rectangle or its outline may touch.
+    pub fn clipped_box(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+    ) -> Option<(u32, u32, u32, u32)> {
+        union_box(
+            clip_box(self.covered_box(antialiasing), width, height),
+            self.stroke_box(antialiasing, width, height),
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EllipseData {
+    /// The centre of the ellipse.
+    pub position: (f32, f32),
+    pub radii: (f32, f32),
+    pub fill: Fill,
+    pub blend_mode: BlendMode,
+}
+impl EllipseData {
+    pub fn new(position: (f32, f32), radii: (f32, f32), fill: impl Into<Fill>) -> Self {
+        Self {
+            position,
+            radii,
+            fill: fill.into(),
+            blend_mode: BlendMode::Normal,
+        }
+    }
+
+    pub fn new_shape(position: (f32, f32), radii: (f32, f32), fill: impl Into<Fill>) -> Shape {
+        Shape::Ellipse(Self::new(position, radii, fill))
+    }
+
+    pub fn with_blend_mode(self, blend_mode: BlendMode) -> Self {
+        Self { blend_mode, ..self }
+    }
+
+    pub fn to_record(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+    ) -> Option<ShapeRecord> {
+        if self.radii.0 <= 0.0 || self.radii.1 <= 0.0 {
+            return None;
+        }
+        let (origin_x, origin_y, _, _) = self.bounding_box();
+        Some(ShapeRecord {
+            kind: ShapeRecord::ELLIPSE,
+            blend_mode: self.blend_mode as u32,
+            clip: self.clipped_box(antialiasing, width, height)?.into(),
+            origin: [origin_x, origin_y],
+            params: [
+                [self.position.0, self.position.1, self.radii.0, self.radii.1],
+                [0.0; 4],
+                [0.0; 4],
+            ],
+            ..Default::default()
+        })
+    }
+
+    /// The pixels whose centres could be inside the ellipse, which may lie partly or entirely off the canvas.
+    pub fn bounding_box(&self) -> (i32, i32, u32, u32) {
+        self.box_with_margin(0.0)
+    }
+
+    /// The pixels which may be touched with `antialiasing`.
+    pub fn covered_box(&self, antialiasing: Antialiasing) -> (i32, i32, u32, u32) {
+        if antialiasing == Antialiasing::None {
+            return self.bounding_box();
+        }
+        self.box_with_margin(1.0)
+    }
+
+    pub fn clipped_box(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+    ) -> Option<(u32, u32, u32, u32)> {
+        clip_box(self.covered_box(antialiasing), width, height)
+    }
+
+    fn box_with_margin(&self, margin: f32) -> (i32, i32, u32, u32) {
+        let (x, y) = self.position;
+        let (radius_x, radius_y) = self.radii;
+        box_around(
+            &[(x - radius_x, y - radius_y), (x + radius_x, y + radius_y)],
+            margin,
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RoundedRectangleData {
+    /// The top-left corner.
+    pub position: (f32, f32),
+    pub size: (f32, f32),
+    /// The radius of each corner, clockwise from the top-left.
+    /// Radii larger than half the shorter side are clamped to it.
+    pub corner_radii: [f32; 4],
+    pub fill: Fill,
+    pub blend_mode: BlendMode,
+}
+impl RoundedRectangleData {
+    pub fn new(
+        position: (f32, f32),
+        size: (f32, f32),
+        corner_radii: [f32; 4],
+        fill: impl Into<Fill>,
+    ) -> Self {
+        Self {
             position,
             size,
-            colour,
+            corner_radii,
+            fill: fill.into(),
+            blend_mode: BlendMode::Normal,
+        }
+    }
+
+    pub fn new_shape(
+        position: (f32, f32),
+        size: (f32, f32),
+        corner_radii: [f32; 4],
+        fill: impl Into<Fill>,
+    ) -> Shape {
+        Shape::RoundedRectangle(Self::new(position, size, corner_radii, fill))
+    }
+
+    pub fn with_blend_mode(self, blend_mode: BlendMode) -> Self {
+        Self { blend_mode, ..self }
+    }
+
+    pub fn to_record(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+    ) -> Option<ShapeRecord> {
+        if self.size.0 <= 0.0 || self.size.1 <= 0.0 {
+            return None;
+        }
+        let (origin_x, origin_y, _, _) = self.bounding_box();
+        let (half_width, half_height) = (self.size.0 / 2.0, self.size.1 / 2.0);
+        let max_radius = half_width.min(half_height);
+        Some(ShapeRecord {
+            kind: ShapeRecord::ROUNDED_RECTANGLE,
+            blend_mode: self.blend_mode as u32,
+            clip: self.clipped_box(antialiasing, width, height)?.into(),
+            origin: [origin_x, origin_y],
+            params: [
+                [
+                    self.position.0 + half_width,
+                    self.position.1 + half_height,
+                    half_width,
+                    half_height,
+                ],
+                self.corner_radii.map(|x| x.clamp(0.0, max_radius)),
+                [0.0; 4],
+            ],
+            ..Default::default()
+        })
+    }
+
+    /// The pixels whose centres could be inside the rectangle, which may lie partly or entirely off the canvas.
+    pub fn bounding_box(&self) -> (i32, i32, u32, u32) {
+        self.box_with_margin(0.0)
+    }
+
+    /// The pixels which may be touched with `antialiasing`.
+    pub fn covered_box(&self, antialiasing: Antialiasing) -> (i32, i32, u32, u32) {
+        if antialiasing == Antialiasing::None {
+            return self.bounding_box();
+        }
+        self.box_with_margin(1.0)
+    }
+
+    pub fn clipped_box(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+    ) -> Option<(u32, u32, u32, u32)> {
+        clip_box(self.covered_box(antialiasing), width, height)
+    }
+
+    fn box_with_margin(&self, margin: f32) -> (i32, i32, u32, u32) {
+        let (x, y) = self.position;
+        box_around(&[(x, y), (x + self.size.0, y + self.size.1)], margin)
+    }
+}
+
+/// How the open ends of a line are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineCap {
+    /// Square, and flush with the end point.
+    #[default]
+    Butt,
+    Round,
+    /// Square, and extended past the end point by half the width.
+    Square,
+}
+
+/// How the corners where the segments of a polyline meet are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineJoin {
+    /// Sharp, unless longer than the miter limit, when it is bevelled instead.
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Debug, Clone)]
+pub struct PolylineData {
+    pub points: Vec<(f32, f32)>,
+    pub width: f32,
+    pub fill: Fill,
+    pub blend_mode: BlendMode,
+    pub cap: LineCap,
+    pub join: LineJoin,
+    /// The longest a miter join can be, as a multiple of the width.
+    pub miter_limit: f32,
+    /// Whether the last point joins back up with the first, rather than both being capped.
+    pub closed: bool,
+}
+impl PolylineData {
+    pub fn new(points: Vec<(f32, f32)>, width: f32, fill: impl Into<Fill>) -> Self {
+        Self {
+            points,
+            width,
+            fill: fill.into(),
+            blend_mode: BlendMode::Normal,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            miter_limit: 4.0,
+            closed: false,
+        }
+    }
+
+    pub fn new_shape(points: Vec<(f32, f32)>, width: f32, fill: impl Into<Fill>) -> Shape {
+        Shape::Polyline(Self::new(points, width, fill))
+    }
+
+    /// A single straight line from `start` to `end`.
+    pub fn new_line(
+        start: (f32, f32),
+        end: (f32, f32),
+        width: f32,
+        fill: impl Into<Fill>,
+    ) -> Shape {
+        Self::new_shape(vec![start, end], width, fill)
+    }
+
+    pub fn with_blend_mode(self, blend_mode: BlendMode) -> Self {
+        Self { blend_mode, ..self }
+    }
+
+    pub fn with_cap(self, cap: LineCap) -> Self {
+        Self { cap, ..self }
+    }
+
+    pub fn with_join(self, join: LineJoin) -> Self {
+        Self { join, ..self }
+    }
+
+    pub fn with_miter_limit(self, miter_limit: f32) -> Self {
+        Self {
+            miter_limit,
+            ..self
+        }
+    }
+
+    pub fn with_closed(self, closed: bool) -> Self {
+        Self { closed, ..self }
+    }
+
+    /// The points without repeats, which would give segments no direction.
+    fn distinct_points(&self) -> Vec<(f32, f32)> {
+        let mut points = self.points.clone();
+        points.dedup();
+        if self.closed && points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        points
+    }
+
+    /// Appends the points to `points`, which the record refers to by index.
+    pub fn to_record(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+        points: &mut Vec<[f32; 2]>,
+    ) -> Option<ShapeRecord> {
+        let distinct_points = self.distinct_points();
+        if distinct_points.len() < 2 || self.width <= 0.0 {
+            return None;
+        }
+        let clip = self.clipped_box(antialiasing, width, height)?.into();
+        let (origin_x, origin_y, _, _) = self.bounding_box();
+        let first = points.len();
+        points.extend(distinct_points.iter().map(|&(x, y)| [x, y]));
+        Some(ShapeRecord {
+            kind: ShapeRecord::POLYLINE,
+            blend_mode: self.blend_mode as u32,
+            clip,
+            origin: [origin_x, origin_y],
+            params: [
+                [
+                    self.width / 2.0,
+                    self.miter_limit,
+                    first as f32,
+                    distinct_points.len() as f32,
+                ],
+                [
+                    self.cap as u32 as f32,
+                    self.join as u32 as f32,
+                    self.closed as u32 as f32,
+                    0.0,
+                ],
+                [0.0; 4],
+            ],
+            ..Default::default()
+        })
+    }
+
+    /// The pixels whose centres could be inside the line, which may lie partly or entirely off the canvas.
+    pub fn bounding_box(&self) -> (i32, i32, u32, u32) {
+        self.box_with_margin(0.0)
+    }
+
+    /// The pixels which may be touched with `antialiasing`.
+    pub fn covered_box(&self, antialiasing: Antialiasing) -> (i32, i32, u32, u32) {
+        if antialiasing == Antialiasing::None {
+            return self.bounding_box();
+        }
+        self.box_with_margin(1.0)
+    }
+
+    pub fn clipped_box(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+    ) -> Option<(u32, u32, u32, u32)> {
+        clip_box(self.covered_box(antialiasing), width, height)
+    }
+
+    /// How far past its points the line can reach.
+    /// Square caps reach furthest at their corners, and miters at their tips.
+    pub fn reach(&self) -> f32 {
+        let reach = match self.join {
+            LineJoin::Miter => self.miter_limit.max(std::f32::consts::SQRT_2),
+            _ => std::f32::consts::SQRT_2,
+        };
+        self.width / 2.0 * reach
+    }
+
+    fn box_with_margin(&self, margin: f32) -> (i32, i32, u32, u32) {
+        box_around(&self.points, self.reach() + margin)
+    }
+}
+
+/// Which points a path's outline counts as inside it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillRule {
+    /// Inside wherever the outline winds around the point at all.
+    #[default]
+    NonZero,
+    /// Inside wherever the outline crosses an odd number of times on the way to the point.
+    EvenOdd,
+}
+
+/// A step in a path's outline, from the end of the previous one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathSegment {
+    /// Starts a new subpath.
+    MoveTo((f32, f32)),
+    LineTo((f32, f32)),
+    /// A quadratic Bezier curve through a control point to an end point.
+    QuadTo((f32, f32), (f32, f32)),
+    /// A cubic Bezier curve through two control points to an end point.
+    CubicTo((f32, f32), (f32, f32), (f32, f32)),
+    /// Joins the subpath back up with its start.
+    Close,
+}
+
+/// How far a flattened curve may stray from the real one, in pixels.
+const FLATTENING_TOLERANCE: f32 = 0.1;
+
+/// Where a stroke lies relative to the edge of the shape it outlines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StrokeAlignment {
+    Inside,
+    /// Half inside and half outside.
+    #[default]
+    Centre,
+    Outside,
+}
+
+/// An outline. Circles and rectangles use only its width, colour and alignment,
+/// while paths use its caps and joins but are always stroked centred.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stroke {
+    pub width: f32,
+    pub colour: Color,
+    pub cap: LineCap,
+    pub join: LineJoin,
+    /// The longest a miter join can be, as a multiple of the width.
+    pub miter_limit: f32,
+    pub alignment: StrokeAlignment,
+}
+impl Stroke {
+    pub fn new(width: f32, colour: impl Into<Color>) -> Self {
+        Self {
+            width,
+            colour: colour.into(),
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            miter_limit: 4.0,
+            alignment: StrokeAlignment::Centre,
+        }
+    }
+
+    pub fn with_alignment(self, alignment: StrokeAlignment) -> Self {
+        Self { alignment, ..self }
+    }
+
+    /// How far the inner and outer edges of the stroke lie from the origin,
+    /// for an outline `edge` away from it.
+    pub fn edges(&self, edge: f32) -> (f32, f32) {
+        let inner = match self.alignment {
+            StrokeAlignment::Inside => edge - self.width,
+            StrokeAlignment::Centre => edge - self.width / 2.0,
+            StrokeAlignment::Outside => edge,
+        };
+        (inner, inner + self.width)
+    }
+
+    pub fn with_cap(self, cap: LineCap) -> Self {
+        Self { cap, ..self }
+    }
+
+    pub fn with_join(self, join: LineJoin) -> Self {
+        Self { join, ..self }
+    }
+
+    pub fn with_miter_limit(self, miter_limit: f32) -> Self {
+        Self {
+            miter_limit,
+            ..self
+        }
+    }
+
+    /// A polyline through `points` drawn with this stroke.
+    pub fn polyline(&self, points: Vec<(f32, f32)>, closed: bool) -> PolylineData {
+        PolylineData::new(points, self.width, self.colour)
+            .with_cap(self.cap)
+            .with_join(self.join)
+            .with_miter_limit(self.miter_limit)
+            .with_closed(closed)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PathData {
+    pub segments: Vec<PathSegment>,
+    /// What the inside of the outline is painted with, or `None` to only stroke it.
+    pub fill: Option<Fill>,
+    pub fill_rule: FillRule,
+    pub stroke: Option<Stroke>,
+    pub blend_mode: BlendMode,
+}
+impl PathData {
+    pub fn new(segments: Vec<PathSegment>, fill: impl Into<Fill>) -> Self {
+        Self {
+            segments,
+            fill: Some(fill.into()),
+            fill_rule: FillRule::NonZero,
+            stroke: None,
+            blend_mode: BlendMode::Normal,
+        }
+    }
+
+    pub fn new_shape(segments: Vec<PathSegment>, fill: impl Into<Fill>) -> Shape {
+        Shape::Path(Self::new(segments, fill))
+    }
+
+    /// A path filled with `fill` from the `d` attribute of an SVG `<path>`, such as `"M 10 10 h 20 v 20 z"`.
+    pub fn parse(data: &str, fill: impl Into<Fill>) -> Result<Self, ParsePathError> {
+        Ok(Self::new(svg::parse_path_data(data)?, fill))
+    }
+
+    pub fn with_fill(self, fill: Option<Fill>) -> Self {
+        Self { fill, ..self }
+    }
+
+    pub fn with_fill_rule(self, fill_rule: FillRule) -> Self {
+        Self { fill_rule, ..self }
+    }
+
+    pub fn with_stroke(self, stroke: Option<Stroke>) -> Self {
+        Self { stroke, ..self }
+    }
+
+    pub fn with_blend_mode(self, blend_mode: BlendMode) -> Self {
+        Self { blend_mode, ..self }
+    }
+
+    /// The outline as straight lines, one list of points per subpath, and whether each is closed.
+    pub fn flatten(&self) -> Vec<(Vec<(f32, f32)>, bool)> {
+        let mut subpaths = vec![];
+        let mut points = vec![];
+        let mut current = (0.0, 0.0);
+        let mut finish = |points: &mut Vec<(f32, f32)>, closed: bool| {
+            if !points.is_empty() {
+                subpaths.push((std::mem::take(points), closed));
+            }
+        };
+        for segment in &self.segments {
+            // Drawing after a close carries on from where the closed subpath started.
+            if points.is_empty() && !matches!(segment, PathSegment::MoveTo(_)) {
+                points.push(current);
+            }
+            match *segment {
+                PathSegment::MoveTo(point) => {
+                    finish(&mut points, false);
+                    points.push(point);
+                }
+                PathSegment::LineTo(point) => points.push(point),
+                PathSegment::QuadTo(control, end) => {
+                    let start = current;
+                    let count = curve_steps(0.25, &[start, control, end]);
+                    points.extend((1..=count).map(|i| {
+                        let t = i as f32 / count as f32;
+                        let u = 1.0 - t;
+                        let (a, b, c) = (u * u, 2.0 * u * t, t * t);
+                        (
+                            a * start.0 + b * control.0 + c * end.0,
+                            a * start.1 + b * control.1 + c * end.1,
+                        )
+                    }));
+                }
+                PathSegment::CubicTo(first, second, end) => {
+                    let start = current;
+                    let count = curve_steps(0.75, &[start, first, second, end]);
+                    points.extend((1..=count).map(|i| {
+                        let t = i as f32 / count as f32;
+                        let u = 1.0 - t;
+                        let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+                        (
+                            a * start.0 + b * first.0 + c * second.0 + d * end.0,
+                            a * start.1 + b * first.1 + c * second.1 + d * end.1,
+                        )
+                    }));
+                }
+                PathSegment::Close => {
+                    let start = points[0];
+                    finish(&mut points, true);
+                    current = start;
+                    continue;
+                }
+            }
+            current = *points.last().unwrap();
+        }
+        finish(&mut points, false);
+        subpaths
+    }
+
+    /// The record for the inside of the path, which refers to its points in `points`.
+    /// Every subpath is filled as if closed, and those without any area are left out.
+    pub fn fill_record(
+        &self,
+        subpaths: &[(Vec<(f32, f32)>, bool)],
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+        points: &mut Vec<[f32; 2]>,
+    ) -> Option<ShapeRecord> {
+        self.fill.as_ref()?;
+        let subpaths: Vec<_> = subpaths
+            .iter()
+            .map(|(points, _)| {
+                let mut points = points.clone();
+                points.dedup();
+                if points.len() > 1 && points.first() == points.last() {
+                    points.pop();
+                }
+                points
+            })
+            .filter(|x| x.len() >= 3)
+            .collect();
+        let all_points: Vec<_> = subpaths.iter().flatten().copied().collect();
+        if all_points.is_empty() {
+            return None;
+        }
+        let margin = if antialiasing == Antialiasing::None {
+            0.0
+        } else {
+            1.0
+        };
+        let clip = clip_box(box_around(&all_points, margin), width, height)?.into();
+        let (origin_x, origin_y, _, _) = box_around(&all_points, 0.0);
+
+        // Each subpath is its point count, followed by its points.
+        let first = points.len();
+        for subpath in &subpaths {
+            points.push([subpath.len() as f32, 0.0]);
+            points.extend(subpath.iter().map(|&(x, y)| [x, y]));
+        }
+        Some(ShapeRecord {
+            kind: ShapeRecord::PATH,
+            blend_mode: self.blend_mode as u32,
+            clip,
+            origin: [origin_x, origin_y],
+            params: [
+                [
+                    first as f32,
+                    (points.len() - first) as f32,
+                    self.fill_rule as u32 as f32,
+                    0.0,
+                ],
+                [0.0; 4],
+                [0.0; 4],
+            ],
+            ..Default::default()
+        })
+    }
+
+    /// The stroke around each subpath, drawn after the fill.
+    pub fn stroke_polylines(&self, subpaths: &[(Vec<(f32, f32)>, bool)]) -> Vec<PolylineData> {
+        let Some(stroke) = self.stroke else {
+            return vec![];
+        };
+        subpaths
+            .iter()
+            .map(|(points, closed)| {
+                stroke
+                    .polyline(points.clone(), *closed)
+                    .with_blend_mode(self.blend_mode)
+            })
+            .collect()
+    }
+
+    /// Appends the fill and stroke records to `records`.
+    pub fn to_records(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+        records: &mut FrameRecords,
+    ) {
+        let subpaths = self.flatten();
+        if let Some(fill) = &self.fill {
+            let record =
+                self.fill_record(&subpaths, antialiasing, width, height, &mut records.points);
+            records.push(record, fill);
+        }
+        for polyline in self.stroke_polylines(&subpaths) {
+            let stroke = polyline.to_record(antialiasing, width, height, &mut records.points);
+            records.push(stroke, &polyline.fill);
+        }
+    }
+
+    /// The pixels whose centres could be inside the path or its stroke, which may lie partly or entirely off the canvas.
+    pub fn bounding_box(&self) -> (i32, i32, u32, u32) {
+        self.box_with_margin(0.0)
+    }
+
+    /// The pixels which may be touched with `antialiasing`.
+    pub fn covered_box(&self, antialiasing: Antialiasing) -> (i32, i32, u32, u32) {
+        if antialiasing == Antialiasing::None {
+            return self.bounding_box();
+        }
+        self.box_with_margin(1.0)
+    }
+
+    pub fn clipped_box(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+    ) -> Option<(u32, u32, u32, u32)> {
+        clip_box(self.covered_box(antialiasing), width, height)
+    }
+
+    fn box_with_margin(&self, margin: f32) -> (i32, i32, u32, u32) {
+        let subpaths = self.flatten();
+        let points: Vec<_> = subpaths.iter().flat_map(|(x, _)| x).copied().collect();
+        let stroke_margin = self
+            .stroke_polylines(&subpaths)
+            .first()
+            .map(PolylineData::reach)
+            .unwrap_or(0.0);
+        box_around(&points, stroke_margin + margin)
+    }
+}
+
+/// How many straight lines a Bezier curve needs to stay within `FLATTENING_TOLERANCE`,
+/// by Wang's formula, where `factor` is `degree * (degree - 1) / 8`.
+fn curve_steps(factor: f32, points: &[(f32, f32)]) -> usize {
+    let bend = points
+        .windows(3)
+        .map(|x| {
+            let (dx, dy) = (
+                x[0].0 - 2.0 * x[1].0 + x[2].0,
+                x[0].1 - 2.0 * x[1].1 + x[2].1,
+            );
+            (dx * dx + dy * dy).sqrt()
         })
+        .fold(0.0, f32::max);
+    ((factor * bend / FLATTENING_TOLERANCE).sqrt().ceil() as usize).clamp(1, 256)
+}
+
+/// The samples per pixel polygons are rasterized with when antialiasing is on, in any mode.
+pub const POLYGON_SAMPLE_COUNT: u32 = 4;
+
+/// The samples per pixel shapes are rasterized with by the GPU renderer's raster path
+/// when antialiasing is on, in any mode.
+pub const RASTER_SAMPLE_COUNT: u32 = 4;
+
+/// A filled polygon, which may be concave but shouldn't cross itself.
+/// Rather than being drawn by the compute kernels, it is triangulated and rasterized.
+#[derive(Debug, Clone)]
+pub struct PolygonData {
+    pub points: Vec<(f32, f32)>,
+    pub fill: Fill,
+    pub blend_mode: BlendMode,
+}
+impl PolygonData {
+    pub fn new(points: Vec<(f32, f32)>, fill: impl Into<Fill>) -> Self {
+        Self {
+            points,
+            fill: fill.into(),
+            blend_mode: BlendMode::Normal,
+        }
+    }
+
+    pub fn new_shape(points: Vec<(f32, f32)>, fill: impl Into<Fill>) -> Shape {
+        Shape::Polygon(Self::new(points, fill))
     }
 
-    pub fn create_buffer(&self, device: &Device, width: u32, height: u32) -> Buffer {
-        let (x, y, _, _) = self.bounding_box();
-        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Circle Uniform Buffer"),
-            contents: bytemuck::cast_slice(&[width, x, y, self.colour]),
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    /// A triangle, the simplest polygon.
+    pub fn new_triangle(points: [(f32, f32); 3], fill: impl Into<Fill>) -> Shape {
+        Self::new_shape(points.to_vec(), fill)
+    }
+
+    pub fn with_blend_mode(self, blend_mode: BlendMode) -> Self {
+        Self { blend_mode, ..self }
+    }
+
+    /// Splits the polygon into triangles by clipping ears.
+    pub fn triangulate(&self) -> Vec<[(f32, f32); 3]> {
+        let mut points = self.points.clone();
+        points.dedup();
+        if points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        if points.len() < 3 {
+            return vec![];
+        }
+
+        let cross = |a: (f32, f32), b: (f32, f32), c: (f32, f32)| {
+            (b.0 - a.0) * (c.1 - b.1) - (b.1 - a.1) * (c.0 - b.0)
+        };
+        // Ears turn the same way as the polygon as a whole.
+        let area: f32 = (0..points.len())
+            .map(|i| {
+                let (a, b) = (points[i], points[(i + 1) % points.len()]);
+                a.0 * b.1 - b.0 * a.1
+            })
+            .sum();
+        let winding = area.signum();
+        let contains = |[a, b, c]: [(f32, f32); 3], point: (f32, f32)| {
+            cross(a, b, point) * winding >= 0.0
+                && cross(b, c, point) * winding >= 0.0
+                && cross(c, a, point) * winding >= 0.0
+        };
+
+        let mut remaining: Vec<usize> = (0..points.len()).collect();
+        let mut triangles = vec![];
+        while remaining.len() > 3 {
+            let count = remaining.len();
+            let corner = |i: usize| {
+                [
+                    points[remaining[(i + count - 1) % count]],
+                    points[remaining[i]],
+                    points[remaining[(i + 1) % count]],
+                ]
+            };
+            let is_ear = |i: usize| {
+                let triangle = corner(i);
+                cross(triangle[0], triangle[1], triangle[2]) * winding > 0.0
+                    && remaining
+                        .iter()
+                        .map(|&x| points[x])
+                        .filter(|x| !triangle.contains(x))
+                        .all(|x| !contains(triangle, x))
+            };
+            // Polygons which cross themselves may have no ears, so clip a corner anyway.
+            let ear = (0..count).find(|&i| is_ear(i)).unwrap_or(0);
+            triangles.push(corner(ear));
+            remaining.remove(ear);
+        }
+        triangles.push([
+            points[remaining[0]],
+            points[remaining[1]],
+            points[remaining[2]],
+        ]);
+        triangles
+    }
+
+    /// Appends the vertices of the triangles to `points`, which the record refers to by index.
+    pub fn to_record(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+        points: &mut Vec<[f32; 2]>,
+    ) -> Option<ShapeRecord> {
+        let triangles = self.triangulate();
+        if triangles.is_empty() {
+            return None;
+        }
+        let clip = self.clipped_box(antialiasing, width, height)?.into();
+        let (origin_x, origin_y, _, _) = self.bounding_box();
+        let first = points.len();
+        points.extend(triangles.iter().flatten().map(|&(x, y)| [x, y]));
+        Some(ShapeRecord {
+            kind: ShapeRecord::POLYGON,
+            blend_mode: self.blend_mode as u32,
+            clip,
+            origin: [origin_x, origin_y],
+            params: [
+                [first as f32, (points.len() - first) as f32, 0.0, 0.0],
+                [0.0; 4],
+                [0.0; 4],
+            ],
+            ..Default::default()
         })
     }
 
-    pub fn bounding_box(&self) -> (u32, u32, u32, u32) {
+    /// The pixels whose centres could be inside the polygon, which may lie partly or entirely off the canvas.
+    /// Its samples all lie within those pixels, so antialiasing doesn't grow it.
+    pub fn bounding_box(&self) -> (i32, i32, u32, u32) {
+        box_around(&self.points, 0.0)
+    }
+
+    pub fn clipped_box(
+        &self,
+        _antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+    ) -> Option<(u32, u32, u32, u32)> {
+        clip_box(self.bounding_box(), width, height)
+    }
+}
+
+/// The whole pixels around `points`, grown by `margin` on every side.
+pub fn box_around(points: &[(f32, f32)], margin: f32) -> (i32, i32, u32, u32) {
+    let (min_x, min_y, max_x, max_y) = points.iter().fold(
         (
-            self.position.0.floor() as u32,
-            self.position.1.floor() as u32,
-            self.size.0.floor() as u32,
-            self.size.1.floor() as u32,
-        )
+            f32::INFINITY,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            f32::NEG_INFINITY,
+        ),
+        |(min_x, min_y, max_x, max_y), &(x, y)| {
+            (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+        },
+    );
+    let (x, y) = ((min_x - margin).floor(), (min_y - margin).floor());
+    (
+        x as i32,
+        y as i32,
+        ((max_x + margin).ceil() - x) as u32,
+        ((max_y + margin).ceil() - y) as u32,
+    )
+}
+
+/// Intersects a box with a `width` by `height` canvas.
+/// Returns `None` when nothing is left, so the shape can be culled.
+pub fn clip_box(
+    (x, y, box_width, box_height): (i32, i32, u32, u32),
+    width: u32,
+    height: u32,
+) -> Option<(u32, u32, u32, u32)> {
+    let clip = |start: i32, length: u32, max: u32| {
+        let start = start as i64;
+        let end = (start + length as i64).min(max as i64);
+        let start = start.max(0);
+        (start < end).then_some((start as u32, (end - start) as u32))
+    };
+    let (x, box_width) = clip(x, box_width, width)?;
+    let (y, box_height) = clip(y, box_height, height)?;
+    Some((x, y, box_width, box_height))
+}
+
+/// The smallest box around two clipped boxes, either of which may be empty.
+pub fn union_box(
+    a: Option<(u32, u32, u32, u32)>,
+    b: Option<(u32, u32, u32, u32)>,
+) -> Option<(u32, u32, u32, u32)> {
+    let (Some(a), Some(b)) = (a, b) else {
+        return a.or(b);
+    };
+    let (x, y) = (a.0.min(b.0), a.1.min(b.1));
+    let (right, bottom) = ((a.0 + a.2).max(b.0 + b.2), (a.1 + a.3).max(b.1 + b.3));
+    Some((x, y, right - x, bottom - y))
+}
+
+/// The pixels two clipped boxes share, or `None` if they don't overlap.
+pub fn intersect_box(
+    a: (u32, u32, u32, u32),
+    b: (u32, u32, u32, u32),
+) -> Option<(u32, u32, u32, u32)> {
+    let (x, y) = (a.0.max(b.0), a.1.max(b.1));
+    let (right, bottom) = ((a.0 + a.2).min(b.0 + b.2), (a.1 + a.3).min(b.1 + b.3));
+    (x < right && y < bottom).then_some((x, y, right - x, bottom - y))
+}
+
+/// A shape as the kernels see it, laid out like `Shape` in shader.wgsl.
+/// Both renderers draw from these, so they agree on every pixel, give or take rounding where
+/// blending converts to and from linear light.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ShapeRecord {
+    pub kind: u32,
+    /// Packed as [`Color::to_packed`] describes.
+    pub colour: u32,
+    pub blend_mode: u32,
+    /// 0 to fill with `colour`, otherwise one more than the index of the gradient to fill with.
+    pub gradient: u32,
+    /// The clipped box on the canvas: x, y, width and height.
+    pub clip: [u32; 4],
+    /// The unclipped corner of the bounding box, which may be off the canvas.
+    pub origin: [i32; 2],
+    pub _padding_origin: [u32; 2],
+    /// Shape-specific parameters, see `to_record` on each shape.
+    pub params: [[f32; 4]; 3],
+}
+impl ShapeRecord {
+    pub const CIRCLE: u32 = 0;
+    pub const RECTANGLE: u32 = 1;
+    pub const ROTATED_RECTANGLE: u32 = 2;
+    pub const ELLIPSE: u32 = 3;
+    pub const ROUNDED_RECTANGLE: u32 = 4;
+    pub const POLYLINE: u32 = 5;
+    pub const PATH: u32 = 6;
+    /// Drawn from coverage rasterized by the polygon render pipeline.
+    pub const POLYGON: u32 = 7;
+    pub const CIRCLE_OUTLINE: u32 = 8;
+    pub const RECTANGLE_OUTLINE: u32 = 9;
+    /// Drawn from the frame's image at the index in its parameters, bound as a texture.
+    pub const IMAGE: u32 = 10;
+    /// Starts drawing into a new transparent layer, until the group's mask or end.
+    pub const GROUP_START: u32 = 11;
+    /// Starts drawing the group's mask into another layer.
+    pub const GROUP_MASK: u32 = 12;
+    /// Composites the group's layer through its mask onto the layer beneath, over the clipped box.
+    pub const GROUP_END: u32 = 13;
+    /// Filters the layer being drawn into with the compute passes of the effect in its parameters.
+    pub const EFFECT: u32 = 14;
+    /// Drawn by the kernel of the frame's custom shape at the index in its parameters.
+    pub const CUSTOM: u32 = 15;
+
+    pub fn contains(&self, (x, y): (u32, u32)) -> bool {
+        let [clip_x, clip_y, clip_width, clip_height] = self.clip;
+        x >= clip_x && y >= clip_y && x - clip_x < clip_width && y - clip_y < clip_height
+    }
+}
+
+/// A frame's shapes as the kernels see them, with those entirely off the canvas culled.
+#[derive(Debug, Clone, Default)]
+pub struct FrameRecords {
+    pub shapes: Vec<ShapeRecord>,
+    /// The points of shapes with any number of them, such as polylines, paths and polygons' triangles,
+    /// which refer to them by index.
+    pub points: Vec<[f32; 2]>,
+    /// The gradients of shapes with gradient fills, which refer to them by index.
+    pub gradients: Vec<GradientRecord>,
+    /// The colour stops of `gradients`.
+    pub stops: Vec<GradientStop>,
+    /// The pictures image shapes draw, which refer to them by index.
+    pub images: Vec<ImageSource>,
+    /// The kernels of blurring effects, which refer to them by index.
+    pub weights: Vec<f32>,
+    /// Custom shapes, whose records refer to them by index for their shaders and uniforms.
+    pub custom_shapes: Vec<CustomData>,
+}
+impl FrameRecords {
+    pub fn new(shapes: &[Shape], antialiasing: Antialiasing, width: u32, height: u32) -> Self {
+        let mut records = Self::default();
+        for shape in shapes {
+            shape.to_records(antialiasing, width, height, &mut records);
+        }
+        records
+    }
+
+    /// How many of each kind of record there are, to go back to with `truncate`.
+    pub(crate) fn lengths(&self) -> [usize; 7] {
+        [
+            self.shapes.len(),
+            self.points.len(),
+            self.gradients.len(),
+            self.stops.len(),
+            self.images.len(),
+            self.weights.len(),
+            self.custom_shapes.len(),
+        ]
+    }
+
+    /// Drops every record added since `lengths` was called.
+    pub(crate) fn truncate(
+        &mut self,
+        [shapes, points, gradients, stops, images, weights, custom_shapes]: [usize; 7],
+    ) {
+        self.shapes.truncate(shapes);
+        self.points.truncate(points);
+        self.gradients.truncate(gradients);
+        self.stops.truncate(stops);
+        self.images.truncate(images);
+        self.weights.truncate(weights);
+        self.custom_shapes.truncate(custom_shapes);
+    }
+
+    /// Appends `record` filled with `fill`, if it is on the canvas.
+    pub fn push(&mut self, record: Option<ShapeRecord>, fill: &Fill) {
+        let Some(mut record) = record else {
+            return;
+        };
+        match fill.to_gradient_record(self.stops.len()) {
+            Some(gradient) => {
+                self.gradients.push(gradient);
+                self.stops.extend_from_slice(fill.stops());
+                record.gradient = self.gradients.len() as u32;
+            }
+            None => record.colour = fill.solid_colour().to_packed(),
+        }
+        self.shapes.push(record);
     }
 }
 
@@ -96,65 +1485,438 @@ pub struct GpuInstance {
     pub width: u32,
     pub height: u32,
     pub instance: wgpu::Instance,
+    /// Which adapter the device is on, such as to report whether it fell back to the CPU.
+    pub adapter_info: wgpu::AdapterInfo,
     pub device: Device,
     pub queue: Queue,
-    pub circle_compute_pipeline: ComputePipeline,
-    pub rect_compute_pipeline: ComputePipeline,
+    /// What the pipelines were compiled from, which custom shaders are compiled after too.
+    pub shaders: ShaderSources,
+    /// Draws one shape per dispatch.
+    pub shape_compute_pipeline: ComputePipeline,
+    /// Draws a whole frame of shapes in one tiled dispatch.
+    pub batch_compute_pipeline: ComputePipeline,
+    pub canvas_compute_pipeline: ComputePipeline,
+    /// Composites a group's layer onto the one beneath it.
+    pub group_compute_pipeline: ComputePipeline,
+    /// Runs one pass of an effect over a layer.
+    pub effect_compute_pipeline: ComputePipeline,
+    /// Adds a sub-frame to the sum motion blur averages.
+    pub accumulate_compute_pipeline: ComputePipeline,
+    /// Replaces the output with the average of the summed sub-frames.
+    pub resolve_compute_pipeline: ComputePipeline,
+    /// Rasterizes the coverage of a polygon's triangles, one sample per pixel.
+    pub polygon_render_pipeline: RenderPipeline,
+    /// Rasterizes the coverage of a polygon's triangles with `POLYGON_SAMPLE_COUNT` samples per pixel.
+    pub polygon_multisample_render_pipeline: RenderPipeline,
+    /// None if the adapter can't shade each sample or read storage buffers while rasterizing.
+    pub raster_pipelines: Option<RasterPipelines>,
+}
+
+/// Draws shapes as instanced quads into a render target of `GpuInstance::RASTER_FORMAT`,
+/// an alternative to the compute kernels.
+pub struct RasterPipelines {
+    /// Draws the canvas into a render target with one sample per pixel.
+    pub canvas_render_pipeline: RenderPipeline,
+    /// Draws the canvas into a render target with `RASTER_SAMPLE_COUNT` samples per pixel.
+    pub canvas_multisample_render_pipeline: RenderPipeline,
+    /// Draws shapes with one sample per pixel.
+    pub shape_render_pipeline: RenderPipeline,
+    /// Draws shapes with `RASTER_SAMPLE_COUNT` samples per pixel.
+    pub shape_multisample_render_pipeline: RenderPipeline,
+    /// Stores the resolved render target back into the canvas.
+    pub store_compute_pipeline: ComputePipeline,
 }
 impl GpuInstance {
-    pub async fn new(width: u32, height: u32, circle_shader: &str, rect_shader: &str) -> Self {
-        let instance = wgpu::Instance::default();
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions::default())
-            .await
-            .unwrap();
+    /// The format polygon coverage is rasterized in, which holds every fraction of
+    /// `POLYGON_SAMPLE_COUNT` exactly.
+    pub const COVERAGE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R16Float;
+
+    /// The format shapes are rasterized in, precise enough for linear colours
+    /// to come back as the same 8 bit sRGB ones.
+    pub const RASTER_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+    /// Sets up the adapter `options` ask for and compiles `sources`, which are validated first.
+    pub async fn new(
+        width: u32,
+        height: u32,
+        sources: &ShaderSources,
+        options: &AdapterOptions,
+    ) -> Result<Self, GpuError> {
+        sources.validate()?;
+        let instance = options.create_instance();
+        let adapter = options.request_adapter(&instance).await?;
+        let adapter_info = adapter.get_info();
+        let raster = adapter.get_downlevel_capabilities().flags.contains(
+            wgpu::DownlevelFlags::MULTISAMPLED_SHADING
+                | wgpu::DownlevelFlags::VERTEX_STORAGE
+                | wgpu::DownlevelFlags::FRAGMENT_STORAGE,
+        );
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: None,
                     required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::downlevel_defaults(),
+                    // The shape kernels bind six storage buffers, more than the downlevel four,
+                    // and images may be as large as the adapter allows, beyond which they're
+                    // scaled down to fit.
+                    required_limits: wgpu::Limits {
+                        max_storage_buffers_per_shader_stage: 6,
+                        max_texture_dimension_2d: adapter.limits().max_texture_dimension_2d,
+                        ..wgpu::Limits::downlevel_defaults()
+                    },
                     memory_hints: wgpu::MemoryHints::MemoryUsage,
                 },
                 None,
             )
             .await
-            .unwrap();
-        let circle_cs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            .map_err(|error| GpuError::Device {
+                adapter: Box::new(adapter_info.clone()),
+                error,
+            })?;
+        let Pipelines {
+            shape_compute_pipeline,
+            batch_compute_pipeline,
+            canvas_compute_pipeline,
+            group_compute_pipeline,
+            effect_compute_pipeline,
+            accumulate_compute_pipeline,
+            resolve_compute_pipeline,
+            polygon_render_pipeline,
+            polygon_multisample_render_pipeline,
+            raster_pipelines,
+        } = Pipelines::new(&device, sources, raster);
+        Ok(Self {
+            width,
+            height,
+            instance,
+            adapter_info,
+            device,
+            queue,
+            shaders: sources.clone(),
+            shape_compute_pipeline,
+            batch_compute_pipeline,
+            canvas_compute_pipeline,
+            group_compute_pipeline,
+            effect_compute_pipeline,
+            accumulate_compute_pipeline,
+            resolve_compute_pipeline,
+            polygon_render_pipeline,
+            polygon_multisample_render_pipeline,
+            raster_pipelines,
+        })
+    }
+
+    /// Recompiles every pipeline from `sources`, keeping the old ones if they're invalid.
+    /// Custom shaders are compiled after the new declarations from then on.
+    pub fn reload_shaders(&mut self, sources: &ShaderSources) -> Result<(), ShaderError> {
+        sources.validate()?;
+        let Pipelines {
+            shape_compute_pipeline,
+            batch_compute_pipeline,
+            canvas_compute_pipeline,
+            group_compute_pipeline,
+            effect_compute_pipeline,
+            accumulate_compute_pipeline,
+            resolve_compute_pipeline,
+            polygon_render_pipeline,
+            polygon_multisample_render_pipeline,
+            raster_pipelines,
+        } = Pipelines::new(&self.device, sources, self.raster_pipelines.is_some());
+        self.shape_compute_pipeline = shape_compute_pipeline;
+        self.batch_compute_pipeline = batch_compute_pipeline;
+        self.canvas_compute_pipeline = canvas_compute_pipeline;
+        self.group_compute_pipeline = group_compute_pipeline;
+        self.effect_compute_pipeline = effect_compute_pipeline;
+        self.accumulate_compute_pipeline = accumulate_compute_pipeline;
+        self.resolve_compute_pipeline = resolve_compute_pipeline;
+        self.polygon_render_pipeline = polygon_render_pipeline;
+        self.polygon_multisample_render_pipeline = polygon_multisample_render_pipeline;
+        self.raster_pipelines = raster_pipelines;
+        self.shaders = sources.clone();
+        Ok(())
+    }
+}
+
+/// The pipelines of a `GpuInstance`, compiled from a set of shaders.
+struct Pipelines {
+    shape_compute_pipeline: ComputePipeline,
+    batch_compute_pipeline: ComputePipeline,
+    canvas_compute_pipeline: ComputePipeline,
+    group_compute_pipeline: ComputePipeline,
+    effect_compute_pipeline: ComputePipeline,
+    accumulate_compute_pipeline: ComputePipeline,
+    resolve_compute_pipeline: ComputePipeline,
+    polygon_render_pipeline: RenderPipeline,
+    polygon_multisample_render_pipeline: RenderPipeline,
+    raster_pipelines: Option<RasterPipelines>,
+}
+impl Pipelines {
+    /// `sources` must have been validated, or invalid WGSL panics.
+    /// The raster pipelines are only created if `raster` is set.
+    fn new(device: &Device, sources: &ShaderSources, raster: bool) -> Self {
+        let shape_cs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: None,
-            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(circle_shader)),
+            source: wgpu::ShaderSource::Wgsl(Cow::Owned(sources.shape_module())),
         });
-        let rect_cs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        let canvas_cs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: None,
-            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(rect_shader)),
+            source: wgpu::ShaderSource::Wgsl(Cow::Owned(sources.canvas_module())),
         });
-        let circle_compute_pipeline =
+        let shape_compute_pipeline =
             device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
                 label: None,
                 layout: None,
-                module: &circle_cs_module,
+                module: &shape_cs_module,
                 entry_point: "main",
                 compilation_options: Default::default(),
                 cache: None,
             });
 
-        let rect_compute_pipeline =
+        let batch_compute_pipeline =
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: None,
+                layout: None,
+                module: &shape_cs_module,
+                entry_point: "main_tiled",
+                compilation_options: Default::default(),
+                cache: None,
+            });
+
+        let canvas_compute_pipeline =
             device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
                 label: None,
                 layout: None,
-                module: &rect_cs_module,
+                module: &canvas_cs_module,
                 entry_point: "main",
                 compilation_options: Default::default(),
                 cache: None,
             });
+
+        let group_compute_pipeline =
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: None,
+                layout: None,
+                module: &canvas_cs_module,
+                entry_point: "composite_group",
+                compilation_options: Default::default(),
+                cache: None,
+            });
+
+        let accumulate_compute_pipeline =
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: None,
+                layout: None,
+                module: &canvas_cs_module,
+                entry_point: "accumulate_sub_frame",
+                compilation_options: Default::default(),
+                cache: None,
+            });
+
+        let resolve_compute_pipeline =
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: None,
+                layout: None,
+                module: &canvas_cs_module,
+                entry_point: "resolve_sub_frames",
+                compilation_options: Default::default(),
+                cache: None,
+            });
+
+        // Passes read one layer and write another, and bind both whether or not they use them,
+        // so they all share this layout.
+        let storage_entry = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let effect_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[&device.create_bind_group_layout(
+                    &wgpu::BindGroupLayoutDescriptor {
+                        label: None,
+                        entries: &[
+                            storage_entry(0, false),
+                            wgpu::BindGroupLayoutEntry {
+                                binding: 1,
+                                visibility: wgpu::ShaderStages::COMPUTE,
+                                ty: wgpu::BindingType::Buffer {
+                                    ty: wgpu::BufferBindingType::Uniform,
+                                    has_dynamic_offset: false,
+                                    min_binding_size: None,
+                                },
+                                count: None,
+                            },
+                            storage_entry(2, true),
+                            storage_entry(3, true),
+                        ],
+                    },
+                )],
+                push_constant_ranges: &[],
+            });
+        let effect_cs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(Cow::Owned(sources.effect_module())),
+        });
+        let effect_compute_pipeline =
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: None,
+                layout: Some(&effect_pipeline_layout),
+                module: &effect_cs_module,
+                entry_point: "main",
+                compilation_options: Default::default(),
+                cache: None,
+            });
+
+        let polygon_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(Cow::Owned(sources.polygon_module())),
+        });
+        let polygon_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[&device.create_bind_group_layout(
+                    &wgpu::BindGroupLayoutDescriptor {
+                        label: None,
+                        entries: &[wgpu::BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: wgpu::ShaderStages::VERTEX,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Uniform,
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        }],
+                    },
+                )],
+                push_constant_ranges: &[],
+            });
+        let polygon_render_pipeline = |sample_count| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: Some(&polygon_pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &polygon_module,
+                    entry_point: "vertex",
+                    compilation_options: Default::default(),
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![0 => Float32x2],
+                    }],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &polygon_module,
+                    entry_point: "fragment",
+                    compilation_options: Default::default(),
+                    targets: &[Some(GpuInstance::COVERAGE_FORMAT.into())],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: sample_count,
+                    ..Default::default()
+                },
+                multiview: None,
+                cache: None,
+            })
+        };
+        let polygon_multisample_render_pipeline = polygon_render_pipeline(POLYGON_SAMPLE_COUNT);
+        let polygon_render_pipeline = polygon_render_pipeline(1);
+        let raster_pipelines = raster.then(|| RasterPipelines::new(device, sources));
         Self {
-            width,
-            height,
-            instance,
-            device,
-            queue,
-            circle_compute_pipeline,
-            rect_compute_pipeline,
+            shape_compute_pipeline,
+            batch_compute_pipeline,
+            canvas_compute_pipeline,
+            group_compute_pipeline,
+            effect_compute_pipeline,
+            accumulate_compute_pipeline,
+            resolve_compute_pipeline,
+            polygon_render_pipeline,
+            polygon_multisample_render_pipeline,
+            raster_pipelines,
+        }
+    }
+}
+impl RasterPipelines {
+    fn new(device: &Device, sources: &ShaderSources) -> Self {
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(Cow::Owned(sources.raster_module())),
+        });
+        // The canvas replaces whatever the target held, and shapes are composited over it.
+        let render_pipeline = |vertex, fragment, blend, topology, sample_count| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: None,
+                vertex: wgpu::VertexState {
+                    module: &module,
+                    entry_point: vertex,
+                    compilation_options: Default::default(),
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &module,
+                    entry_point: fragment,
+                    compilation_options: Default::default(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: GpuInstance::RASTER_FORMAT,
+                        blend,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: sample_count,
+                    ..Default::default()
+                },
+                multiview: None,
+                cache: None,
+            })
+        };
+        let canvas_render_pipeline = |sample_count| {
+            render_pipeline(
+                "canvas_vertex",
+                "canvas_fragment",
+                None,
+                wgpu::PrimitiveTopology::TriangleList,
+                sample_count,
+            )
+        };
+        let shape_render_pipeline = |sample_count| {
+            render_pipeline(
+                "raster_vertex",
+                "raster_fragment",
+                Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                wgpu::PrimitiveTopology::TriangleStrip,
+                sample_count,
+            )
+        };
+        let store_compute_pipeline =
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: None,
+                layout: None,
+                module: &module,
+                entry_point: "store_raster",
+                compilation_options: Default::default(),
+                cache: None,
+            });
+        Self {
+            canvas_render_pipeline: canvas_render_pipeline(1),
+            canvas_multisample_render_pipeline: canvas_render_pipeline(RASTER_SAMPLE_COUNT),
+            shape_render_pipeline: shape_render_pipeline(1),
+            shape_multisample_render_pipeline: shape_render_pipeline(RASTER_SAMPLE_COUNT),
+            store_compute_pipeline,
         }
     }
 }
@@ -163,19 +1925,111 @@ impl GpuInstance {
 pub enum Shape {
     Circle(CircleData),
     Rectangle(RectangleData),
+    Ellipse(EllipseData),
+    RoundedRectangle(RoundedRectangleData),
+    Polyline(PolylineData),
+    Path(PathData),
+    Polygon(PolygonData),
+    Image(ImageData),
+    Text(TextData),
+    Group(GroupData),
+    Effect(Effect),
+    Custom(CustomData),
 }
 impl Shape {
-    pub fn create_buffer(&self, device: &Device, width: u32, height: u32) -> Buffer {
+    /// Appends the shape as the kernels see it to `records`, or nothing if it is entirely off the canvas.
+    /// Most shapes are one record, but outlined shapes add one for their outline, paths one for each subpath's,
+    /// and text one for each glyph. Groups add their children's between records marking their layers.
+    pub fn to_records(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+        records: &mut FrameRecords,
+    ) {
         match self {
-            Shape::Circle(x) => x.create_buffer(device, width, height),
-            Shape::Rectangle(x) => x.create_buffer(device, width, height),
+            Shape::Circle(x) => {
+                records.push(x.to_record(antialiasing, width, height), &x.fill);
+                records
+                    .shapes
+                    .extend(x.stroke_record(antialiasing, width, height));
+            }
+            Shape::Rectangle(x) => {
+                records.push(x.to_record(antialiasing, width, height), &x.fill);
+                records
+                    .shapes
+                    .extend(x.stroke_record(antialiasing, width, height));
+            }
+            Shape::Ellipse(x) => records.push(x.to_record(antialiasing, width, height), &x.fill),
+            Shape::RoundedRectangle(x) => {
+                records.push(x.to_record(antialiasing, width, height), &x.fill)
+            }
+            Shape::Polyline(x) => {
+                let record = x.to_record(antialiasing, width, height, &mut records.points);
+                records.push(record, &x.fill);
+            }
+            Shape::Polygon(x) => {
+                let record = x.to_record(antialiasing, width, height, &mut records.points);
+                records.push(record, &x.fill);
+            }
+            Shape::Path(x) => x.to_records(antialiasing, width, height, records),
+            Shape::Image(x) => {
+                let record = x.to_record(antialiasing, width, height, &mut records.images);
+                records.shapes.extend(record);
+            }
+            Shape::Text(x) => {
+                for glyph in x.glyphs() {
+                    let record = glyph.to_record(antialiasing, width, height, &mut records.images);
+                    records.shapes.extend(record);
+                }
+            }
+            Shape::Group(x) => x.to_records(antialiasing, width, height, records),
+            Shape::Effect(x) => x.to_records(width, height, records),
+            Shape::Custom(x) => {
+                let record = x.to_record(width, height, &mut records.custom_shapes);
+                records.shapes.extend(record);
+            }
         }
     }
 
-    pub fn bounding_box(&self) -> (u32, u32, u32, u32) {
+    pub fn bounding_box(&self) -> (i32, i32, u32, u32) {
         match self {
             Shape::Circle(x) => x.bounding_box(),
             Shape::Rectangle(x) => x.bounding_box(),
+            Shape::Ellipse(x) => x.bounding_box(),
+            Shape::RoundedRectangle(x) => x.bounding_box(),
+            Shape::Polyline(x) => x.bounding_box(),
+            Shape::Path(x) => x.bounding_box(),
+            Shape::Polygon(x) => x.bounding_box(),
+            Shape::Image(x) => x.bounding_box(),
+            Shape::Text(x) => x.bounding_box(),
+            Shape::Group(x) => x.bounding_box(),
+            // Effects cover the whole canvas, whatever its size.
+            Shape::Effect(_) => (0, 0, 0, 0),
+            Shape::Custom(x) => x.bounding_box(),
+        }
+    }
+
+    /// The on-canvas pixels a shape may touch, or `None` if it is entirely off the canvas.
+    pub fn clipped_box(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+    ) -> Option<(u32, u32, u32, u32)> {
+        match self {
+            Shape::Circle(x) => x.clipped_box(antialiasing, width, height),
+            Shape::Rectangle(x) => x.clipped_box(antialiasing, width, height),
+            Shape::Ellipse(x) => x.clipped_box(antialiasing, width, height),
+            Shape::RoundedRectangle(x) => x.clipped_box(antialiasing, width, height),
+            Shape::Polyline(x) => x.clipped_box(antialiasing, width, height),
+            Shape::Path(x) => x.clipped_box(antialiasing, width, height),
+            Shape::Polygon(x) => x.clipped_box(antialiasing, width, height),
+            Shape::Image(x) => x.clipped_box(antialiasing, width, height),
+            Shape::Text(x) => x.clipped_box(antialiasing, width, height),
+            Shape::Group(x) => x.clipped_box(antialiasing, width, height),
+            Shape::Effect(_) => Some((0, 0, width, height)),
+            Shape::Custom(x) => x.clipped_box(width, height),
         }
     }
 }
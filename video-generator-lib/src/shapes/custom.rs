@@ -0,0 +1,139 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use super::{box_around, clip_box, BlendMode, Shape, ShapeRecord};
+
+/// A compute kernel in WGSL which draws a custom shape, compiled once by the GPU renderer
+/// and shared by every shape using it.
+///
+/// The source follows shader-common.wgsl and shader-custom.wgsl, so it can use their helpers,
+/// and defines a `main` entry point with a workgroup size of 8 by 8. That is run over the shape's
+/// clipped box, writing into `v_indices_output` as the built-in kernels do. The shape's uniforms,
+/// if it has any, are bound at `@group(0) @binding(2)`.
+#[derive(Clone)]
+pub struct CustomShader {
+    inner: Arc<CustomShaderInner>,
+}
+
+struct CustomShaderInner {
+    /// Tells shaders apart without comparing their source.
+    id: u64,
+    source: String,
+}
+
+impl CustomShader {
+    pub fn new(source: impl Into<String>) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        Self {
+            inner: Arc::new(CustomShaderInner {
+                id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+                source: source.into(),
+            }),
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.inner.id
+    }
+
+    pub fn source(&self) -> &str {
+        &self.inner.source
+    }
+
+    /// Whether this is the last handle to the shader, so caches holding it may let it go.
+    pub fn is_unique(&self) -> bool {
+        Arc::strong_count(&self.inner) == 1
+    }
+}
+impl fmt::Debug for CustomShader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomShader")
+            .field("id", &self.id())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A box drawn by a [`CustomShader`], such as a procedural background.
+/// Only the GPU renderer can run WGSL, so the CPU renderer leaves custom shapes out,
+/// as [`Renderer::supports_custom_shapes`](crate::renderer::Renderer::supports_custom_shapes) tells.
+#[derive(Debug, Clone)]
+pub struct CustomData {
+    pub shader: CustomShader,
+    /// The top left corner.
+    pub position: (f32, f32),
+    pub size: (f32, f32),
+    /// The bytes of the shader's uniform struct, laid out as WGSL expects.
+    pub uniforms: Vec<u8>,
+    pub blend_mode: BlendMode,
+}
+impl CustomData {
+    pub fn new(shader: CustomShader, position: (f32, f32), size: (f32, f32)) -> Self {
+        Self {
+            shader,
+            position,
+            size,
+            uniforms: vec![],
+            blend_mode: BlendMode::Normal,
+        }
+    }
+
+    pub fn new_shape(shader: CustomShader, position: (f32, f32), size: (f32, f32)) -> Shape {
+        Shape::Custom(Self::new(shader, position, size))
+    }
+
+    /// `uniforms` must match the layout of the shader's struct, padding included.
+    pub fn with_uniforms<T: bytemuck::Pod>(self, uniforms: &T) -> Self {
+        Self {
+            uniforms: bytemuck::bytes_of(uniforms).to_vec(),
+            ..self
+        }
+    }
+
+    pub fn with_blend_mode(self, blend_mode: BlendMode) -> Self {
+        Self { blend_mode, ..self }
+    }
+
+    /// Adds the shape to `custom_shapes`, where the renderer finds its shader and uniforms,
+    /// and refers to it by index.
+    pub fn to_record(
+        &self,
+        width: u32,
+        height: u32,
+        custom_shapes: &mut Vec<CustomData>,
+    ) -> Option<ShapeRecord> {
+        if self.size.0 <= 0.0 || self.size.1 <= 0.0 {
+            return None;
+        }
+        let clip = self.clipped_box(width, height)?.into();
+        custom_shapes.push(self.clone());
+        let (origin_x, origin_y, _, _) = self.bounding_box();
+        Some(ShapeRecord {
+            kind: ShapeRecord::CUSTOM,
+            blend_mode: self.blend_mode as u32,
+            clip,
+            origin: [origin_x, origin_y],
+            params: [
+                [self.position.0, self.position.1, self.size.0, self.size.1],
+                [(custom_shapes.len() - 1) as f32, 0.0, 0.0, 0.0],
+                [0.0; 4],
+            ],
+            ..Default::default()
+        })
+    }
+
+    /// The pixels the shader is run over, which may lie partly or entirely off the canvas.
+    pub fn bounding_box(&self) -> (i32, i32, u32, u32) {
+        let (x, y) = self.position;
+        box_around(&[(x, y), (x + self.size.0, y + self.size.1)], 0.0)
+    }
+
+    /// Shaders draw whatever edges they like, so antialiasing doesn't grow the box.
+    pub fn clipped_box(&self, width: u32, height: u32) -> Option<(u32, u32, u32, u32)> {
+        clip_box(self.bounding_box(), width, height)
+    }
+}
@@ -0,0 +1,199 @@
+use super::{FrameRecords, ShapeRecord};
+
+/// A filter over everything drawn before it in the frame, or in its group.
+/// Effects last in a frame post-process the whole rendered image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect {
+    /// A Gaussian blur with a standard deviation of `radius` pixels.
+    Blur { radius: f32 },
+    /// Makes pixels brighter than `threshold`, a luminance from 0.0 to 1.0, glow.
+    /// The glow is blurred by `radius` like [`Effect::Blur`], and added at `intensity` from 0.0 to 1.0.
+    Bloom {
+        threshold: f32,
+        radius: f32,
+        intensity: f32,
+    },
+    /// Darkens the corners. Pixels further than `radius` from the centre, where the corners are
+    /// about 1.4 away and the middles of the edges 1.0, fade over `softness` to `strength` darker.
+    Vignette {
+        strength: f32,
+        radius: f32,
+        softness: f32,
+    },
+    /// Noise of up to `amount` brighter or darker, which changes with `seed`,
+    /// so it moves when the seed differs from frame to frame.
+    FilmGrain { amount: f32, seed: u32 },
+    /// Splits red and blue away from the centre, by up to `offset` pixels at the edges.
+    ChromaticAberration { offset: f32 },
+    /// `brightness` is added to every channel, `contrast` scales them away from grey
+    /// and `saturation` away from the pixel's luminance, so 0.0, 1.0 and 1.0 change nothing.
+    ColourAdjust {
+        brightness: f32,
+        contrast: f32,
+        saturation: f32,
+    },
+}
+impl Effect {
+    /// The largest number of pixels blurs reach on each side, three standard deviations
+    /// of a 21.3 pixel radius.
+    pub const MAX_BLUR_REACH: usize = 64;
+
+    pub const BLUR: u32 = 0;
+    pub const BLOOM: u32 = 1;
+    pub const VIGNETTE: u32 = 2;
+    pub const FILM_GRAIN: u32 = 3;
+    pub const CHROMATIC_ABERRATION: u32 = 4;
+    pub const COLOUR_ADJUST: u32 = 5;
+
+    /// Appends the effect as an `EFFECT` record over the whole canvas, or nothing if it changes nothing.
+    /// Blurs add their kernel's weights to `records.weights`.
+    ///
+    /// The record's parameters are the kind and up to three of the effect's own,
+    /// then where its weights start and how many there are.
+    pub fn to_records(&self, width: u32, height: u32, records: &mut FrameRecords) {
+        let (kind, params, radius) = match *self {
+            Effect::Blur { radius } => (Self::BLUR, [0.0; 3], radius),
+            Effect::Bloom {
+                threshold,
+                radius,
+                intensity,
+            } => {
+                if intensity <= 0.0 {
+                    return;
+                }
+                (Self::BLOOM, [threshold, intensity.min(1.0), 0.0], radius)
+            }
+            Effect::Vignette {
+                strength,
+                radius,
+                softness,
+            } => {
+                if strength <= 0.0 {
+                    return;
+                }
+                (Self::VIGNETTE, [strength, radius, softness], 0.0)
+            }
+            Effect::FilmGrain { amount, seed } => {
+                if amount <= 0.0 {
+                    return;
+                }
+                // Seeds above 2^24 would lose bits as floats.
+                let seed = (seed & 0xFFFFFF) as f32;
+                (Self::FILM_GRAIN, [amount, seed, 0.0], 0.0)
+            }
+            Effect::ChromaticAberration { offset } => {
+                if offset == 0.0 {
+                    return;
+                }
+                (Self::CHROMATIC_ABERRATION, [offset, 0.0, 0.0], 0.0)
+            }
+            Effect::ColourAdjust {
+                brightness,
+                contrast,
+                saturation,
+            } => (Self::COLOUR_ADJUST, [brightness, contrast, saturation], 0.0),
+        };
+        let weights = blur_weights(radius);
+        if kind == Self::BLUR && weights.is_empty() {
+            return;
+        }
+        let first_weight = records.weights.len();
+        records.weights.extend_from_slice(&weights);
+        records.shapes.push(ShapeRecord {
+            kind: ShapeRecord::EFFECT,
+            clip: [0, 0, width, height],
+            params: [
+                [kind as f32, params[0], params[1], params[2]],
+                [first_weight as f32, weights.len() as f32, 0.0, 0.0],
+                [0.0; 4],
+            ],
+            ..Default::default()
+        });
+    }
+}
+
+/// Where a pass of an effect reads or writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PassLayer {
+    /// The layer the effect filters.
+    Drawn,
+    /// One of two layers for the passes' intermediate results.
+    Scratch(usize),
+}
+
+/// One compute pass of an effect, as both renderers run them.
+/// Passes whose source is their target change pixels in place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct EffectPass {
+    pub kind: u32,
+    pub source: PassLayer,
+    pub target: PassLayer,
+}
+impl EffectPass {
+    pub const BLUR_HORIZONTAL: u32 = 0;
+    pub const BLUR_VERTICAL: u32 = 1;
+    pub const BRIGHT: u32 = 2;
+    pub const ADD_GLOW: u32 = 3;
+    pub const VIGNETTE: u32 = 4;
+    pub const FILM_GRAIN: u32 = 5;
+    pub const CHROMATIC_ABERRATION: u32 = 6;
+    pub const COLOUR_ADJUST: u32 = 7;
+    /// Copies the source to the target, without a kernel.
+    pub const COPY: u32 = 8;
+
+    fn new(kind: u32, source: PassLayer, target: PassLayer) -> Self {
+        Self {
+            kind,
+            source,
+            target,
+        }
+    }
+
+    /// The passes of the effect in an `EFFECT` record.
+    pub fn passes(record: &ShapeRecord) -> Vec<Self> {
+        use PassLayer::{Drawn, Scratch};
+        let [kind, _, _, _] = record.params[0];
+        let blurred = record.params[1][1] > 0.0;
+        match kind as u32 {
+            Effect::BLUR => vec![
+                Self::new(Self::BLUR_HORIZONTAL, Drawn, Scratch(0)),
+                Self::new(Self::BLUR_VERTICAL, Scratch(0), Drawn),
+            ],
+            Effect::BLOOM if blurred => vec![
+                Self::new(Self::BRIGHT, Drawn, Scratch(0)),
+                Self::new(Self::BLUR_HORIZONTAL, Scratch(0), Scratch(1)),
+                Self::new(Self::BLUR_VERTICAL, Scratch(1), Scratch(0)),
+                Self::new(Self::ADD_GLOW, Scratch(0), Drawn),
+            ],
+            Effect::BLOOM => vec![
+                Self::new(Self::BRIGHT, Drawn, Scratch(0)),
+                Self::new(Self::ADD_GLOW, Scratch(0), Drawn),
+            ],
+            Effect::VIGNETTE => vec![Self::new(Self::VIGNETTE, Drawn, Drawn)],
+            Effect::FILM_GRAIN => vec![Self::new(Self::FILM_GRAIN, Drawn, Drawn)],
+            Effect::CHROMATIC_ABERRATION => vec![
+                Self::new(Self::COPY, Drawn, Scratch(0)),
+                Self::new(Self::CHROMATIC_ABERRATION, Scratch(0), Drawn),
+            ],
+            Effect::COLOUR_ADJUST => vec![Self::new(Self::COLOUR_ADJUST, Drawn, Drawn)],
+            _ => vec![],
+        }
+    }
+}
+
+/// The normalized weights of a Gaussian kernel with a standard deviation of `radius`,
+/// from the furthest pixel on one side to the furthest on the other. Empty for no blur.
+fn blur_weights(radius: f32) -> Vec<f32> {
+    if radius <= 0.0 {
+        return vec![];
+    }
+    let reach = ((radius * 3.0).ceil() as usize).min(Effect::MAX_BLUR_REACH);
+    let weights: Vec<_> = (0..=reach * 2)
+        .map(|i| {
+            let x = i as f32 - reach as f32;
+            (-x * x / (2.0 * radius * radius)).exp()
+        })
+        .collect();
+    let total: f32 = weights.iter().sum();
+    weights.into_iter().map(|x| x / total).collect()
+}
@@ -0,0 +1,179 @@
+use std::f32::consts::TAU;
+
+use crate::color::Color;
+
+/// A point along a gradient, from 0.0 at its start to 1.0 at its end, and the colour there.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GradientStop {
+    pub offset: f32,
+    /// Packed as [`Color::to_packed`] describes, since the kernels read stops as they are.
+    pub colour: u32,
+}
+impl GradientStop {
+    pub fn new(offset: f32, colour: impl Into<Color>) -> Self {
+        Self {
+            offset,
+            colour: colour.into().to_packed(),
+        }
+    }
+}
+
+/// What the inside of a shape is painted with. Gradients are positioned on the canvas,
+/// not relative to the shape, and pad with their first and last colours past their ends.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fill {
+    Solid(Color),
+    /// Runs from `start` to `end`, constant along lines at right angles to them.
+    Linear {
+        start: (f32, f32),
+        end: (f32, f32),
+        stops: Vec<GradientStop>,
+    },
+    /// Runs outwards from `centre` to `radius`.
+    Radial {
+        centre: (f32, f32),
+        radius: f32,
+        stops: Vec<GradientStop>,
+    },
+    /// Runs once clockwise around `centre`, starting at `angle` radians clockwise from the right.
+    Conic {
+        centre: (f32, f32),
+        angle: f32,
+        stops: Vec<GradientStop>,
+    },
+}
+impl Fill {
+    /// `stops` are offsets and colours, in any order.
+    pub fn linear(
+        start: (f32, f32),
+        end: (f32, f32),
+        stops: &[(f32, impl Into<Color> + Copy)],
+    ) -> Self {
+        Fill::Linear {
+            start,
+            end,
+            stops: sorted_stops(stops),
+        }
+    }
+
+    pub fn radial(
+        centre: (f32, f32),
+        radius: f32,
+        stops: &[(f32, impl Into<Color> + Copy)],
+    ) -> Self {
+        Fill::Radial {
+            centre,
+            radius,
+            stops: sorted_stops(stops),
+        }
+    }
+
+    pub fn conic(centre: (f32, f32), angle: f32, stops: &[(f32, impl Into<Color> + Copy)]) -> Self {
+        Fill::Conic {
+            centre,
+            angle,
+            stops: sorted_stops(stops),
+        }
+    }
+
+    /// The gradient as the kernels see it, without its stops, or `None` for solid fills.
+    pub(crate) fn to_gradient_record(&self, first_stop: usize) -> Option<GradientRecord> {
+        let (kind, params, stops) = match self {
+            Fill::Solid(_) => return None,
+            Fill::Linear { start, end, stops } => (
+                GradientRecord::LINEAR,
+                [start.0, start.1, end.0, end.1],
+                stops,
+            ),
+            Fill::Radial {
+                centre,
+                radius,
+                stops,
+            } => (
+                GradientRecord::RADIAL,
+                [centre.0, centre.1, *radius, 0.0],
+                stops,
+            ),
+            Fill::Conic {
+                centre,
+                angle,
+                stops,
+            } => (
+                GradientRecord::CONIC,
+                [centre.0, centre.1, angle.rem_euclid(TAU), 0.0],
+                stops,
+            ),
+        };
+        Some(GradientRecord {
+            kind,
+            first_stop: first_stop as u32,
+            stop_count: stops.len() as u32,
+            params,
+            ..Default::default()
+        })
+    }
+
+    /// The colour of solid fills, or transparent for gradients.
+    pub fn solid_colour(&self) -> Color {
+        match self {
+            Fill::Solid(colour) => *colour,
+            _ => Color::TRANSPARENT,
+        }
+    }
+
+    pub fn stops(&self) -> &[GradientStop] {
+        match self {
+            Fill::Solid(_) => &[],
+            Fill::Linear { stops, .. } | Fill::Radial { stops, .. } | Fill::Conic { stops, .. } => {
+                stops
+            }
+        }
+    }
+}
+impl Default for Fill {
+    fn default() -> Self {
+        Fill::Solid(Color::BLACK)
+    }
+}
+impl From<Color> for Fill {
+    fn from(colour: Color) -> Self {
+        Fill::Solid(colour)
+    }
+}
+/// A solid fill of a colour written as `0xAARRGGBB`, see [`Color`]'s `From<u32>`.
+impl From<u32> for Fill {
+    fn from(colour: u32) -> Self {
+        Fill::Solid(colour.into())
+    }
+}
+
+/// Stops sorted by offset, clamped from 0.0 to 1.0. Stops at the same offset keep their order,
+/// so the colour changes sharply there.
+fn sorted_stops(stops: &[(f32, impl Into<Color> + Copy)]) -> Vec<GradientStop> {
+    let mut stops: Vec<_> = stops
+        .iter()
+        .map(|&(offset, colour)| GradientStop::new(offset.clamp(0.0, 1.0), colour))
+        .collect();
+    stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+    stops
+}
+
+/// A gradient as the kernels see it, laid out like `Gradient` in shader.wgsl.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GradientRecord {
+    pub kind: u32,
+    /// Where its stops start in `FrameRecords::stops`.
+    pub first_stop: u32,
+    pub stop_count: u32,
+    pub _padding: u32,
+    /// Linear: start x, start y, end x, end y. Radial: centre x, centre y, radius.
+    /// Conic: centre x, centre y, starting angle.
+    pub params: [f32; 4],
+}
+impl GradientRecord {
+    pub const LINEAR: u32 = 0;
+    pub const RADIAL: u32 = 1;
+    pub const CONIC: u32 = 2;
+}
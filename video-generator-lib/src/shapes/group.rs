@@ -0,0 +1,200 @@
+use super::{intersect_box, union_box, BlendMode, FrameRecords, Shape, ShapeRecord};
+use crate::renderer::Antialiasing;
+
+/// Which part of a mask's pixels lets a group through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MaskMode {
+    /// Where the mask is opaque, so any shape clips the group to its outline.
+    #[default]
+    Alpha,
+    /// Where the mask is bright, black hiding the group and white showing it.
+    Luminance,
+}
+
+#[derive(Debug, Clone)]
+pub struct Mask {
+    pub shape: Box<Shape>,
+    pub mode: MaskMode,
+}
+impl Mask {
+    pub fn new(shape: Shape, mode: MaskMode) -> Self {
+        Self {
+            shape: Box::new(shape),
+            mode,
+        }
+    }
+}
+
+/// Shapes drawn together into a layer of their own, which is then composited onto the canvas
+/// as a whole. Blend modes of the children only mix with each other, not with what's behind the group.
+#[derive(Debug, Clone)]
+pub struct GroupData {
+    pub children: Vec<Shape>,
+    /// Fades the whole layer, so overlapping children don't show through each other.
+    pub opacity: f32,
+    /// Hides the parts of the layer the mask doesn't cover. The mask itself isn't drawn.
+    pub mask: Option<Mask>,
+    pub blend_mode: BlendMode,
+}
+impl GroupData {
+    pub fn new(children: Vec<Shape>) -> Self {
+        Self {
+            children,
+            opacity: 1.0,
+            mask: None,
+            blend_mode: BlendMode::Normal,
+        }
+    }
+
+    pub fn new_shape(children: Vec<Shape>) -> Shape {
+        Shape::Group(Self::new(children))
+    }
+
+    pub fn with_opacity(self, opacity: f32) -> Self {
+        Self { opacity, ..self }
+    }
+
+    pub fn with_mask(self, mask: Option<Mask>) -> Self {
+        Self { mask, ..self }
+    }
+
+    pub fn with_blend_mode(self, blend_mode: BlendMode) -> Self {
+        Self { blend_mode, ..self }
+    }
+
+    /// Appends a `GROUP_START` record, the children's, then a `GROUP_MASK` record and the mask's
+    /// if there is one, and finally a `GROUP_END` record compositing the layer over its clipped box.
+    /// Groups which can't be seen add nothing, not even the points or images of their children.
+    ///
+    /// The clipped box is worked out from the children's records, rather than with
+    /// [`GroupData::clipped_box`], so shapes such as text are only laid out once however deeply
+    /// they're nested.
+    ///
+    /// The end record's parameters are the opacity and 0 without a mask, or one more than its mode.
+    pub fn to_records(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+        records: &mut FrameRecords,
+    ) {
+        if self.opacity <= 0.0 {
+            return;
+        }
+        let lengths = records.lengths();
+        let start = records.shapes.len();
+        records.shapes.push(ShapeRecord {
+            kind: ShapeRecord::GROUP_START,
+            ..Default::default()
+        });
+        for child in &self.children {
+            child.to_records(antialiasing, width, height, records);
+        }
+        let Some((mut clip, origin)) = layer_extent(&records.shapes[start + 1..]) else {
+            records.truncate(lengths);
+            return;
+        };
+        if let Some(mask) = &self.mask {
+            let mask_start = records.shapes.len();
+            records.shapes.push(ShapeRecord {
+                kind: ShapeRecord::GROUP_MASK,
+                ..Default::default()
+            });
+            mask.shape.to_records(antialiasing, width, height, records);
+            let mask_clip = layer_extent(&records.shapes[mask_start + 1..])
+                .and_then(|(mask_clip, _)| intersect_box(clip, mask_clip));
+            match mask_clip {
+                Some(mask_clip) => clip = mask_clip,
+                None => {
+                    records.truncate(lengths);
+                    return;
+                }
+            }
+        }
+
+        let mask = self.mask.as_ref().map_or(0, |x| x.mode as u32 + 1);
+        records.shapes.push(ShapeRecord {
+            kind: ShapeRecord::GROUP_END,
+            blend_mode: self.blend_mode as u32,
+            clip: clip.into(),
+            origin,
+            params: [
+                [self.opacity.min(1.0), mask as f32, 0.0, 0.0],
+                [0.0; 4],
+                [0.0; 4],
+            ],
+            ..Default::default()
+        });
+    }
+
+    /// The pixels the children may touch, which may lie partly or entirely off the canvas.
+    pub fn bounding_box(&self) -> (i32, i32, u32, u32) {
+        let boxes: Vec<_> = self.children.iter().map(Shape::bounding_box).collect();
+        if boxes.is_empty() {
+            return (0, 0, 0, 0);
+        }
+        let left = boxes.iter().map(|x| x.0).min().unwrap();
+        let top = boxes.iter().map(|x| x.1).min().unwrap();
+        let right = boxes.iter().map(|x| x.0 as i64 + x.2 as i64).max().unwrap();
+        let bottom = boxes.iter().map(|x| x.1 as i64 + x.3 as i64).max().unwrap();
+        (
+            left,
+            top,
+            (right - left as i64) as u32,
+            (bottom - top as i64) as u32,
+        )
+    }
+
+    /// The on-canvas pixels both the children and the mask may touch.
+    pub fn clipped_box(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+    ) -> Option<(u32, u32, u32, u32)> {
+        let children = self
+            .children
+            .iter()
+            .map(|x| x.clipped_box(antialiasing, width, height))
+            .fold(None, union_box)?;
+        match &self.mask {
+            Some(mask) => intersect_box(
+                children,
+                mask.shape.clipped_box(antialiasing, width, height)?,
+            ),
+            None => Some(children),
+        }
+    }
+}
+
+/// A clipped box and the top left corner of the unclipped one.
+type Extent = ((u32, u32, u32, u32), [i32; 2]);
+
+/// The union of the clipped boxes of the records drawn straight into a layer, and the top left
+/// corner of their unclipped boxes, or `None` if there are none. Records inside nested groups
+/// are left out, since the groups' end records cover them.
+fn layer_extent(records: &[ShapeRecord]) -> Option<Extent> {
+    let mut depth = 0;
+    let mut clip = None;
+    let mut origin = [i32::MAX; 2];
+    for record in records {
+        match record.kind {
+            ShapeRecord::GROUP_START => depth += 1,
+            ShapeRecord::GROUP_MASK => {}
+            kind => {
+                if kind == ShapeRecord::GROUP_END {
+                    depth -= 1;
+                }
+                if depth == 0 {
+                    let [x, y, width, height] = record.clip;
+                    clip = union_box(clip, Some((x, y, width, height)));
+                    origin = [
+                        origin[0].min(record.origin[0]),
+                        origin[1].min(record.origin[1]),
+                    ];
+                }
+            }
+        }
+    }
+    Some((clip?, origin))
+}
@@ -0,0 +1,236 @@
+use std::{
+    fmt,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use image::{ImageResult, RgbaImage};
+
+use super::{box_around, clip_box, BlendMode, Shape, ShapeRecord};
+use crate::color::Color;
+use crate::renderer::Antialiasing;
+
+/// A picture loaded once and shared by every shape drawing it, so the GPU renderer uploads it once.
+/// Pictures wider or taller than the GPU's textures can be are scaled down to fit when uploaded,
+/// so they're drawn less sharply there than on the CPU.
+#[derive(Clone)]
+pub struct ImageSource {
+    inner: Arc<ImageSourceInner>,
+}
+
+struct ImageSourceInner {
+    /// Tells images apart without comparing their pixels.
+    id: u64,
+    pixels: RgbaImage,
+}
+
+impl ImageSource {
+    /// Loads a PNG, JPEG or any other format `image` can decode.
+    pub fn open(path: impl AsRef<Path>) -> ImageResult<Self> {
+        Ok(Self::from_rgba(image::open(path)?.into_rgba8()))
+    }
+
+    pub fn from_rgba(pixels: RgbaImage) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        Self {
+            inner: Arc::new(ImageSourceInner {
+                id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+                pixels,
+            }),
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.inner.id
+    }
+
+    pub fn width(&self) -> u32 {
+        self.inner.pixels.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.inner.pixels.height()
+    }
+
+    pub fn pixels(&self) -> &RgbaImage {
+        &self.inner.pixels
+    }
+
+    /// The packed colour of a pixel, which must be inside the image.
+    pub fn pixel(&self, x: u32, y: u32) -> u32 {
+        u32::from_le_bytes(self.inner.pixels.get_pixel(x, y).0)
+    }
+
+    /// Whether this is the last handle to the image, so caches holding it may let it go.
+    pub fn is_unique(&self) -> bool {
+        Arc::strong_count(&self.inner) == 1
+    }
+}
+impl fmt::Debug for ImageSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImageSource")
+            .field("id", &self.id())
+            .field("width", &self.width())
+            .field("height", &self.height())
+            .finish()
+    }
+}
+
+/// How an image is sampled when drawn at a different size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageFilter {
+    /// Blocky, keeping pixel art sharp.
+    Nearest,
+    /// Smooth, mixing the four nearest pixels.
+    #[default]
+    Bilinear,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImageData {
+    pub source: ImageSource,
+    /// The top left corner.
+    pub position: (f32, f32),
+    pub size: (f32, f32),
+    /// The part of the source drawn: x, y, width and height in its pixels.
+    pub region: (u32, u32, u32, u32),
+    pub filter: ImageFilter,
+    /// Multiplies every pixel, leaving them unchanged when white.
+    pub tint: Color,
+    pub opacity: f32,
+    pub blend_mode: BlendMode,
+}
+impl ImageData {
+    /// The image at its own size.
+    pub fn new(source: ImageSource, position: (f32, f32)) -> Self {
+        Self {
+            size: (source.width() as f32, source.height() as f32),
+            region: (0, 0, source.width(), source.height()),
+            source,
+            position,
+            filter: ImageFilter::default(),
+            tint: Color::WHITE,
+            opacity: 1.0,
+            blend_mode: BlendMode::Normal,
+        }
+    }
+
+    pub fn new_shape(source: ImageSource, position: (f32, f32)) -> Shape {
+        Shape::Image(Self::new(source, position))
+    }
+
+    pub fn with_size(self, size: (f32, f32)) -> Self {
+        Self { size, ..self }
+    }
+
+    /// Draws only part of the source, such as one frame of a sprite sheet, stretched over `size`.
+    pub fn with_region(self, region: (u32, u32, u32, u32)) -> Self {
+        Self { region, ..self }
+    }
+
+    pub fn with_filter(self, filter: ImageFilter) -> Self {
+        Self { filter, ..self }
+    }
+
+    pub fn with_tint(self, tint: impl Into<Color>) -> Self {
+        Self {
+            tint: tint.into(),
+            ..self
+        }
+    }
+
+    pub fn with_opacity(self, opacity: f32) -> Self {
+        Self { opacity, ..self }
+    }
+
+    pub fn with_blend_mode(self, blend_mode: BlendMode) -> Self {
+        Self { blend_mode, ..self }
+    }
+
+    /// Adds the source to `images` unless the frame already draws it, and refers to it by index.
+    pub fn to_record(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+        images: &mut Vec<ImageSource>,
+    ) -> Option<ShapeRecord> {
+        if self.size.0 <= 0.0 || self.size.1 <= 0.0 || self.opacity <= 0.0 {
+            return None;
+        }
+        let (region_x, region_y, region_width, region_height) = self.region;
+        let region_width = region_width.min(self.source.width().saturating_sub(region_x));
+        let region_height = region_height.min(self.source.height().saturating_sub(region_y));
+        if region_width == 0 || region_height == 0 {
+            return None;
+        }
+        let clip = self.clipped_box(antialiasing, width, height)?.into();
+        let index = match images.iter().position(|x| x.id() == self.source.id()) {
+            Some(index) => index,
+            None => {
+                images.push(self.source.clone());
+                images.len() - 1
+            }
+        };
+        let (origin_x, origin_y, _, _) = self.bounding_box();
+        let (half_width, half_height) = (self.size.0 / 2.0, self.size.1 / 2.0);
+        Some(ShapeRecord {
+            kind: ShapeRecord::IMAGE,
+            colour: self.tint.to_packed(),
+            blend_mode: self.blend_mode as u32,
+            clip,
+            origin: [origin_x, origin_y],
+            params: [
+                [
+                    self.position.0 + half_width,
+                    self.position.1 + half_height,
+                    half_width,
+                    half_height,
+                ],
+                [
+                    self.opacity.min(1.0),
+                    self.filter as u32 as f32,
+                    index as f32,
+                    0.0,
+                ],
+                [
+                    region_x as f32,
+                    region_y as f32,
+                    region_width as f32,
+                    region_height as f32,
+                ],
+            ],
+            ..Default::default()
+        })
+    }
+
+    /// The pixels whose centres could be inside the image, which may lie partly or entirely off the canvas.
+    pub fn bounding_box(&self) -> (i32, i32, u32, u32) {
+        self.box_with_margin(0.0)
+    }
+
+    /// The pixels which may be touched with `antialiasing`.
+    pub fn covered_box(&self, antialiasing: Antialiasing) -> (i32, i32, u32, u32) {
+        if antialiasing == Antialiasing::None {
+            return self.bounding_box();
+        }
+        self.box_with_margin(1.0)
+    }
+
+    pub fn clipped_box(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+    ) -> Option<(u32, u32, u32, u32)> {
+        clip_box(self.covered_box(antialiasing), width, height)
+    }
+
+    fn box_with_margin(&self, margin: f32) -> (i32, i32, u32, u32) {
+        let (x, y) = self.position;
+        box_around(&[(x, y), (x + self.size.0, y + self.size.1)], margin)
+    }
+}
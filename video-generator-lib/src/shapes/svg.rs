@@ -0,0 +1,348 @@
+use std::{f32::consts::PI, fmt};
+
+use super::PathSegment;
+
+/// Why an SVG path `d` attribute couldn't be parsed, with the byte offset it went wrong at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParsePathError {
+    /// Something other than a command, number or separator.
+    UnexpectedCharacter { position: usize, character: char },
+    /// A command ran out of numbers, or a number was malformed.
+    ExpectedNumber { position: usize },
+    /// Arc flags must be `0` or `1`.
+    ExpectedFlag { position: usize },
+    /// Path data must start with a move.
+    ExpectedMoveTo { position: usize },
+}
+impl fmt::Display for ParsePathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParsePathError::UnexpectedCharacter {
+                position,
+                character,
+            } => write!(f, "unexpected {character:?} at {position}"),
+            ParsePathError::ExpectedNumber { position } => {
+                write!(f, "expected a number at {position}")
+            }
+            ParsePathError::ExpectedFlag { position } => {
+                write!(f, "expected an arc flag of 0 or 1 at {position}")
+            }
+            ParsePathError::ExpectedMoveTo { position } => {
+                write!(f, "expected the path to start with a move at {position}")
+            }
+        }
+    }
+}
+impl std::error::Error for ParsePathError {}
+
+/// The control point of the previous curve, which `S` and `T` reflect.
+enum PreviousControl {
+    None,
+    Cubic((f32, f32)),
+    Quadratic((f32, f32)),
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    fn skip_separators(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r' | b'\x0C' | b',') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    fn skip_digits(&mut self) -> usize {
+        let start = self.position;
+        while self.peek().is_some_and(|x| x.is_ascii_digit()) {
+            self.position += 1;
+        }
+        self.position - start
+    }
+
+    /// Whether another number follows, so the current command repeats.
+    fn at_number(&mut self) -> bool {
+        self.skip_separators();
+        matches!(self.peek(), Some(b'0'..=b'9' | b'.' | b'-' | b'+'))
+    }
+
+    fn number(&mut self) -> Result<f32, ParsePathError> {
+        self.skip_separators();
+        let start = self.position;
+        if let Some(b'-' | b'+') = self.peek() {
+            self.position += 1;
+        }
+        let mut digits = self.skip_digits();
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            digits += self.skip_digits();
+        }
+        if digits == 0 {
+            return Err(ParsePathError::ExpectedNumber { position: start });
+        }
+        // An exponent only counts if digits follow, as in `1e5` but not `1em`.
+        if let Some(b'e' | b'E') = self.peek() {
+            let mantissa_end = self.position;
+            self.position += 1;
+            if let Some(b'-' | b'+') = self.peek() {
+                self.position += 1;
+            }
+            if self.skip_digits() == 0 {
+                self.position = mantissa_end;
+            }
+        }
+        std::str::from_utf8(&self.bytes[start..self.position])
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .ok_or(ParsePathError::ExpectedNumber { position: start })
+    }
+
+    fn point(&mut self) -> Result<(f32, f32), ParsePathError> {
+        Ok((self.number()?, self.number()?))
+    }
+
+    /// Flags may be written without separators, as in `a1 1 0 00 1 1`.
+    fn flag(&mut self) -> Result<bool, ParsePathError> {
+        self.skip_separators();
+        let flag = match self.peek() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => {
+                return Err(ParsePathError::ExpectedFlag {
+                    position: self.position,
+                })
+            }
+        };
+        self.position += 1;
+        Ok(flag)
+    }
+}
+
+/// Parses the `d` attribute of an SVG `<path>`.
+/// Arcs are approximated with cubic curves.
+pub fn parse_path_data(data: &str) -> Result<Vec<PathSegment>, ParsePathError> {
+    let mut parser = Parser {
+        bytes: data.as_bytes(),
+        position: 0,
+    };
+    let mut segments = vec![];
+    let mut current = (0.0, 0.0);
+    let mut subpath_start = (0.0, 0.0);
+    let mut previous_control = PreviousControl::None;
+    let mut command: Option<u8> = None;
+
+    loop {
+        parser.skip_separators();
+        let Some(next) = parser.peek() else {
+            break;
+        };
+        let position = parser.position;
+        if next.is_ascii_alphabetic() {
+            parser.position += 1;
+            command = Some(next);
+        } else if !parser.at_number() || matches!(command, None | Some(b'Z' | b'z')) {
+            return Err(ParsePathError::UnexpectedCharacter {
+                position,
+                character: data[position..].chars().next().unwrap_or_default(),
+            });
+        } else if let Some(b'M') = command {
+            // Extra coordinates after a move are lines.
+            command = Some(b'L');
+        } else if let Some(b'm') = command {
+            command = Some(b'l');
+        }
+        let Some(command) = command else {
+            unreachable!()
+        };
+        if segments.is_empty() && !matches!(command, b'M' | b'm') {
+            return Err(ParsePathError::ExpectedMoveTo { position });
+        }
+
+        let relative = command.is_ascii_lowercase();
+        let base = current;
+        let offset = |(x, y): (f32, f32)| {
+            if relative {
+                (x + base.0, y + base.1)
+            } else {
+                (x, y)
+            }
+        };
+        let reflect = |control: (f32, f32)| (2.0 * base.0 - control.0, 2.0 * base.1 - control.1);
+
+        let mut next_control = PreviousControl::None;
+        match command.to_ascii_uppercase() {
+            b'M' => {
+                current = offset(parser.point()?);
+                subpath_start = current;
+                segments.push(PathSegment::MoveTo(current));
+            }
+            b'L' => {
+                current = offset(parser.point()?);
+                segments.push(PathSegment::LineTo(current));
+            }
+            b'H' => {
+                let x = parser.number()?;
+                current.0 = if relative { current.0 + x } else { x };
+                segments.push(PathSegment::LineTo(current));
+            }
+            b'V' => {
+                let y = parser.number()?;
+                current.1 = if relative { current.1 + y } else { y };
+                segments.push(PathSegment::LineTo(current));
+            }
+            b'C' => {
+                let first = offset(parser.point()?);
+                let second = offset(parser.point()?);
+                current = offset(parser.point()?);
+                segments.push(PathSegment::CubicTo(first, second, current));
+                next_control = PreviousControl::Cubic(second);
+            }
+            b'S' => {
+                let first = match previous_control {
+                    PreviousControl::Cubic(control) => reflect(control),
+                    _ => current,
+                };
+                let second = offset(parser.point()?);
+                current = offset(parser.point()?);
+                segments.push(PathSegment::CubicTo(first, second, current));
+                next_control = PreviousControl::Cubic(second);
+            }
+            b'Q' => {
+                let control = offset(parser.point()?);
+                current = offset(parser.point()?);
+                segments.push(PathSegment::QuadTo(control, current));
+                next_control = PreviousControl::Quadratic(control);
+            }
+            b'T' => {
+                let control = match previous_control {
+                    PreviousControl::Quadratic(control) => reflect(control),
+                    _ => current,
+                };
+                current = offset(parser.point()?);
+                segments.push(PathSegment::QuadTo(control, current));
+                next_control = PreviousControl::Quadratic(control);
+            }
+            b'A' => {
+                let radii = parser.point()?;
+                let rotation = parser.number()?;
+                let large_arc = parser.flag()?;
+                let sweep = parser.flag()?;
+                let end = offset(parser.point()?);
+                arc_to_cubics(
+                    current,
+                    radii,
+                    rotation,
+                    large_arc,
+                    sweep,
+                    end,
+                    &mut segments,
+                );
+                current = end;
+            }
+            b'Z' => {
+                segments.push(PathSegment::Close);
+                current = subpath_start;
+            }
+            _ => {
+                return Err(ParsePathError::UnexpectedCharacter {
+                    position,
+                    character: command as char,
+                })
+            }
+        }
+        previous_control = next_control;
+    }
+    Ok(segments)
+}
+
+/// Appends an elliptical arc from `start` to `end` as cubic curves of at most a quarter turn,
+/// following the SVG implementation notes.
+fn arc_to_cubics(
+    start: (f32, f32),
+    radii: (f32, f32),
+    rotation: f32,
+    large_arc: bool,
+    sweep: bool,
+    end: (f32, f32),
+    segments: &mut Vec<PathSegment>,
+) {
+    if start == end {
+        return;
+    }
+    let (mut radius_x, mut radius_y) = (radii.0.abs(), radii.1.abs());
+    if radius_x == 0.0 || radius_y == 0.0 {
+        segments.push(PathSegment::LineTo(end));
+        return;
+    }
+    let (sin, cos) = rotation.to_radians().sin_cos();
+
+    // The start point in the ellipse's own axes, relative to the chord's midpoint.
+    let (dx, dy) = ((start.0 - end.0) / 2.0, (start.1 - end.1) / 2.0);
+    let (x, y) = (cos * dx + sin * dy, -sin * dx + cos * dy);
+
+    // Radii too small to reach are scaled up until they just do.
+    let scale = (x * x) / (radius_x * radius_x) + (y * y) / (radius_y * radius_y);
+    if scale > 1.0 {
+        radius_x *= scale.sqrt();
+        radius_y *= scale.sqrt();
+    }
+    let (rx2, ry2) = (radius_x * radius_x, radius_y * radius_y);
+    let numerator = rx2 * ry2 - rx2 * y * y - ry2 * x * x;
+    let denominator = rx2 * y * y + ry2 * x * x;
+    let sign = if large_arc == sweep { -1.0 } else { 1.0 };
+    let coefficient = sign * (numerator / denominator).max(0.0).sqrt();
+    let (centre_x, centre_y) = (
+        coefficient * radius_x * y / radius_y,
+        -coefficient * radius_y * x / radius_x,
+    );
+    let centre = (
+        cos * centre_x - sin * centre_y + (start.0 + end.0) / 2.0,
+        sin * centre_x + cos * centre_y + (start.1 + end.1) / 2.0,
+    );
+
+    let angle =
+        |(ux, uy): (f32, f32), (vx, vy): (f32, f32)| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+    let from = ((x - centre_x) / radius_x, (y - centre_y) / radius_y);
+    let to = ((-x - centre_x) / radius_x, (-y - centre_y) / radius_y);
+    let start_angle = angle((1.0, 0.0), from);
+    let mut sweep_angle = angle(from, to);
+    if !sweep && sweep_angle > 0.0 {
+        sweep_angle -= 2.0 * PI;
+    } else if sweep && sweep_angle < 0.0 {
+        sweep_angle += 2.0 * PI;
+    }
+
+    // Maps a point on the unit circle onto the ellipse.
+    let map = |(x, y): (f32, f32)| {
+        (
+            centre.0 + radius_x * x * cos - radius_y * y * sin,
+            centre.1 + radius_x * x * sin + radius_y * y * cos,
+        )
+    };
+    let count = (sweep_angle.abs() / (PI / 2.0)).ceil().max(1.0) as usize;
+    let step = sweep_angle / count as f32;
+    let handle = 4.0 / 3.0 * (step / 4.0).tan();
+    for i in 0..count {
+        let (a, b) = (
+            start_angle + step * i as f32,
+            start_angle + step * (i + 1) as f32,
+        );
+        let (sin_a, cos_a) = a.sin_cos();
+        let (sin_b, cos_b) = b.sin_cos();
+        let segment_end = if i + 1 == count {
+            end
+        } else {
+            map((cos_b, sin_b))
+        };
+        segments.push(PathSegment::CubicTo(
+            map((cos_a - handle * sin_a, sin_a + handle * cos_a)),
+            map((cos_b + handle * sin_b, sin_b - handle * cos_b)),
+            segment_end,
+        ));
+    }
+}
@@ -0,0 +1,380 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use ab_glyph::{Font as _, FontVec, GlyphId, InvalidFont, PxScale, ScaleFont as _};
+use image::{Rgba, RgbaImage};
+
+use super::{box_around, union_box, BlendMode, ImageData, ImageFilter, ImageSource, Shape};
+use crate::{color::Color, renderer::Antialiasing};
+
+/// Why a font couldn't be loaded.
+#[derive(Debug)]
+pub enum LoadFontError {
+    Io(std::io::Error),
+    /// The data isn't a TrueType or OpenType font.
+    Invalid(InvalidFont),
+}
+impl fmt::Display for LoadFontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadFontError::Io(error) => write!(f, "couldn't read the font: {error}"),
+            LoadFontError::Invalid(error) => write!(f, "couldn't parse the font: {error}"),
+        }
+    }
+}
+impl std::error::Error for LoadFontError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadFontError::Io(error) => Some(error),
+            LoadFontError::Invalid(error) => Some(error),
+        }
+    }
+}
+
+/// A TrueType or OpenType font, with the glyphs drawn so far rasterized into an atlas
+/// which every text in the font shares.
+#[derive(Clone)]
+pub struct Font {
+    inner: Arc<FontInner>,
+}
+
+struct FontInner {
+    font: FontVec,
+    atlas: Mutex<GlyphAtlas>,
+}
+
+impl Font {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, LoadFontError> {
+        Self::from_bytes(std::fs::read(path).map_err(LoadFontError::Io)?)
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, LoadFontError> {
+        Ok(Self {
+            inner: Arc::new(FontInner {
+                font: FontVec::try_from_vec(data).map_err(LoadFontError::Invalid)?,
+                atlas: Mutex::new(GlyphAtlas::new()),
+            }),
+        })
+    }
+
+    /// The scale at which the font's em square is `size` pixels, as font sizes usually are.
+    /// `PxScale` is the height from descent to ascent instead, which is usually a little more.
+    fn scale(&self, size: f32) -> PxScale {
+        let font = &self.inner.font;
+        match font.units_per_em() {
+            Some(units_per_em) => PxScale::from(size * font.height_unscaled() / units_per_em),
+            None => PxScale::from(size),
+        }
+    }
+
+    /// The size glyphs of `size` are rasterized at, which is never smaller and at most
+    /// one sixteenth of an octave larger, so animated sizes share a bounded set of glyphs.
+    /// Whole sizes from 16 to 32 pixels, and halves from 8 to 16, are their own.
+    fn atlas_size(size: f32) -> f32 {
+        let step = 2f32.powf(size.log2().floor() - 4.0);
+        (size / step).ceil() * step
+    }
+}
+impl fmt::Debug for Font {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Font")
+            .field("glyphs", &self.inner.font.glyph_count())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Where a glyph is in the atlas, and where its top left corner is from its origin on the baseline.
+#[derive(Debug, Clone, Copy)]
+struct AtlasGlyph {
+    region: (u32, u32, u32, u32),
+    offset: (f32, f32),
+}
+
+/// Rasterized glyphs packed into rows of an image which grows up to `MAX_SIZE` pixels across,
+/// then starts over empty.
+struct GlyphAtlas {
+    pixels: RgbaImage,
+    /// By glyph and the bits of its size. Glyphs with nothing to draw, such as spaces, are `None`.
+    glyphs: HashMap<(GlyphId, u32), Option<AtlasGlyph>>,
+    /// Where the next glyph goes, and the height of the row it's in.
+    cursor: (u32, u32),
+    row_height: u32,
+    /// The pixels as drawn, or `None` if glyphs have been added since.
+    source: Option<ImageSource>,
+    /// How many times the atlas has started over, so glyphs found before can be told apart.
+    generation: u32,
+}
+
+impl GlyphAtlas {
+    /// Empty pixels between glyphs, so filtering never reaches into a neighbour.
+    const PADDING: u32 = 1;
+    /// Within the texture size of all but the smallest GPUs, which scale the atlas down.
+    const MAX_SIZE: u32 = 4096;
+
+    fn new() -> Self {
+        Self {
+            pixels: RgbaImage::new(256, 256),
+            glyphs: HashMap::new(),
+            cursor: (0, 0),
+            row_height: 0,
+            source: None,
+            generation: 0,
+        }
+    }
+
+    /// Empties the atlas. Sources taken before keep the pixels they were taken with.
+    fn clear(&mut self) {
+        *self = Self {
+            generation: self.generation + 1,
+            ..Self::new()
+        };
+    }
+
+    /// Rasterizes the glyph if it isn't in the atlas yet, starting the atlas over if it's full.
+    /// Glyphs too big for even an empty atlas aren't drawn.
+    fn glyph(&mut self, font: &FontVec, id: GlyphId, scale: PxScale) -> Option<AtlasGlyph> {
+        let key = (id, scale.y.to_bits());
+        if let Some(glyph) = self.glyphs.get(&key) {
+            return *glyph;
+        }
+        let glyph = font
+            .outline_glyph(id.with_scale(scale))
+            .and_then(|outlined| {
+                let bounds = outlined.px_bounds();
+                let (width, height) = (bounds.width() as u32, bounds.height() as u32);
+                let (x, y) = match self.allocate(width, height) {
+                    Some(position) => position,
+                    None => {
+                        self.clear();
+                        self.allocate(width, height)?
+                    }
+                };
+                outlined.draw(|glyph_x, glyph_y, coverage| {
+                    let alpha = (coverage.clamp(0.0, 1.0) * 255.0).round() as u8;
+                    self.pixels
+                        .put_pixel(x + glyph_x, y + glyph_y, Rgba([255, 255, 255, alpha]));
+                });
+                Some(AtlasGlyph {
+                    region: (x, y, width, height),
+                    offset: (bounds.min.x, bounds.min.y),
+                })
+            })
+            .filter(|x| x.region.2 > 0 && x.region.3 > 0);
+        self.glyphs.insert(key, glyph);
+        self.source = None;
+        glyph
+    }
+
+    /// Finds room for a `width` by `height` glyph, growing the atlas if it's full,
+    /// or `None` if it can't grow any more.
+    fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        if self.cursor.0 + width + Self::PADDING > self.pixels.width() {
+            self.cursor = (0, self.cursor.1 + self.row_height);
+            self.row_height = 0;
+        }
+        let needed = (
+            (width + Self::PADDING).max(self.pixels.width()),
+            self.cursor.1 + height + Self::PADDING,
+        );
+        if needed.0 > Self::MAX_SIZE || needed.1 > Self::MAX_SIZE {
+            return None;
+        }
+        if needed.0 > self.pixels.width() || needed.1 > self.pixels.height() {
+            let mut pixels = RgbaImage::new(
+                needed.0.next_power_of_two(),
+                needed.1.max(self.pixels.height()).next_power_of_two(),
+            );
+            image::imageops::replace(&mut pixels, &self.pixels, 0, 0);
+            self.pixels = pixels;
+        }
+        let position = self.cursor;
+        self.cursor.0 += width + Self::PADDING;
+        self.row_height = self.row_height.max(height + Self::PADDING);
+        Some(position)
+    }
+
+    /// The pixels as an image, which is only copied again once glyphs have been added.
+    fn source(&mut self) -> ImageSource {
+        self.source
+            .get_or_insert_with(|| ImageSource::from_rgba(self.pixels.clone()))
+            .clone()
+    }
+}
+
+/// Which side of each line the position is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Centre,
+    Right,
+}
+
+#[derive(Debug, Clone)]
+pub struct TextData {
+    /// Lines are separated by `\n`.
+    pub content: String,
+    pub font: Font,
+    /// The height of the font's em square, in pixels.
+    pub size: f32,
+    /// The top of the first line, on the side given by `align`.
+    pub position: (f32, f32),
+    pub colour: Color,
+    pub align: TextAlign,
+    /// The distance between baselines, as a multiple of `size`.
+    pub line_height: f32,
+    pub blend_mode: BlendMode,
+}
+impl TextData {
+    pub fn new(
+        content: impl Into<String>,
+        font: Font,
+        size: f32,
+        position: (f32, f32),
+        colour: impl Into<Color>,
+    ) -> Self {
+        Self {
+            content: content.into(),
+            font,
+            size,
+            position,
+            colour: colour.into(),
+            align: TextAlign::Left,
+            line_height: 1.2,
+            blend_mode: BlendMode::Normal,
+        }
+    }
+
+    pub fn new_shape(
+        content: impl Into<String>,
+        font: Font,
+        size: f32,
+        position: (f32, f32),
+        colour: impl Into<Color>,
+    ) -> Shape {
+        Shape::Text(Self::new(content, font, size, position, colour))
+    }
+
+    pub fn with_align(self, align: TextAlign) -> Self {
+        Self { align, ..self }
+    }
+
+    pub fn with_line_height(self, line_height: f32) -> Self {
+        Self {
+            line_height,
+            ..self
+        }
+    }
+
+    pub fn with_blend_mode(self, blend_mode: BlendMode) -> Self {
+        Self { blend_mode, ..self }
+    }
+
+    /// Lays the text out as one image of each visible glyph, cut from the font's atlas.
+    /// Glyphs are placed on whole pixels, so they're drawn exactly as rasterized
+    /// unless they were rasterized a little larger and are scaled down.
+    pub fn glyphs(&self) -> Vec<ImageData> {
+        if self.size <= 0.0 {
+            return vec![];
+        }
+        let mut atlas = self.font.inner.atlas.lock().unwrap();
+        // If the atlas starts over partway through, the glyphs placed before are gone from it.
+        let generation = atlas.generation;
+        let placed = self.place_glyphs(&mut atlas);
+        let placed = if atlas.generation == generation {
+            placed
+        } else {
+            self.place_glyphs(&mut atlas)
+        };
+
+        let source = atlas.source();
+        let ratio = self.size / Font::atlas_size(self.size);
+        let filter = if ratio == 1.0 {
+            ImageFilter::Nearest
+        } else {
+            ImageFilter::Bilinear
+        };
+        placed
+            .into_iter()
+            .map(|(glyph, (x, y))| {
+                let (_, _, width, height) = glyph.region;
+                let position = (x + glyph.offset.0 * ratio, y + glyph.offset.1 * ratio);
+                ImageData::new(source.clone(), position)
+                    .with_region(glyph.region)
+                    .with_size((width as f32 * ratio, height as f32 * ratio))
+                    .with_filter(filter)
+                    .with_tint(self.colour)
+                    .with_blend_mode(self.blend_mode)
+            })
+            .collect()
+    }
+
+    /// Each visible glyph and its origin, laid out at the text's size.
+    fn place_glyphs(&self, atlas: &mut GlyphAtlas) -> Vec<(AtlasGlyph, (f32, f32))> {
+        let font = &self.font.inner.font;
+        let scaled = font.as_scaled(self.font.scale(self.size));
+        let atlas_scale = self.font.scale(Font::atlas_size(self.size));
+
+        let mut placed = vec![];
+        for (i, line) in self.content.lines().enumerate() {
+            let mut caret = 0.0;
+            let mut previous = None;
+            let mut line_glyphs = vec![];
+            for character in line.chars() {
+                let id = scaled.glyph_id(character);
+                if let Some(previous) = previous {
+                    caret += scaled.kern(previous, id);
+                }
+                line_glyphs.push((id, caret));
+                caret += scaled.h_advance(id);
+                previous = Some(id);
+            }
+            let left = match self.align {
+                TextAlign::Left => self.position.0,
+                TextAlign::Centre => self.position.0 - caret / 2.0,
+                TextAlign::Right => self.position.0 - caret,
+            };
+            let baseline =
+                self.position.1 + scaled.ascent() + i as f32 * self.line_height * self.size;
+            for (id, x) in line_glyphs {
+                if let Some(glyph) = atlas.glyph(font, id, atlas_scale) {
+                    let origin = ((left + x).round(), baseline.round());
+                    placed.push((glyph, origin));
+                }
+            }
+        }
+        placed
+    }
+
+    /// The pixels the glyphs cover, which may lie partly or entirely off the canvas.
+    pub fn bounding_box(&self) -> (i32, i32, u32, u32) {
+        let corners: Vec<_> = self
+            .glyphs()
+            .iter()
+            .flat_map(|x| {
+                let (left, top) = x.position;
+                [(left, top), (left + x.size.0, top + x.size.1)]
+            })
+            .collect();
+        if corners.is_empty() {
+            return (self.position.0 as i32, self.position.1 as i32, 0, 0);
+        }
+        box_around(&corners, 0.0)
+    }
+
+    pub fn clipped_box(
+        &self,
+        antialiasing: Antialiasing,
+        width: u32,
+        height: u32,
+    ) -> Option<(u32, u32, u32, u32)> {
+        self.glyphs()
+            .iter()
+            .map(|x| x.clipped_box(antialiasing, width, height))
+            .fold(None, union_box)
+    }
+}
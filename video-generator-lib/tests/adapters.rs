@@ -0,0 +1,143 @@
+use std::sync::Mutex;
+
+use video_generator_lib::renderer::{
+    available_adapters, AdapterOptions, AdapterSummary, GpuError, GpuRenderer, ShaderSources,
+};
+
+/// Held by tests which change the environment or create wgpu instances, since drivers read it too.
+static ENVIRONMENT: Mutex<()> = Mutex::new(());
+
+const VARIABLES: [&str; 3] = ["WGPU_BACKEND", "WGPU_POWER_PREF", "WGPU_ADAPTER_NAME"];
+
+/// Runs `test` with the adapter variables set to `values`, then puts them back.
+fn with_variables(values: [Option<&str>; 3], test: impl FnOnce()) {
+    let saved = VARIABLES.map(|x| std::env::var(x).ok());
+    let set = |values: [Option<&str>; 3]| {
+        for (name, value) in VARIABLES.iter().zip(values) {
+            match value {
+                Some(value) => std::env::set_var(name, value),
+                None => std::env::remove_var(name),
+            }
+        }
+    };
+    set(values);
+    test();
+    set(saved.each_ref().map(|x| x.as_deref()));
+}
+
+fn llvmpipe() -> wgpu::AdapterInfo {
+    wgpu::AdapterInfo {
+        name: "llvmpipe (LLVM 15.0.7, 256 bits)".to_string(),
+        vendor: 0,
+        device: 0,
+        device_type: wgpu::DeviceType::Cpu,
+        driver: String::new(),
+        driver_info: String::new(),
+        backend: wgpu::Backend::Vulkan,
+    }
+}
+
+#[test]
+fn options_come_from_the_environment() {
+    let _lock = ENVIRONMENT.lock().unwrap_or_else(|x| x.into_inner());
+    with_variables([None; 3], || {
+        assert_eq!(AdapterOptions::from_env(), AdapterOptions::default());
+    });
+    with_variables([Some("vulkan,gl"), Some("high"), Some("llvmpipe")], || {
+        let options = AdapterOptions::from_env();
+        assert_eq!(
+            options.backends,
+            wgpu::Backends::VULKAN | wgpu::Backends::GL
+        );
+        assert_eq!(
+            options.power_preference,
+            wgpu::PowerPreference::HighPerformance
+        );
+        assert_eq!(options.adapter_name.as_deref(), Some("llvmpipe"));
+        // Forcing the fallback adapter isn't read from the environment.
+        assert!(!options.force_fallback_adapter);
+    });
+    with_variables([Some("gl"), Some("low"), None], || {
+        let options = AdapterOptions::from_env();
+        assert_eq!(options.backends, wgpu::Backends::GL);
+        assert_eq!(options.power_preference, wgpu::PowerPreference::LowPower);
+        assert_eq!(options.adapter_name, None);
+    });
+}
+
+#[test]
+fn builders_set_each_option() {
+    let options = AdapterOptions::default()
+        .with_backends(wgpu::Backends::VULKAN)
+        .with_power_preference(wgpu::PowerPreference::LowPower)
+        .with_fallback_adapter(true)
+        .with_adapter_name(Some("lavapipe".to_string()));
+    assert_eq!(
+        options,
+        AdapterOptions {
+            backends: wgpu::Backends::VULKAN,
+            power_preference: wgpu::PowerPreference::LowPower,
+            force_fallback_adapter: true,
+            adapter_name: Some("lavapipe".to_string()),
+        }
+    );
+}
+
+#[test]
+fn errors_list_the_adapters_there_are() {
+    assert_eq!(
+        AdapterSummary(&llvmpipe()).to_string(),
+        "llvmpipe (LLVM 15.0.7, 256 bits) (Cpu, Vulkan)"
+    );
+
+    let options = AdapterOptions::default().with_backends(wgpu::Backends::GL);
+    let none = GpuError::NoAdapter {
+        options: options.clone(),
+        available: vec![],
+    };
+    assert_eq!(
+        none.to_string(),
+        format!("no GPU adapter matches {options:?}, and none are available")
+    );
+    let some = GpuError::NoAdapter {
+        options: options.clone(),
+        available: vec![llvmpipe(), llvmpipe()],
+    };
+    assert_eq!(
+        some.to_string(),
+        format!(
+            "no GPU adapter matches {options:?}. The available adapters are:\
+             \n  llvmpipe (LLVM 15.0.7, 256 bits) (Cpu, Vulkan)\
+             \n  llvmpipe (LLVM 15.0.7, 256 bits) (Cpu, Vulkan)"
+        )
+    );
+    assert!(std::error::Error::source(&some).is_none());
+}
+
+#[test]
+fn unknown_adapter_names_are_an_error() {
+    let _lock = ENVIRONMENT.lock().unwrap_or_else(|x| x.into_inner());
+    let options = AdapterOptions::default().with_adapter_name(Some("no such adapter".to_string()));
+    let result = pollster::block_on(GpuRenderer::with_options(
+        16,
+        16,
+        &ShaderSources::built_in(),
+        &options,
+    ));
+    match result {
+        Err(GpuError::NoAdapter {
+            options: asked,
+            available,
+        }) => {
+            assert_eq!(asked, options);
+            let names =
+                |x: &[wgpu::AdapterInfo]| x.iter().map(|x| x.name.clone()).collect::<Vec<_>>();
+            assert_eq!(
+                names(&available),
+                names(&available_adapters(options.backends))
+            );
+        }
+        Err(error) => panic!("expected no adapter, not {error}"),
+        Ok(_) => panic!("found an adapter called \"no such adapter\""),
+    }
+}
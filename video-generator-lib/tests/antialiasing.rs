@@ -0,0 +1,119 @@
+mod common;
+
+use common::{gpu_renderer, pixel, COLOUR};
+use video_generator_lib::{
+    renderer::{Antialiasing, CpuRenderer, Renderer},
+    shapes::*,
+};
+
+const SIZE: u32 = 16;
+
+/// The alpha of a pixel drawn on a transparent canvas, which is its coverage.
+fn alpha(
+    renderer: &mut dyn Renderer,
+    antialiasing: Antialiasing,
+    shape: &Shape,
+    x: u32,
+    y: u32,
+) -> u32 {
+    renderer.set_antialiasing(antialiasing);
+    let frame = renderer.render_frame(std::slice::from_ref(shape));
+    let pixel = pixel(&frame, SIZE, x, y);
+    if pixel != 0 {
+        assert_eq!(pixel & 0xFFFFFF, COLOUR.to_packed() & 0xFFFFFF);
+    }
+    pixel >> 24
+}
+
+/// Checks the alpha of pixels of `shape` in each mode.
+fn check(renderer: &mut dyn Renderer, shape: &Shape, pixels: &[((u32, u32), [u32; 3])]) {
+    let modes = [
+        Antialiasing::None,
+        Antialiasing::Analytic,
+        Antialiasing::Supersample(4),
+    ];
+    for &((x, y), expected) in pixels {
+        for (antialiasing, expected) in modes.into_iter().zip(expected) {
+            assert_eq!(
+                alpha(renderer, antialiasing, shape, x, y),
+                expected,
+                "pixel ({x}, {y}) with {antialiasing:?}"
+            );
+        }
+    }
+}
+
+fn check_edges(renderer: &mut dyn Renderer) {
+    // A line covering 7.25 to 8.75 down the canvas, so three quarters of rows 7 and 8.
+    // Supersampling four rows a pixel puts three of them inside.
+    let line = PolylineData::new_line((-4.0, 8.0), (20.0, 8.0), 1.5, COLOUR);
+    check(
+        renderer,
+        &line,
+        &[
+            ((4, 6), [0, 0, 0]),
+            ((4, 7), [255, 191, 191]),
+            ((4, 8), [255, 191, 191]),
+            ((4, 9), [0, 0, 0]),
+        ],
+    );
+    // The coverage scales the colour's own alpha.
+    let faint = PolylineData::new_line((-4.0, 8.0), (20.0, 8.0), 1.5, COLOUR.with_alpha(0.5));
+    renderer.set_antialiasing(Antialiasing::Analytic);
+    let frame = renderer.render_frame(&[faint]);
+    assert_eq!(pixel(&frame, SIZE, 4, 7) >> 24, 96);
+
+    // A circle's edge cuts pixel (12, 7), whose centre is 0.28 outside it, so it is 0.22 covered,
+    // and the 4 samples of the first of its 4 columns fall inside.
+    let circle = CircleData::new_shape((8.0, 8.0), 4.25, COLOUR);
+    check(
+        renderer,
+        &circle,
+        &[
+            ((8, 8), [255, 255, 255]),
+            ((12, 7), [0, 57, 64]),
+            ((13, 7), [0, 0, 0]),
+        ],
+    );
+
+    // Polygons are rasterized with four samples a pixel whenever they're antialiased.
+    // An edge at 8.4 leaves two of them outside pixel 8, but its centre inside.
+    let polygon = PolygonData::new_shape(
+        vec![(8.4, -4.0), (20.0, -4.0), (20.0, 20.0), (8.4, 20.0)],
+        COLOUR,
+    );
+    check(
+        renderer,
+        &polygon,
+        &[
+            ((7, 4), [0, 0, 0]),
+            ((8, 4), [255, 128, 128]),
+            ((9, 4), [255, 255, 255]),
+        ],
+    );
+}
+
+#[test]
+fn the_cpu_covers_edge_pixels_in_part() {
+    check_edges(&mut CpuRenderer::new(SIZE, SIZE));
+}
+
+#[test]
+fn the_gpu_covers_edge_pixels_in_part() {
+    let Some(mut renderer) = gpu_renderer(SIZE, SIZE) else {
+        return;
+    };
+    check_edges(&mut renderer);
+}
+
+#[test]
+fn samples_are_limited() {
+    assert_eq!(Antialiasing::None.to_uniforms(), (0, 1));
+    assert_eq!(Antialiasing::Analytic.to_uniforms(), (1, 1));
+    assert_eq!(Antialiasing::Supersample(4).to_uniforms(), (2, 4));
+    assert_eq!(Antialiasing::Supersample(0).to_uniforms(), (2, 1));
+    assert_eq!(
+        Antialiasing::Supersample(1000).to_uniforms(),
+        (2, Antialiasing::MAX_SAMPLES)
+    );
+}
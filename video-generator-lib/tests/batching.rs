@@ -0,0 +1,83 @@
+mod common;
+
+use common::{gpu_renderer, pixel};
+use video_generator_lib::{
+    color::Color,
+    renderer::{Antialiasing, Renderer},
+    shapes::*,
+};
+
+const SIZE: u32 = 32;
+const RED: Color = Color::rgb(255, 0, 0);
+const BLUE: Color = Color::rgb(0, 0, 255);
+
+/// Translucent shapes piled on each other across tile boundaries, in blend modes which
+/// give a different colour in a different order, with an opaque pair whose order is known.
+fn scene() -> Vec<Shape> {
+    let mut shapes = vec![
+        RectangleData::new_shape((2.0, 2.0), (12.0, 12.0), RED),
+        CircleData::new_shape((10.0, 10.0), 5.0, BLUE),
+    ];
+    let modes = [
+        BlendMode::Normal,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Darken,
+    ];
+    for i in 0..16 {
+        let offset = i as f32 * 1.3;
+        let colour = Color::rgba(40 + i * 13, 200 - i * 11, 90 + i * 7, 150);
+        shapes.push(Shape::Rectangle(
+            RectangleData::new((6.0 + offset, 4.0 + offset), (11.5, 7.25), colour)
+                .with_rotation(i as f32 * 0.2)
+                .with_blend_mode(modes[i as usize % modes.len()]),
+        ));
+        shapes.push(CircleData::new_shape(
+            (26.0 - offset, 6.0 + offset),
+            4.5,
+            Color::rgba(250 - i * 9, 120, 30 + i * 12, 120),
+        ));
+    }
+    shapes
+}
+
+#[test]
+fn batched_frames_match_one_dispatch_per_shape() {
+    let Some(mut renderer) = gpu_renderer(SIZE, SIZE) else {
+        return;
+    };
+    let shapes = scene();
+    for antialiasing in [
+        Antialiasing::None,
+        Antialiasing::Analytic,
+        Antialiasing::Supersample(4),
+    ] {
+        renderer.set_antialiasing(antialiasing);
+        let unbatched = renderer.set_batched(false).render_frame(&shapes);
+        let batched = renderer.set_batched(true).render_frame(&shapes);
+        assert!(
+            batched == unbatched,
+            "{antialiasing:?}: batching changed the frame"
+        );
+    }
+}
+
+#[test]
+fn batched_shapes_keep_their_draw_order() {
+    let Some(mut renderer) = gpu_renderer(SIZE, SIZE) else {
+        return;
+    };
+    let (red, blue) = (
+        RectangleData::new_shape((2.0, 2.0), (12.0, 12.0), RED),
+        CircleData::new_shape((10.0, 10.0), 5.0, BLUE),
+    );
+    for batched in [false, true] {
+        renderer.set_batched(batched);
+        let frame = renderer.render_frame(&[red.clone(), blue.clone()]);
+        assert_eq!(pixel(&frame, SIZE, 10, 10), BLUE.to_packed(), "{batched}");
+        assert_eq!(pixel(&frame, SIZE, 3, 3), RED.to_packed(), "{batched}");
+        let frame = renderer.render_frame(&[blue.clone(), red.clone()]);
+        assert_eq!(pixel(&frame, SIZE, 10, 10), RED.to_packed(), "{batched}");
+        assert_eq!(pixel(&frame, SIZE, 14, 10), BLUE.to_packed(), "{batched}");
+    }
+}
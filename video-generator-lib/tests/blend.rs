@@ -0,0 +1,160 @@
+mod common;
+
+use common::{assert_close, gpu_renderer, pixel};
+use video_generator_lib::{
+    color::Color,
+    renderer::{CpuRenderer, Renderer},
+    shapes::*,
+};
+
+const SIZE: u32 = 8;
+
+const MODES: [BlendMode; 6] = [
+    BlendMode::Normal,
+    BlendMode::Multiply,
+    BlendMode::Screen,
+    BlendMode::Additive,
+    BlendMode::Darken,
+    BlendMode::Lighten,
+];
+
+/// B(Cb, Cs) from the W3C's Compositing and Blending Level 1, section 10.
+/// Additive isn't one of its blend functions, and is the sum clamped to white.
+fn blend(mode: BlendMode, cb: f32, cs: f32) -> f32 {
+    match mode {
+        BlendMode::Normal => cs,
+        BlendMode::Multiply => cb * cs,
+        BlendMode::Screen => cb + cs - cb * cs,
+        BlendMode::Additive => (cb + cs).min(1.0),
+        BlendMode::Darken => cb.min(cs),
+        BlendMode::Lighten => cb.max(cs),
+    }
+}
+
+/// `source` drawn with `mode` over `backdrop`, worked out in linear light with the spec's general
+/// formula: Cs' = (1 - αb) × Cs + αb × B(Cb, Cs), then source-over,
+/// co = αs × Cs' + αb × Cb × (1 - αs) and αo = αs + αb × (1 - αs).
+fn expected(mode: BlendMode, backdrop: Color, source: Color) -> u32 {
+    let (cb, cs) = (backdrop.to_linear(), source.to_linear());
+    let (ab, a_s) = (cb[3], cs[3]);
+    let alpha = a_s + ab * (1.0 - a_s);
+    if alpha == 0.0 {
+        return 0;
+    }
+    let mut result = [0.0; 4];
+    for i in 0..3 {
+        let mixed = (1.0 - ab) * cs[i] + ab * blend(mode, cb[i], cs[i]);
+        result[i] = (a_s * mixed + ab * cb[i] * (1.0 - a_s)) / alpha;
+    }
+    result[3] = alpha;
+    Color::from_linear(result).to_packed()
+}
+
+fn pairs() -> Vec<(Color, Color)> {
+    let colours = [
+        Color::rgb(255, 0, 0),
+        Color::rgb(40, 180, 90),
+        Color::rgba(200, 100, 50, 128),
+        Color::rgba(10, 20, 250, 64),
+        Color::WHITE,
+        Color::BLACK,
+    ];
+    let mut pairs = vec![];
+    for backdrop in colours {
+        for source in colours {
+            pairs.push((backdrop, source));
+        }
+    }
+    pairs
+}
+
+/// Draws `source` over `backdrop` with every mode, one mode to a row, returning each pixel.
+fn render(renderer: &mut dyn Renderer, backdrop: Color, source: Color) -> Vec<u32> {
+    let mut shapes = vec![RectangleData::new_shape(
+        (0.0, 0.0),
+        (SIZE as f32, SIZE as f32),
+        backdrop,
+    )];
+    for (y, mode) in MODES.into_iter().enumerate() {
+        shapes.push(Shape::Rectangle(
+            RectangleData::new((0.0, y as f32), (SIZE as f32, 1.0), source).with_blend_mode(mode),
+        ));
+    }
+    let frame = renderer.render_frame(&shapes);
+    (0..MODES.len() as u32)
+        .map(|y| pixel(&frame, SIZE, 4, y))
+        .collect()
+}
+
+fn check(renderer: &mut dyn Renderer) {
+    for (backdrop, source) in pairs() {
+        let pixels = render(renderer, backdrop, source);
+        for (mode, actual) in MODES.into_iter().zip(pixels) {
+            assert_close(
+                actual,
+                expected(mode, backdrop, source),
+                &format!("{source:?} over {backdrop:?} with {mode:?}"),
+            );
+        }
+    }
+}
+
+#[test]
+fn the_cpu_blends_as_the_w3c_formulas_say() {
+    check(&mut CpuRenderer::new(SIZE, SIZE));
+}
+
+#[test]
+fn the_gpu_blends_as_the_w3c_formulas_say() {
+    let Some(mut renderer) = gpu_renderer(SIZE, SIZE) else {
+        return;
+    };
+    check(&mut renderer);
+}
+
+#[test]
+fn blends_work_in_linear_light() {
+    // Half-transparent white over black gives half the light, which is far lighter than half the
+    // sRGB value, and multiplying by that grey halves the light again.
+    let grey = Color::from_linear([0.5, 0.5, 0.5, 1.0]);
+    let cases = [
+        (
+            BlendMode::Normal,
+            Color::BLACK,
+            Color::WHITE.with_alpha(0.5),
+            grey,
+        ),
+        (BlendMode::Multiply, Color::WHITE, grey, grey),
+        (
+            BlendMode::Multiply,
+            grey,
+            grey,
+            Color::from_linear([0.25, 0.25, 0.25, 1.0]),
+        ),
+        (BlendMode::Screen, Color::BLACK, grey, grey),
+        (BlendMode::Additive, grey, grey, Color::WHITE),
+    ];
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    for (mode, backdrop, source, result) in cases {
+        assert_close(
+            render(&mut renderer, backdrop, source)[mode as usize],
+            result.to_packed(),
+            &format!("{mode:?}"),
+        );
+    }
+    // Blending onto nothing draws the source as it is, whatever the mode.
+    let source = Color::rgba(200, 100, 50, 128);
+    for pixel in render(&mut renderer, Color::TRANSPARENT, source) {
+        assert_close(pixel, source.to_packed(), "over nothing");
+    }
+}
+
+#[test]
+fn unknown_modes_are_normal() {
+    for (i, mode) in MODES.into_iter().enumerate() {
+        assert_eq!(BlendMode::from_index(i as u32), mode);
+        assert_eq!(mode as u32, i as u32);
+    }
+    assert_eq!(BlendMode::from_index(MODES.len() as u32), BlendMode::Normal);
+    assert_eq!(BlendMode::from_index(u32::MAX), BlendMode::Normal);
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use common::{gpu_renderer, pixel};
+use video_generator_lib::{
+    color::Color,
+    renderer::{Canvas, CanvasMode, CpuRenderer, Renderer},
+    shapes::*,
+};
+
+const SIZE: u32 = 16;
+
+fn white_square() -> Vec<Shape> {
+    vec![RectangleData::new_shape(
+        (0.0, 0.0),
+        (4.0, 4.0),
+        Color::WHITE,
+    )]
+}
+
+/// Renders the square, then `empty` frames of nothing, and returns the last frame.
+fn fade(canvas: Canvas, empty: usize) -> Vec<u8> {
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    renderer.set_canvas(canvas);
+    let mut frame = renderer.render_frame(&white_square());
+    for _ in 0..empty {
+        frame = renderer.render_frame(&[]);
+    }
+    frame
+}
+
+#[test]
+fn cleared_canvases_start_from_the_background() {
+    let canvas = Canvas::new(Color::BLACK);
+    assert_eq!(canvas.mode, CanvasMode::Clear);
+    let frame = fade(canvas, 0);
+    assert_eq!(pixel(&frame, SIZE, 1, 1), Color::WHITE.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 8, 8), Color::BLACK.to_packed());
+    assert_eq!(
+        pixel(&fade(canvas, 1), SIZE, 1, 1),
+        Color::BLACK.to_packed()
+    );
+    assert_eq!(pixel(&fade(Canvas::transparent(), 1), SIZE, 1, 1), 0);
+}
+
+#[test]
+fn persisted_canvases_fade_towards_the_background() {
+    let canvas = Canvas::persist(Color::BLACK, 0.5);
+    // Each frame goes half the rest of the way, rounded to the nearest byte.
+    for (empty, grey) in [(0, 0xFF), (1, 0x80), (2, 0x40), (3, 0x20)] {
+        let frame = fade(canvas, empty);
+        assert_eq!(
+            pixel(&frame, SIZE, 1, 1),
+            Color::rgb(grey, grey, grey).to_packed(),
+            "after {empty} frames"
+        );
+        assert_eq!(pixel(&frame, SIZE, 8, 8), Color::BLACK.to_packed());
+    }
+
+    // No decay keeps the frame as it was, and full decay clears it.
+    let frame = fade(Canvas::persist(Color::BLACK, 0.0), 5);
+    assert_eq!(pixel(&frame, SIZE, 1, 1), Color::WHITE.to_packed());
+    assert_eq!(
+        fade(Canvas::persist(Color::BLACK, 1.0), 1),
+        fade(Canvas::new(Color::BLACK), 1)
+    );
+}
+
+#[test]
+fn decay_is_clamped() {
+    assert_eq!(Canvas::new(Color::BLACK).decay(), 1.0);
+    assert_eq!(Canvas::persist(Color::BLACK, 0.25).decay(), 0.25);
+    assert_eq!(Canvas::persist(Color::BLACK, -1.0).decay(), 0.0);
+    assert_eq!(Canvas::persist(Color::BLACK, 2.0).decay(), 1.0);
+    assert_eq!(
+        fade(Canvas::persist(Color::BLACK, 2.0), 1),
+        fade(Canvas::new(Color::BLACK), 1)
+    );
+}
+
+#[test]
+fn sub_frames_all_start_from_the_persisted_canvas() {
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    renderer.set_canvas(Canvas::persist(Color::BLACK, 0.0));
+    renderer.render_frame(&white_square());
+    // Neither sub-frame draws over the other, so the square is kept whole.
+    let frame = renderer.render_sub_frames(&[vec![], vec![]]);
+    assert_eq!(pixel(&frame, SIZE, 1, 1), Color::WHITE.to_packed());
+    let moved = RectangleData::new_shape((8.0, 8.0), (4.0, 4.0), Color::WHITE);
+    let frame = renderer.render_sub_frames(&[vec![moved], vec![]]);
+    assert_eq!(pixel(&frame, SIZE, 1, 1), Color::WHITE.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 9, 9), 0xFFBCBCBC);
+}
+
+#[test]
+fn the_gpu_fades_like_the_cpu() {
+    let Some(mut gpu) = gpu_renderer(SIZE, SIZE) else {
+        return;
+    };
+    let mut cpu = CpuRenderer::new(SIZE, SIZE);
+    for canvas in [
+        Canvas::persist(Color::rgb(0, 0, 255), 0.25),
+        Canvas::new(Color::BLACK),
+        Canvas::persist(Color::BLACK, 0.0),
+    ] {
+        gpu.set_canvas(canvas);
+        cpu.set_canvas(canvas);
+        for shapes in [white_square(), vec![], vec![]] {
+            assert_eq!(
+                gpu.render_frame(&shapes),
+                cpu.render_frame(&shapes),
+                "{canvas:?}"
+            );
+        }
+    }
+}
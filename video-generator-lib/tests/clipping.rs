@@ -0,0 +1,102 @@
+mod common;
+
+use common::{pixel, COLOUR};
+use video_generator_lib::{
+    renderer::{Antialiasing, CpuRenderer, Renderer},
+    shapes::*,
+};
+
+const SIZE: u32 = 64;
+
+/// A ball under gravity with no walls, so it leaves the canvas through the left and bottom edges.
+fn generate_frames(save_frame: &mut dyn FnMut(Vec<Shape>)) {
+    let radius = 10.0f32;
+    let mut centre = (20.0f32, 20.0f32);
+    let mut velocity = (-1.5f32, 0.0f32);
+    for _ in 0..60 {
+        velocity.1 += 0.2;
+        centre.0 += velocity.0;
+        centre.1 += velocity.1;
+        save_frame(vec![CircleData::new_shape(centre, radius, COLOUR)]);
+    }
+}
+
+/// Whether the hard-edged circle kernel covers a pixel, worked out without any clipping.
+fn covers(circle: &CircleData, x: i32, y: i32) -> bool {
+    let (origin_x, origin_y, width, height) = circle.bounding_box();
+    if x < origin_x || y < origin_y || x >= origin_x + width as i32 || y >= origin_y + height as i32
+    {
+        return false;
+    }
+    let dx = (x - origin_x) as f32 - circle.radius;
+    let dy = (y - origin_y) as f32 - circle.radius;
+    dx * dx + dy * dy <= circle.radius * circle.radius
+}
+
+#[test]
+fn balls_leaving_the_canvas_are_clipped_in_place() {
+    let mut frames = vec![];
+    generate_frames(&mut |frame| frames.push(frame));
+
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    for shapes in &frames {
+        let Shape::Circle(circle) = &shapes[0] else {
+            unreachable!()
+        };
+        let frame = renderer.render_frame(shapes);
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let expected = if covers(circle, x as i32, y as i32) {
+                    COLOUR.to_packed()
+                } else {
+                    0
+                };
+                assert_eq!(
+                    pixel(&frame, SIZE, x, y),
+                    expected,
+                    "pixel ({x}, {y}) with the ball at {:?}",
+                    circle.position
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn shapes_off_the_canvas_are_culled() {
+    let off_canvas = [
+        CircleData::new_shape((-30.0, 10.0), 10.0, COLOUR),
+        CircleData::new_shape((10.0, SIZE as f32 + 30.0), 10.0, COLOUR),
+        RectangleData::new_shape((SIZE as f32, 0.0), (20.0, 20.0), COLOUR),
+        RectangleData::new_shape((-20.0, -20.0), (20.0, 20.0), COLOUR),
+    ];
+    for shape in &off_canvas {
+        for antialiasing in [Antialiasing::None, Antialiasing::Analytic] {
+            assert_eq!(
+                shape.clipped_box(antialiasing, SIZE, SIZE),
+                None,
+                "{shape:?}"
+            );
+        }
+    }
+
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    let frame = renderer.render_frame(&off_canvas);
+    assert!(frame.iter().all(|&x| x == 0));
+}
+
+#[test]
+fn shapes_past_the_right_edge_do_not_wrap() {
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    let frame = renderer.render_frame(&[RectangleData::new_shape(
+        (SIZE as f32 - 4.0, 10.0),
+        (20.0, 4.0),
+        COLOUR,
+    )]);
+    for y in 10..14 {
+        for x in 0..SIZE {
+            let expected = if x >= SIZE - 4 { COLOUR.to_packed() } else { 0 };
+            assert_eq!(pixel(&frame, SIZE, x, y), expected, "pixel ({x}, {y})");
+        }
+    }
+}
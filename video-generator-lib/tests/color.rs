@@ -0,0 +1,169 @@
+mod common;
+
+use common::{assert_close, channels, pixel};
+use video_generator_lib::{
+    color::Color,
+    node::Circle,
+    renderer::{Canvas, CpuRenderer, Renderer},
+    shapes::*,
+    signal::Signal,
+};
+
+const SIZE: u32 = 16;
+
+#[test]
+fn argb_colours_are_still_solid_fills() {
+    let red = 0xFFFF0000;
+    assert_eq!(Color::from(red), Color::rgb(255, 0, 0));
+    assert_eq!(Color::from(0x80123456), Color::rgba(0x12, 0x34, 0x56, 0x80));
+    // Opaque, they read like hex.
+    assert_eq!(Color::from(0xFF3366CC), Color::hex(0x3366CC));
+    assert_eq!(Fill::from(red), Fill::Solid(Color::rgb(255, 0, 0)));
+    assert_eq!(
+        Stroke::new(1.0, red).colour,
+        Stroke::new(1.0, Color::rgb(255, 0, 0)).colour
+    );
+    assert_eq!(
+        GradientStop::new(0.5, red).colour,
+        Color::rgb(255, 0, 0).to_packed()
+    );
+
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    let frame = renderer.render_frame(&[RectangleData::new_shape((0.0, 0.0), (4.0, 4.0), red)]);
+    assert_eq!(channels(&frame, SIZE, 1, 1), [255, 0, 0, 255]);
+
+    let colour = Signal::new(red);
+    let fill = |circle: &Circle| match circle.to_shape() {
+        Shape::Circle(circle) => circle.fill,
+        _ => unreachable!(),
+    };
+    let mut circle = Circle::new(|| 8.0, || 8.0, || 4.0, &colour);
+    assert_eq!(fill(&circle), Fill::Solid(Color::rgb(255, 0, 0)));
+    circle.set_colour(|| 0xFF0000FFu32);
+    assert_eq!(fill(&circle), Fill::Solid(Color::rgb(0, 0, 255)));
+}
+
+#[test]
+fn packed_bytes_are_rgba() {
+    let colour = Color::rgba(0x11, 0x22, 0x33, 0x44);
+    assert_eq!(colour.to_packed(), 0x44332211);
+    assert_eq!(colour.to_packed().to_le_bytes(), [0x11, 0x22, 0x33, 0x44]);
+    assert_eq!(Color::from_packed(0x44332211), colour);
+    // Channels are clamped and rounded to the nearest byte.
+    assert_eq!(Color::new(1.5, -0.5, 0.5, 1.0).to_packed(), 0xFF80_00FF);
+}
+
+#[test]
+fn hex_colours_parse_in_every_css_form() {
+    let orange = Color::rgb(0xFF, 0x88, 0x00);
+    assert_eq!(Color::parse_hex("#F80"), Ok(orange));
+    assert_eq!(Color::parse_hex("f80"), Ok(orange));
+    assert_eq!(Color::parse_hex("#FF8800"), Ok(orange));
+    assert_eq!(
+        Color::parse_hex("#F808"),
+        Ok(orange.with_alpha(0x88 as f32 / 255.0))
+    );
+    assert_eq!(
+        Color::parse_hex("#ff880080"),
+        Ok(Color::rgba(0xFF, 0x88, 0, 0x80))
+    );
+    assert_eq!(Color::hex(0x336699), Color::rgb(0x33, 0x66, 0x99));
+    assert_eq!(
+        Color::hex_alpha(0x33669980),
+        Color::rgba(0x33, 0x66, 0x99, 0x80)
+    );
+
+    for text in [
+        "",
+        "#",
+        "#12",
+        "#12345",
+        "#1234567",
+        "#123456789",
+        "#GGG",
+        "#+12",
+        "# 123",
+    ] {
+        let error = Color::parse_hex(text).unwrap_err();
+        assert_eq!(error.to_string(), format!("{text:?} isn't a hex colour"));
+    }
+}
+
+#[test]
+fn hsl_hsv_and_oklch_give_the_colours_they_name() {
+    let named = [
+        (Color::hsl(0.0, 1.0, 0.5), 0xFF0000FF),
+        (Color::hsl(120.0, 1.0, 0.5), 0xFF00FF00),
+        (Color::hsl(240.0, 1.0, 0.25), 0xFF800000),
+        (Color::hsl(-60.0, 1.0, 0.5), 0xFFFF00FF),
+        (Color::hsl(30.0, 0.0, 0.6), 0xFF999999),
+        (Color::hsv(60.0, 1.0, 1.0), 0xFF00FFFF),
+        (Color::hsv(300.0, 0.5, 1.0), 0xFFFF80FF),
+        (Color::hsv(720.0, 1.0, 0.5), 0xFF000080),
+        (Color::hsv(0.0, 0.0, 1.0), 0xFFFFFFFF),
+        // The sRGB primaries and white, as CSS Color 4 gives them in OKLCH.
+        (Color::oklch(0.627955, 0.257683, 29.2339), 0xFF0000FF),
+        (Color::oklch(0.86644, 0.294827, 142.4953), 0xFF00FF00),
+        (Color::oklch(0.452014, 0.313214, 264.052), 0xFFFF0000),
+        (Color::oklch(1.0, 0.0, 0.0), 0xFFFFFFFF),
+    ];
+    for (colour, packed) in named {
+        assert_eq!(colour.to_packed(), packed, "{colour:?}");
+    }
+}
+
+#[test]
+fn linear_light_round_trips() {
+    for byte in 0..=255 {
+        let colour = Color::rgba(byte, 255 - byte, byte / 2, 200);
+        assert_eq!(
+            Color::from_linear(colour.to_linear()).to_packed(),
+            colour.to_packed()
+        );
+    }
+    // Mid grey in linear light is far lighter than half the sRGB value.
+    assert_eq!(
+        Color::from_linear([0.5, 0.5, 0.5, 1.0]).to_packed(),
+        0xFFBCBCBC
+    );
+    assert!((Color::rgb(128, 128, 128).to_linear()[0] - 0.2158605).abs() < 1e-6);
+}
+
+/// The colour between `a` and `b`, mixed in linear light as renderers mix them.
+fn mix_linear(a: Color, b: Color, t: f32) -> Color {
+    let (a, b) = (a.to_linear(), b.to_linear());
+    Color::from_linear(std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t))
+}
+
+#[test]
+fn gradients_mix_in_linear_light() {
+    let (red, green) = (Color::rgb(255, 0, 0), Color::rgb(0, 255, 0));
+    let fill = Fill::linear((0.0, 0.0), (SIZE as f32, 0.0), &[(0.0, red), (1.0, green)]);
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    let frame = renderer.render_frame(&[RectangleData::new_shape(
+        (0.0, 0.0),
+        (SIZE as f32, 1.0),
+        fill,
+    )]);
+    for x in 0..SIZE {
+        let t = (x as f32 + 0.5) / SIZE as f32;
+        let expected = mix_linear(red, green, t).to_packed();
+        assert_close(pixel(&frame, SIZE, x, 0), expected, &format!("pixel {x}"));
+    }
+}
+
+#[test]
+fn sub_frames_average_in_linear_light() {
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    renderer.set_canvas(Canvas::new(Color::BLACK));
+    let white = RectangleData::new_shape((0.0, 0.0), (4.0, 4.0), Color::WHITE);
+    let frame = renderer.render_sub_frames(&[vec![white], vec![]]);
+    assert_eq!(pixel(&frame, SIZE, 1, 1), 0xFFBCBCBC);
+    assert_eq!(pixel(&frame, SIZE, 8, 8), Color::BLACK.to_packed());
+
+    // On a transparent canvas, the colour is kept and only its alpha averaged.
+    renderer.set_canvas(Canvas::transparent());
+    let red = RectangleData::new_shape((0.0, 0.0), (4.0, 4.0), Color::rgb(255, 0, 0));
+    let frame = renderer.render_sub_frames(&[vec![red], vec![], vec![], vec![]]);
+    assert_eq!(pixel(&frame, SIZE, 1, 1), 0x400000FF);
+}
@@ -0,0 +1,85 @@
+//! Helpers shared by the integration tests, each of which uses only some of them.
+#![allow(dead_code)]
+
+use video_generator_lib::{
+    color::Color,
+    renderer::{AdapterOptions, Antialiasing, GpuRenderer, Renderer, ShaderSources},
+    shapes::Shape,
+};
+
+/// The colour shapes are drawn in when all that matters is which pixels they cover.
+pub const COLOUR: Color = Color::rgb(0, 255, 0);
+
+/// Pixel `(x, y)` of a frame `width` pixels across, packed as [`Color::to_packed`] packs it.
+pub fn pixel(frame: &[u8], width: u32, x: u32, y: u32) -> u32 {
+    let i = ((y * width + x) * 4) as usize;
+    u32::from_le_bytes(frame[i..i + 4].try_into().unwrap())
+}
+
+/// The red, green, blue and alpha of pixel `(x, y)` of a frame `width` pixels across.
+pub fn channels(frame: &[u8], width: u32, x: u32, y: u32) -> [u8; 4] {
+    pixel(frame, width, x, y).to_le_bytes()
+}
+
+/// The GPU renderer on the adapter the environment asks for, or `None` if there isn't one.
+pub fn gpu_renderer(width: u32, height: u32) -> Option<GpuRenderer> {
+    let options = AdapterOptions::from_env();
+    match pollster::block_on(GpuRenderer::with_options(
+        width,
+        height,
+        &ShaderSources::built_in(),
+        &options,
+    )) {
+        Ok(renderer) => Some(renderer),
+        Err(error) => {
+            eprintln!("Skipping the GPU: {error}");
+            None
+        }
+    }
+}
+
+/// Checks that each channel of packed pixel `actual` is within one step of rounding of `expected`.
+pub fn assert_close(actual: u32, expected: u32, what: &str) {
+    let (actual, expected) = (actual.to_le_bytes(), expected.to_le_bytes());
+    assert!(
+        actual
+            .iter()
+            .zip(&expected)
+            .all(|(a, b)| a.abs_diff(*b) <= 1),
+        "{what}: {actual:?}, expected {expected:?}"
+    );
+}
+
+/// Checks that every byte of frame `actual` is within one step of rounding of `expected`.
+pub fn assert_frames_close(actual: &[u8], expected: &[u8], what: &str) {
+    for (i, (a, b)) in actual.iter().zip(expected).enumerate() {
+        assert!(a.abs_diff(*b) <= 1, "{what}: byte {i} is {a}, expected {b}");
+    }
+}
+
+/// Checks that hard-edged `shape` fills exactly the pixels whose centres `inside` says it does,
+/// in [`COLOUR`].
+pub fn assert_fills(
+    renderer: &mut dyn Renderer,
+    shape: &Shape,
+    inside: impl Fn(f32, f32) -> bool,
+    what: &str,
+) {
+    renderer.set_antialiasing(Antialiasing::None);
+    let frame = renderer.render_frame(std::slice::from_ref(shape));
+    let width = renderer.width();
+    for y in 0..renderer.height() {
+        for x in 0..width {
+            let expected = if inside(x as f32 + 0.5, y as f32 + 0.5) {
+                COLOUR.to_packed()
+            } else {
+                0
+            };
+            assert_eq!(
+                pixel(&frame, width, x, y),
+                expected,
+                "pixel ({x}, {y}) of {what}"
+            );
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use video_generator_lib::{
+    color::Color,
+    config::{FrameFormat, RenderConfig},
+    render_scene,
+    renderer::CpuRenderer,
+    shapes::*,
+};
+
+#[test]
+fn frames_are_saved_next_to_the_video() {
+    let config = RenderConfig::new(16, 16, 30, "renders/intro.mp4");
+    assert_eq!(config.frame_path(0), PathBuf::from("renders/intro-0.bmp"));
+    assert_eq!(config.frame_path(12), PathBuf::from("renders/intro-12.bmp"));
+    assert_eq!(
+        config.frame_path_pattern(),
+        PathBuf::from("renders/intro-%d.bmp")
+    );
+
+    let config = config.with_frame_format(FrameFormat::Png);
+    assert_eq!(config.frame_path(3), PathBuf::from("renders/intro-3.png"));
+    assert_eq!(
+        config.frame_path_pattern(),
+        PathBuf::from("renders/intro-%d.png")
+    );
+
+    // A video in the working directory has its frames there too.
+    let config = RenderConfig::new(16, 16, 30, "video.mp4");
+    assert_eq!(config.frame_path(1), PathBuf::from("video-1.bmp"));
+}
+
+#[test]
+fn scenes_without_frames_render_nothing() {
+    let config = RenderConfig::new(16, 16, 30, "output/empty.mp4");
+    let mut renderer = CpuRenderer::new(16, 16);
+    let frames = render_scene(&config, &mut renderer, |_, _| {}, 0, 10);
+    assert!(frames.is_empty());
+}
+
+#[test]
+fn scenes_are_rendered_between_the_frames_asked_for() {
+    let config = RenderConfig::new(16, 16, 30, "output/scene.mp4");
+    let mut renderer = CpuRenderer::new(16, 16);
+    // Frame `i` is a square `i` pixels wide.
+    let scene = |config: &RenderConfig, save: &mut dyn FnMut(Vec<Shape>)| {
+        for time in config.frame_times(6) {
+            save(vec![RectangleData::new_shape(
+                (0.0, 0.0),
+                (time, 1.0),
+                Color::WHITE,
+            )]);
+        }
+    };
+    let frames = render_scene(&config, &mut renderer, scene, 2, 4);
+    assert_eq!(frames.len(), 2);
+    for (frame, width) in frames.iter().zip(2..) {
+        assert_eq!(frame.get_pixel(width - 1, 0).0, [255; 4]);
+        assert_eq!(frame.get_pixel(width, 0).0, [0; 4]);
+    }
+}
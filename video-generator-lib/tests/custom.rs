@@ -0,0 +1,133 @@
+mod common;
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use common::{gpu_renderer, pixel, COLOUR};
+use video_generator_lib::{
+    color::Color,
+    renderer::{CpuRenderer, Renderer, ShaderError, ShaderSources},
+    shapes::*,
+};
+
+const SIZE: u32 = 32;
+
+/// Fills its box with red.
+fn red_box(position: (f32, f32), size: (f32, f32)) -> Shape {
+    let shader = CustomShader::new(
+        "@compute @workgroup_size(8, 8)
+        fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+            if (in_region(global_id)) {
+                draw_pixel(region_pixel(global_id), vec4<f32>(1.0, 0.0, 0.0, 1.0));
+            }
+        }",
+    );
+    CustomData::new_shape(shader, position, size)
+}
+
+fn scene() -> Vec<Shape> {
+    vec![
+        red_box((0.0, 0.0), (8.0, 8.0)),
+        RectangleData::new_shape((16.0, 16.0), (8.0, 8.0), COLOUR),
+    ]
+}
+
+#[test]
+fn the_cpu_leaves_custom_shapes_out() {
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    assert!(!renderer.supports_custom_shapes());
+    let frame = renderer.render_frame(&scene());
+    assert_eq!(pixel(&frame, SIZE, 4, 4), 0);
+    assert_eq!(pixel(&frame, SIZE, 20, 20), COLOUR.to_packed());
+}
+
+#[test]
+fn the_gpu_draws_custom_shapes() {
+    let Some(mut renderer) = gpu_renderer(SIZE, SIZE) else {
+        return;
+    };
+    assert!(renderer.supports_custom_shapes());
+    let frame = renderer.render_frame(&scene());
+    assert_eq!(pixel(&frame, SIZE, 4, 4), Color::rgb(255, 0, 0).to_packed());
+    assert_eq!(pixel(&frame, SIZE, 12, 12), 0);
+    assert_eq!(pixel(&frame, SIZE, 20, 20), COLOUR.to_packed());
+}
+
+#[test]
+fn broken_custom_shaders_are_reported_once() {
+    let Some(mut renderer) = gpu_renderer(SIZE, SIZE) else {
+        return;
+    };
+    let broken = CustomData::new_shape(CustomShader::new("oops"), (0.0, 0.0), (8.0, 8.0));
+    let shapes = [
+        broken,
+        RectangleData::new_shape((16.0, 16.0), (8.0, 8.0), COLOUR),
+    ];
+    let frame = renderer.render_frame(&shapes);
+    assert_eq!(pixel(&frame, SIZE, 4, 4), 0);
+    assert_eq!(pixel(&frame, SIZE, 20, 20), COLOUR.to_packed());
+    let errors = renderer.take_shader_errors();
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    let ShaderError::Invalid { file, line, .. } = &errors[0] else {
+        panic!("{errors:?}")
+    };
+    assert!(file.starts_with("<custom shader "), "{file}");
+    assert_eq!(*line, 1);
+
+    renderer.render_frame(&shapes);
+    assert!(renderer.take_shader_errors().is_empty());
+}
+
+#[test]
+fn watched_shaders_which_break_are_reported_and_kept() {
+    let Some(mut renderer) = gpu_renderer(SIZE, SIZE) else {
+        return;
+    };
+    let directory = std::env::temp_dir().join(format!("shader-watch-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let sources = ShaderSources::built_in();
+    for (file, source) in [
+        (ShaderSources::COMMON_FILE, &sources.common),
+        (ShaderSources::SHAPE_FILE, &sources.shape),
+        (ShaderSources::CANVAS_FILE, &sources.canvas),
+        (ShaderSources::POLYGON_FILE, &sources.polygon),
+        (ShaderSources::EFFECT_FILE, &sources.effect),
+        (ShaderSources::CUSTOM_FILE, &sources.custom),
+        (ShaderSources::RASTER_FILE, &sources.raster),
+    ] {
+        std::fs::write(directory.join(file), source).unwrap();
+    }
+
+    let errors = Arc::new(Mutex::new(vec![]));
+    let handled = errors.clone();
+    renderer
+        .watch_shaders(&directory)
+        .set_shader_error_handler(move |error| handled.lock().unwrap().push(error.to_string()));
+    let canvas = directory.join(ShaderSources::CANVAS_FILE);
+    std::fs::write(
+        &canvas,
+        sources.canvas.clone() + "\nfn broken() -> u32 { return 1.0; }\n",
+    )
+    .unwrap();
+    // Changes are told by modification time, which may be too coarse to have moved.
+    std::fs::File::options()
+        .write(true)
+        .open(&canvas)
+        .and_then(|x| x.set_modified(SystemTime::now() + Duration::from_secs(10)))
+        .unwrap();
+    let frame = renderer.render_frame(&scene()[1..]);
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    assert_eq!(pixel(&frame, SIZE, 20, 20), COLOUR.to_packed());
+    let errors = errors.lock().unwrap();
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert!(
+        errors[0].starts_with(ShaderSources::CANVAS_FILE),
+        "{}",
+        errors[0]
+    );
+    // Errors went to the handler instead.
+    assert!(renderer.take_shader_errors().is_empty());
+}
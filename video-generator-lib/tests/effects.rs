@@ -0,0 +1,308 @@
+mod common;
+
+use common::{assert_frames_close, channels, gpu_renderer, pixel};
+use video_generator_lib::{
+    color::Color,
+    node::PostEffect,
+    renderer::{Antialiasing, Canvas, CpuRenderer, Renderer},
+    shapes::*,
+    signal::Signal,
+};
+
+const SIZE: u32 = 16;
+
+/// A renderer on black, so effects have something opaque to work on.
+fn cpu_renderer() -> CpuRenderer {
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    renderer.set_canvas(Canvas::new(Color::BLACK));
+    renderer
+}
+
+fn full(colour: Color) -> Shape {
+    RectangleData::new_shape((0.0, 0.0), (SIZE as f32, SIZE as f32), colour)
+}
+
+fn square(colour: Color) -> Shape {
+    RectangleData::new_shape((6.0, 6.0), (4.0, 4.0), colour)
+}
+
+fn with_effect(shapes: &[Shape], effect: Effect) -> Vec<Shape> {
+    let mut shapes = shapes.to_vec();
+    shapes.push(Shape::Effect(effect));
+    shapes
+}
+
+#[test]
+fn effects_which_change_nothing_are_left_out() {
+    let unchanged = [
+        Effect::Blur { radius: 0.0 },
+        Effect::Bloom {
+            threshold: 0.5,
+            radius: 2.0,
+            intensity: 0.0,
+        },
+        Effect::Vignette {
+            strength: 0.0,
+            radius: 0.5,
+            softness: 0.5,
+        },
+        Effect::FilmGrain {
+            amount: 0.0,
+            seed: 1,
+        },
+        Effect::ChromaticAberration { offset: 0.0 },
+    ];
+    for effect in unchanged {
+        let records = FrameRecords::new(&[Shape::Effect(effect)], Antialiasing::None, SIZE, SIZE);
+        assert!(records.shapes.is_empty(), "{effect:?}");
+    }
+
+    // Neutral colour adjustments are drawn, but leave every pixel as it was.
+    let shapes = [
+        full(Color::rgb(10, 120, 230)),
+        square(Color::rgb(250, 40, 90)),
+    ];
+    let neutral = Effect::ColourAdjust {
+        brightness: 0.0,
+        contrast: 1.0,
+        saturation: 1.0,
+    };
+    let mut renderer = cpu_renderer();
+    assert_eq!(
+        renderer.render_frame(&with_effect(&shapes, neutral)),
+        renderer.render_frame(&shapes)
+    );
+}
+
+#[test]
+fn blurs_spread_light_evenly() {
+    let records = FrameRecords::new(
+        &[Shape::Effect(Effect::Blur { radius: 1.0 })],
+        Antialiasing::None,
+        SIZE,
+        SIZE,
+    );
+    // Three standard deviations each side of the centre, adding up to one.
+    assert_eq!(records.weights.len(), 7);
+    assert!((records.weights.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+    let far = Effect::Blur { radius: 1000.0 };
+    let records = FrameRecords::new(&[Shape::Effect(far)], Antialiasing::None, SIZE, SIZE);
+    assert_eq!(records.weights.len(), Effect::MAX_BLUR_REACH * 2 + 1);
+
+    let mut renderer = cpu_renderer();
+    let dot = RectangleData::new_shape((8.0, 8.0), (1.0, 1.0), Color::WHITE);
+    let frame = renderer.render_frame(&with_effect(&[dot], Effect::Blur { radius: 1.0 }));
+    let centre = channels(&frame, SIZE, 8, 8)[0];
+    assert!(centre > 0 && centre < 255, "{centre}");
+    for (x, y) in [(7, 8), (9, 8), (8, 7), (8, 9)] {
+        let neighbour = channels(&frame, SIZE, x, y)[0];
+        assert!(
+            neighbour > 0 && neighbour < centre,
+            "({x}, {y}) is {neighbour}"
+        );
+        assert_eq!(pixel(&frame, SIZE, x, y), pixel(&frame, SIZE, 7, 8));
+    }
+    assert_eq!(pixel(&frame, SIZE, 0, 0), Color::BLACK.to_packed());
+
+    // Edges are extended rather than fading in from outside the canvas.
+    let colour = Color::rgb(30, 160, 200);
+    let frame = renderer.render_frame(&with_effect(&[full(colour)], Effect::Blur { radius: 3.0 }));
+    assert!(frame
+        .chunks(4)
+        .all(|x| x == colour.to_packed().to_le_bytes()));
+}
+
+#[test]
+fn bloom_makes_only_bright_pixels_glow() {
+    let bloom = Effect::Bloom {
+        threshold: 0.8,
+        radius: 1.5,
+        intensity: 1.0,
+    };
+    let mut renderer = cpu_renderer();
+    let frame = renderer.render_frame(&with_effect(&[square(Color::WHITE)], bloom));
+    assert!(channels(&frame, SIZE, 5, 8)[0] > 0);
+    assert_eq!(pixel(&frame, SIZE, 8, 8), Color::WHITE.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 0, 0), Color::BLACK.to_packed());
+
+    let grey = Color::rgb(150, 150, 150);
+    let frame = renderer.render_frame(&with_effect(&[square(grey)], bloom));
+    assert_eq!(frame, renderer.render_frame(&[square(grey)]));
+}
+
+#[test]
+fn vignettes_darken_the_corners() {
+    let vignette = Effect::Vignette {
+        strength: 1.0,
+        radius: 0.5,
+        softness: 0.5,
+    };
+    let mut renderer = cpu_renderer();
+    let frame = renderer.render_frame(&with_effect(&[full(Color::WHITE)], vignette));
+    assert_eq!(pixel(&frame, SIZE, 8, 8), Color::WHITE.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 0, 0), Color::BLACK.to_packed());
+    let edge = channels(&frame, SIZE, 0, 8)[0];
+    assert!(edge > 0 && edge < 255, "{edge}");
+    // Only colour is darkened, not alpha.
+    renderer.set_canvas(Canvas::transparent());
+    let frame = renderer.render_frame(&with_effect(&[full(Color::WHITE)], vignette));
+    assert_eq!(channels(&frame, SIZE, 0, 0), [0, 0, 0, 255]);
+}
+
+#[test]
+fn film_grain_follows_its_seed() {
+    let grain = |seed| Effect::FilmGrain { amount: 0.1, seed };
+    let grey = Color::rgb(128, 128, 128);
+    let mut renderer = cpu_renderer();
+    let first = renderer.render_frame(&with_effect(&[full(grey)], grain(1)));
+    assert_eq!(
+        first,
+        renderer.render_frame(&with_effect(&[full(grey)], grain(1)))
+    );
+    assert_ne!(
+        first,
+        renderer.render_frame(&with_effect(&[full(grey)], grain(2)))
+    );
+
+    let mut total = 0i32;
+    for pixel in first.chunks(4) {
+        let change = pixel[0] as i32 - 128;
+        assert!(change.abs() <= 26, "{change}");
+        assert!(pixel[0] == pixel[1] && pixel[1] == pixel[2] && pixel[3] == 255);
+        total += change;
+    }
+    let mean = total as f32 / (SIZE * SIZE) as f32;
+    assert!(mean.abs() < 4.0, "{mean}");
+}
+
+#[test]
+fn chromatic_aberration_splits_red_and_blue_outwards() {
+    let aberration = Effect::ChromaticAberration { offset: 4.0 };
+    let mut renderer = cpu_renderer();
+    let frame = renderer.render_frame(&with_effect(&[square(Color::WHITE)], aberration));
+    // Red is sampled from further out and blue from nearer the centre, so past the square's
+    // right edge red pulls in and blue spreads out, while the middle doesn't move.
+    assert_eq!(pixel(&frame, SIZE, 8, 8), Color::WHITE.to_packed());
+    assert_eq!(channels(&frame, SIZE, 9, 8), [0, 255, 255, 255]);
+    assert_eq!(channels(&frame, SIZE, 10, 8), [0, 0, 255, 255]);
+    assert_eq!(channels(&frame, SIZE, 11, 8), [0, 0, 255, 255]);
+    assert_eq!(pixel(&frame, SIZE, 12, 8), Color::BLACK.to_packed());
+    // And the same on the other side.
+    assert_eq!(channels(&frame, SIZE, 6, 8), [0, 255, 255, 255]);
+    assert_eq!(channels(&frame, SIZE, 4, 8), [0, 0, 255, 255]);
+    let frame = renderer.render_frame(&with_effect(&[full(Color::WHITE)], aberration));
+    assert!(frame.iter().all(|&x| x == 255));
+}
+
+#[test]
+fn colour_adjustments_work_on_each_channel() {
+    let adjust = |brightness, contrast, saturation| Effect::ColourAdjust {
+        brightness,
+        contrast,
+        saturation,
+    };
+    let mut renderer = cpu_renderer();
+    let mut adjusted = |colour, effect| {
+        let frame = renderer.render_frame(&with_effect(&[full(colour)], effect));
+        pixel(&frame, SIZE, 4, 4)
+    };
+    let grey = Color::rgb(100, 100, 100);
+    assert_eq!(
+        adjusted(grey, adjust(0.2, 1.0, 1.0)),
+        Color::rgb(151, 151, 151).to_packed()
+    );
+    assert_eq!(
+        adjusted(Color::rgb(20, 200, 90), adjust(0.0, 0.0, 1.0)),
+        Color::rgb(128, 128, 128).to_packed()
+    );
+    assert_eq!(
+        adjusted(Color::rgb(100, 150, 200), adjust(0.0, 2.0, 1.0)),
+        Color::rgb(73, 173, 255).to_packed()
+    );
+    // Without saturation, colours become their Rec. 709 luminance.
+    assert_eq!(
+        adjusted(Color::rgb(255, 0, 0), adjust(0.0, 1.0, 0.0)),
+        Color::rgb(54, 54, 54).to_packed()
+    );
+}
+
+#[test]
+fn effects_filter_only_what_was_drawn_before_them() {
+    let vignette = Shape::Effect(Effect::Vignette {
+        strength: 1.0,
+        radius: 0.0,
+        softness: 0.1,
+    });
+    let mut renderer = cpu_renderer();
+    let frame = renderer.render_frame(&[vignette.clone(), square(Color::WHITE)]);
+    assert_eq!(pixel(&frame, SIZE, 8, 8), Color::WHITE.to_packed());
+
+    // In a group, only the group's own layer.
+    let frame = renderer.render_frame(&[
+        full(Color::WHITE),
+        GroupData::new_shape(vec![square(Color::rgb(255, 0, 0)), vignette]),
+    ]);
+    assert_eq!(pixel(&frame, SIZE, 0, 0), Color::WHITE.to_packed());
+    assert_ne!(pixel(&frame, SIZE, 6, 6), Color::rgb(255, 0, 0).to_packed());
+}
+
+#[test]
+fn effect_nodes_follow_signals() {
+    let radius = Signal::new(0.0f32);
+    let node = PostEffect::new(|| Effect::Blur {
+        radius: radius.get(),
+    });
+    let mut renderer = cpu_renderer();
+    let draw = |renderer: &mut CpuRenderer| {
+        renderer.render_frame(&[square(Color::WHITE), node.to_shape()])
+    };
+    let sharp = draw(&mut renderer);
+    assert_eq!(sharp, renderer.render_frame(&[square(Color::WHITE)]));
+    radius.update(|x| *x = 2.0);
+    assert_ne!(draw(&mut renderer), sharp);
+}
+
+#[test]
+fn the_gpu_applies_effects_like_the_cpu() {
+    let Some(mut gpu) = gpu_renderer(SIZE, SIZE) else {
+        return;
+    };
+    gpu.set_canvas(Canvas::new(Color::BLACK));
+    let mut cpu = cpu_renderer();
+    let effects = [
+        Effect::Blur { radius: 1.5 },
+        Effect::Bloom {
+            threshold: 0.5,
+            radius: 2.0,
+            intensity: 0.8,
+        },
+        Effect::Vignette {
+            strength: 0.8,
+            radius: 0.4,
+            softness: 0.6,
+        },
+        Effect::FilmGrain {
+            amount: 0.2,
+            seed: 7,
+        },
+        Effect::ChromaticAberration { offset: 1.5 },
+        Effect::ColourAdjust {
+            brightness: 0.1,
+            contrast: 1.3,
+            saturation: 0.5,
+        },
+    ];
+    let scene = [
+        full(Color::rgb(40, 60, 90)),
+        square(Color::WHITE),
+        CircleData::new_shape((4.0, 11.0), 3.0, Color::rgb(250, 120, 30)),
+    ];
+    for effect in effects {
+        let shapes = with_effect(&scene, effect);
+        assert_frames_close(
+            &gpu.render_frame(&shapes),
+            &cpu.render_frame(&shapes),
+            &format!("{effect:?}"),
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Compares CPU renders with the PNGs in `tests/golden`.
+//! Run with `UPDATE_GOLDEN=1` to write them again after a deliberate change, then look at them.
+
+use std::path::PathBuf;
+
+use image::{Rgba, RgbaImage};
+use video_generator_lib::{
+    color::Color,
+    renderer::{Antialiasing, Canvas, CpuRenderer, Renderer},
+    shapes::*,
+};
+
+const SIZE: u32 = 64;
+
+fn font() -> Font {
+    Font::open(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fonts/DejaVuSans.ttf"
+    ))
+    .unwrap()
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(format!("tests/golden/{name}.png"))
+}
+
+/// Checks `frame` against the golden image `name`, or writes it when `UPDATE_GOLDEN` is set.
+fn assert_golden(name: &str, frame: Vec<u8>) {
+    let path = golden_path(name);
+    let actual = RgbaImage::from_raw(SIZE, SIZE, frame).unwrap();
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        actual.save(&path).unwrap();
+        return;
+    }
+    let expected = image::open(&path)
+        .unwrap_or_else(|error| {
+            panic!(
+                "couldn't open {}: {error}. Run with UPDATE_GOLDEN=1 to write it",
+                path.display()
+            )
+        })
+        .into_rgba8();
+    let different: Vec<_> = actual
+        .enumerate_pixels()
+        .filter(|(x, y, pixel)| expected.get_pixel(*x, *y) != *pixel)
+        .collect();
+    if let Some((x, y, pixel)) = different.first() {
+        panic!(
+            "{name}: {} pixels differ from {}, the first ({x}, {y}) being {:?} instead of {:?}",
+            different.len(),
+            path.display(),
+            pixel.0,
+            expected.get_pixel(*x, *y).0
+        );
+    }
+}
+
+/// Overlapping shapes of most kinds, with strokes, gradients and a blend mode.
+fn shapes() -> Vec<Shape> {
+    let sky = Fill::linear(
+        (0.0, 0.0),
+        (0.0, SIZE as f32),
+        &[
+            (0.0, Color::rgb(30, 20, 80)),
+            (1.0, Color::rgb(250, 170, 90)),
+        ],
+    );
+    let star: Vec<_> = (0..10)
+        .map(|i| {
+            let angle = i as f32 * std::f32::consts::PI / 5.0;
+            let radius = if i % 2 == 0 { 10.0 } else { 4.5 };
+            (46.0 + radius * angle.sin(), 44.0 - radius * angle.cos())
+        })
+        .collect();
+    vec![
+        RectangleData::new_shape((0.0, 0.0), (SIZE as f32, SIZE as f32), sky),
+        Shape::Circle(
+            CircleData::new((18.3, 16.7), 10.2, Color::rgba(80, 200, 120, 200))
+                .with_stroke(Some(Stroke::new(2.5, Color::rgb(10, 40, 20)))),
+        ),
+        Shape::Rectangle(
+            RectangleData::new((22.0, 24.0), (24.0, 9.0), Color::rgb(240, 240, 255))
+                .with_rotation(0.4)
+                .with_blend_mode(BlendMode::Multiply),
+        ),
+        EllipseData::new_shape((16.0, 46.0), (11.5, 8.25), Color::rgb(60, 120, 230)),
+        RoundedRectangleData::new_shape(
+            (40.5, 4.25),
+            (20.0, 16.0),
+            [6.0, 1.5, 4.0, 3.0],
+            Color::rgba(30, 30, 60, 140),
+        ),
+        PolygonData::new_shape(star, Color::rgb(255, 220, 40)),
+        Shape::Polyline(
+            PolylineData::new(
+                vec![(4.0, 60.0), (20.0, 30.5), (34.0, 52.0), (60.0, 28.0)],
+                2.75,
+                Color::rgba(255, 255, 255, 220),
+            )
+            .with_cap(LineCap::Round)
+            .with_join(LineJoin::Round),
+        ),
+        TextData::new_shape("Aa", font(), 16.0, (26.0, 36.0), Color::WHITE),
+    ]
+}
+
+#[test]
+fn shapes_match_their_golden_images() {
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    for (name, antialiasing) in [
+        ("shapes-hard", Antialiasing::None),
+        ("shapes-analytic", Antialiasing::Analytic),
+        ("shapes-supersampled", Antialiasing::Supersample(4)),
+    ] {
+        renderer.set_antialiasing(antialiasing);
+        assert_golden(name, renderer.render_frame(&shapes()));
+    }
+}
+
+#[test]
+fn layers_match_their_golden_image() {
+    let checkerboard = ImageSource::from_rgba(RgbaImage::from_fn(8, 8, |x, y| {
+        let colour = if (x / 2 + y / 2) % 2 == 0 {
+            Color::rgb(240, 200, 40)
+        } else {
+            Color::rgba(20, 60, 200, 160)
+        };
+        Rgba(colour.to_packed().to_le_bytes())
+    }));
+    let shapes = [
+        RectangleData::new_shape(
+            (0.0, 0.0),
+            (SIZE as f32, SIZE as f32),
+            Color::rgb(40, 40, 48),
+        ),
+        Shape::Image(ImageData::new(checkerboard, (4.0, 4.0)).with_size((24.0, 24.0))),
+        Shape::Group(
+            GroupData::new(vec![
+                CircleData::new_shape((40.0, 40.0), 16.0, Color::rgb(0, 200, 255)),
+                Shape::Rectangle(
+                    RectangleData::new((20.0, 34.0), (40.0, 10.0), Color::rgb(255, 90, 90))
+                        .with_blend_mode(BlendMode::Screen),
+                ),
+            ])
+            .with_opacity(0.75)
+            .with_mask(Some(Mask::new(
+                CircleData::new_shape((36.0, 36.0), 14.0, Color::WHITE),
+                MaskMode::Alpha,
+            ))),
+        ),
+        Shape::Effect(Effect::Blur { radius: 0.8 }),
+        Shape::Effect(Effect::Vignette {
+            strength: 0.6,
+            radius: 0.5,
+            softness: 0.7,
+        }),
+    ];
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    assert_golden("layers", renderer.render_frame(&shapes));
+}
+
+#[test]
+fn trails_match_their_golden_image() {
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    renderer.set_canvas(Canvas::persist(Color::rgb(10, 10, 30), 0.25));
+    let mut frame = vec![];
+    for i in 0..8 {
+        let ball = [CircleData::new_shape(
+            (8.0 + i as f32 * 6.3, 32.0 - i as f32 * 2.0),
+            5.0,
+            Color::rgb(255, 180, 60),
+        )];
+        frame = renderer.render_frame(&ball);
+    }
+    assert_golden("trail", frame);
+}
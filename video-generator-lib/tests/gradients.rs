@@ -0,0 +1,230 @@
+mod common;
+
+use std::f32::consts::FRAC_PI_2;
+
+use common::{gpu_renderer, pixel};
+use video_generator_lib::{
+    color::Color,
+    node::{conic_gradient, linear_gradient, radial_gradient, Rectangle},
+    renderer::{CpuRenderer, Renderer},
+    shapes::*,
+    signal::Signal,
+};
+
+const SIZE: u32 = 16;
+const RED: Color = Color::rgb(255, 0, 0);
+const GREEN: Color = Color::rgb(0, 255, 0);
+const BLUE: Color = Color::rgb(0, 0, 255);
+
+fn render(renderer: &mut dyn Renderer, fill: Fill) -> Vec<u8> {
+    renderer.render_frame(&[RectangleData::new_shape(
+        (0.0, 0.0),
+        (SIZE as f32, SIZE as f32),
+        fill,
+    )])
+}
+
+/// The fill a rectangle node has at the time.
+fn fill(node: &Rectangle) -> Fill {
+    match node.to_shape() {
+        Shape::Rectangle(x) => x.fill,
+        _ => unreachable!(),
+    }
+}
+
+fn offsets_and_colours(fill: &Fill) -> Vec<(f32, u32)> {
+    fill.stops().iter().map(|x| (x.offset, x.colour)).collect()
+}
+
+#[test]
+fn stops_are_sorted_and_clamped() {
+    let fill = Fill::linear(
+        (0.0, 0.0),
+        (1.0, 0.0),
+        &[(0.75, BLUE), (-0.5, RED), (0.25, GREEN), (1.5, RED)],
+    );
+    assert_eq!(
+        offsets_and_colours(&fill),
+        [
+            (0.0, RED.to_packed()),
+            (0.25, GREEN.to_packed()),
+            (0.75, BLUE.to_packed()),
+            (1.0, RED.to_packed()),
+        ]
+    );
+    // Stops at the same offset keep the order they were given in.
+    let fill = Fill::radial((0.0, 0.0), 1.0, &[(0.5, GREEN), (0.0, RED), (0.5, BLUE)]);
+    assert_eq!(
+        offsets_and_colours(&fill),
+        [
+            (0.0, RED.to_packed()),
+            (0.5, GREEN.to_packed()),
+            (0.5, BLUE.to_packed()),
+        ]
+    );
+    let fill = Fill::conic((0.0, 0.0), 0.0, &[(0.5, BLUE), (0.5, GREEN)]);
+    assert_eq!(
+        offsets_and_colours(&fill),
+        [(0.5, BLUE.to_packed()), (0.5, GREEN.to_packed())]
+    );
+    assert!(Fill::from(RED).stops().is_empty());
+}
+
+fn check_gradients(renderer: &mut dyn Renderer) {
+    // Past their ends, gradients pad with their first and last colours.
+    let frame = render(
+        renderer,
+        Fill::linear((4.0, 0.0), (12.0, 0.0), &[(0.0, RED), (1.0, BLUE)]),
+    );
+    for x in 0..4 {
+        assert_eq!(pixel(&frame, SIZE, x, 3), RED.to_packed(), "pixel {x}");
+    }
+    for x in 12..SIZE {
+        assert_eq!(pixel(&frame, SIZE, x, 3), BLUE.to_packed(), "pixel {x}");
+    }
+    // Lines at right angles to the gradient are all one colour.
+    for y in 0..SIZE {
+        assert_eq!(pixel(&frame, SIZE, 7, y), pixel(&frame, SIZE, 7, 0));
+    }
+
+    // And the same before the first stop and after the last.
+    let frame = render(
+        renderer,
+        Fill::linear((0.0, 0.0), (16.0, 0.0), &[(0.25, RED), (0.75, BLUE)]),
+    );
+    assert_eq!(pixel(&frame, SIZE, 3, 0), RED.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 12, 0), BLUE.to_packed());
+
+    // Two stops at one offset change colour sharply there, in the order given.
+    let hard = |first, second| {
+        Fill::linear(
+            (0.0, 0.0),
+            (16.0, 0.0),
+            &[(0.0, RED), (0.5, first), (0.5, second), (1.0, BLUE)],
+        )
+    };
+    let green = |pixel: u32| pixel.to_le_bytes()[1];
+    let frame = render(renderer, hard(GREEN, RED));
+    assert!(green(pixel(&frame, SIZE, 7, 0)) > 200);
+    assert_eq!(green(pixel(&frame, SIZE, 8, 0)), 0);
+    let frame = render(renderer, hard(RED, GREEN));
+    assert_eq!(green(pixel(&frame, SIZE, 7, 0)), 0);
+    assert!(green(pixel(&frame, SIZE, 8, 0)) > 200);
+
+    // Offsets outside 0.0 to 1.0 are clamped to the ends.
+    let clamped = render(
+        renderer,
+        Fill::linear((0.0, 0.0), (16.0, 0.0), &[(-1.0, RED), (2.0, BLUE)]),
+    );
+    let ends = render(
+        renderer,
+        Fill::linear((0.0, 0.0), (16.0, 0.0), &[(0.0, RED), (1.0, BLUE)]),
+    );
+    assert_eq!(clamped, ends);
+
+    // Radial gradients start at their centre and pad beyond their radius.
+    let frame = render(
+        renderer,
+        Fill::radial((8.0, 8.0), 4.0, &[(0.0, RED), (0.5, RED), (0.5, BLUE)]),
+    );
+    assert_eq!(pixel(&frame, SIZE, 8, 8), RED.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 9, 7), RED.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 11, 8), BLUE.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 0, 0), BLUE.to_packed());
+
+    // Conic gradients turn clockwise from their angle, so the first half is below the centre
+    // when they start from the right, and on the left when they start from below.
+    let half = |angle| Fill::conic((8.0, 8.0), angle, &[(0.5, RED), (0.5, BLUE)]);
+    let frame = render(renderer, half(0.0));
+    assert_eq!(pixel(&frame, SIZE, 12, 10), RED.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 4, 10), RED.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 12, 5), BLUE.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 4, 5), BLUE.to_packed());
+    let frame = render(renderer, half(FRAC_PI_2));
+    assert_eq!(pixel(&frame, SIZE, 4, 10), RED.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 4, 5), RED.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 12, 10), BLUE.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 12, 5), BLUE.to_packed());
+
+    // One stop is a solid colour, and none draws nothing.
+    let frame = render(
+        renderer,
+        Fill::linear((0.0, 0.0), (16.0, 0.0), &[(0.3, GREEN)]),
+    );
+    assert!(frame
+        .chunks(4)
+        .all(|x| x == GREEN.to_packed().to_le_bytes()));
+    let frame = render(
+        renderer,
+        Fill::linear((0.0, 0.0), (16.0, 0.0), &[] as &[(f32, Color)]),
+    );
+    assert!(frame.iter().all(|&x| x == 0));
+}
+
+#[test]
+fn the_cpu_draws_gradients_between_their_stops() {
+    check_gradients(&mut CpuRenderer::new(SIZE, SIZE));
+}
+
+#[test]
+fn the_gpu_draws_gradients_between_their_stops() {
+    let Some(mut renderer) = gpu_renderer(SIZE, SIZE) else {
+        return;
+    };
+    check_gradients(&mut renderer);
+}
+
+#[test]
+fn gradient_ends_follow_signals() {
+    let end = Signal::new(16.0f32);
+    let mut node = Rectangle::new(|| 0.0, || 0.0, || 16.0, || 16.0, || RED);
+    node.set_fill(linear_gradient(
+        || 0.0,
+        || 0.0,
+        || end.get(),
+        || 0.0,
+        &[(1.0, BLUE), (0.0, RED)],
+    ));
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    let frame = renderer.render_frame(&[node.to_shape()]);
+    assert_ne!(pixel(&frame, SIZE, 8, 0), BLUE.to_packed());
+
+    end.update(|x| *x = 8.0);
+    let frame = renderer.render_frame(&[node.to_shape()]);
+    assert_eq!(pixel(&frame, SIZE, 8, 0), BLUE.to_packed());
+    assert_eq!(
+        fill(&node),
+        Fill::linear((0.0, 0.0), (8.0, 0.0), &[(0.0, RED), (1.0, BLUE)])
+    );
+}
+
+#[test]
+fn radial_and_conic_gradients_follow_signals() {
+    let centre = Signal::new((8.0f32, 8.0f32));
+    let turn = Signal::new(0.0f32);
+    let stops = [(0.0, RED), (0.5, GREEN), (1.0, BLUE)];
+    let mut radial = Rectangle::new(|| 0.0, || 0.0, || 16.0, || 16.0, || RED);
+    radial.set_fill(radial_gradient(
+        || centre.get().0,
+        || centre.get().1,
+        || 4.0 + turn.get(),
+        &stops,
+    ));
+    let mut conic = Rectangle::new(|| 0.0, || 0.0, || 16.0, || 16.0, || RED);
+    conic.set_fill(conic_gradient(
+        || centre.get().0,
+        || centre.get().1,
+        || turn.get(),
+        &stops,
+    ));
+    assert_eq!(fill(&radial), Fill::radial((8.0, 8.0), 4.0, &stops));
+    assert_eq!(fill(&conic), Fill::conic((8.0, 8.0), 0.0, &stops));
+
+    centre.update(|x| *x = (4.0, 12.0));
+    turn.update(|x| *x = FRAC_PI_2);
+    assert_eq!(
+        fill(&radial),
+        Fill::radial((4.0, 12.0), 4.0 + FRAC_PI_2, &stops)
+    );
+    assert_eq!(fill(&conic), Fill::conic((4.0, 12.0), FRAC_PI_2, &stops));
+}
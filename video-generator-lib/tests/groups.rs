@@ -0,0 +1,141 @@
+mod common;
+
+use common::pixel;
+use video_generator_lib::{
+    color::Color,
+    renderer::{Antialiasing, CpuRenderer, Renderer},
+    shapes::*,
+};
+
+const SIZE: u32 = 16;
+const RED: Color = Color::rgb(255, 0, 0);
+const BLUE: Color = Color::rgb(0, 0, 255);
+
+fn records(shapes: &[Shape]) -> FrameRecords {
+    FrameRecords::new(shapes, Antialiasing::None, SIZE, SIZE)
+}
+
+fn gradient() -> Fill {
+    Fill::linear((0.0, 0.0), (SIZE as f32, 0.0), &[(0.0, RED), (1.0, BLUE)])
+}
+
+fn full(fill: impl Into<Fill>) -> Shape {
+    RectangleData::new_shape((0.0, 0.0), (SIZE as f32, SIZE as f32), fill)
+}
+
+#[test]
+fn groups_which_cant_be_seen_leave_no_records() {
+    let off_canvas = RectangleData::new_shape((-10.0, -10.0), (4.0, 4.0), RED);
+    // On the canvas, but drawing nothing.
+    let invisible = Shape::Group(GroupData::new(vec![full(Color::WHITE)]).with_opacity(0.0));
+    let polyline = PolylineData::new_shape(vec![(2.0, 2.0), (12.0, 12.0)], 2.0, gradient());
+    let hidden = [
+        // Children drawn but masked away by a mask which draws nothing.
+        Shape::Group(
+            GroupData::new(vec![polyline.clone(), full(gradient())])
+                .with_mask(Some(Mask::new(invisible.clone(), MaskMode::Alpha))),
+        ),
+        // The same, a level down.
+        GroupData::new_shape(vec![Shape::Group(
+            GroupData::new(vec![polyline.clone()])
+                .with_mask(Some(Mask::new(invisible.clone(), MaskMode::Luminance))),
+        )]),
+        GroupData::new_shape(vec![
+            off_canvas,
+            invisible.clone(),
+            GroupData::new_shape(vec![]),
+        ]),
+        Shape::Group(GroupData::new(vec![polyline]).with_opacity(0.0)),
+    ];
+    for shape in hidden {
+        let records = records(std::slice::from_ref(&shape));
+        assert!(records.shapes.is_empty(), "{:?}", records.shapes);
+        assert!(records.points.is_empty());
+        assert!(records.gradients.is_empty());
+        assert!(records.stops.is_empty());
+    }
+
+    // Shapes after a culled group still find their own gradients.
+    let shapes = [
+        Shape::Group(
+            GroupData::new(vec![full(gradient())])
+                .with_mask(Some(Mask::new(invisible, MaskMode::Alpha))),
+        ),
+        full(gradient()),
+    ];
+    let frame = CpuRenderer::new(SIZE, SIZE).render_frame(&shapes);
+    let expected = CpuRenderer::new(SIZE, SIZE).render_frame(&shapes[1..]);
+    assert_eq!(frame, expected);
+}
+
+#[test]
+fn groups_are_clipped_to_their_children_and_mask() {
+    let group = Shape::Group(
+        GroupData::new(vec![
+            RectangleData::new_shape((2.0, 2.0), (4.0, 4.0), RED),
+            GroupData::new_shape(vec![RectangleData::new_shape(
+                (10.0, 8.0),
+                (20.0, 2.0),
+                RED,
+            )]),
+        ])
+        .with_mask(Some(Mask::new(
+            RectangleData::new_shape((4.0, 0.0), (12.0, 9.0), Color::WHITE),
+            MaskMode::Alpha,
+        ))),
+    );
+    let records = records(&[group]);
+    let end = records.shapes.last().unwrap();
+    assert_eq!(end.kind, ShapeRecord::GROUP_END);
+    // The children cover (2, 2) to (16, 10), and the mask (4, 0) to (16, 9).
+    assert_eq!(end.clip, [4, 2, 12, 7]);
+}
+
+#[test]
+fn group_opacity_applies_to_the_layer_as_a_whole() {
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    // Overlapping children don't show through each other.
+    let frame = renderer.render_frame(&[Shape::Group(
+        GroupData::new(vec![
+            RectangleData::new_shape((0.0, 0.0), (8.0, 8.0), BLUE),
+            RectangleData::new_shape((4.0, 4.0), (8.0, 8.0), RED),
+        ])
+        .with_opacity(0.5),
+    )]);
+    assert_eq!(pixel(&frame, SIZE, 2, 2), 0x80FF0000);
+    assert_eq!(pixel(&frame, SIZE, 6, 6), 0x800000FF);
+    assert_eq!(pixel(&frame, SIZE, 10, 10), 0x800000FF);
+    assert_eq!(pixel(&frame, SIZE, 14, 14), 0);
+}
+
+#[test]
+fn masks_keep_what_they_cover() {
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    let masked = |mask: Vec<Shape>, mode| {
+        Shape::Group(
+            GroupData::new(vec![full(RED)])
+                .with_mask(Some(Mask::new(GroupData::new_shape(mask), mode))),
+        )
+    };
+
+    let alpha = [
+        RectangleData::new_shape((0.0, 0.0), (4.0, SIZE as f32), Color::BLACK),
+        RectangleData::new_shape((4.0, 0.0), (4.0, SIZE as f32), BLUE.with_alpha(0.5)),
+    ];
+    let frame = renderer.render_frame(&[masked(alpha.to_vec(), MaskMode::Alpha)]);
+    assert_eq!(pixel(&frame, SIZE, 2, 2), RED.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 6, 2), 0x800000FF);
+    assert_eq!(pixel(&frame, SIZE, 10, 2), 0);
+
+    // Luminance masks go by how light the mask is as well as how opaque.
+    let luminance = [
+        RectangleData::new_shape((0.0, 0.0), (4.0, SIZE as f32), Color::WHITE),
+        RectangleData::new_shape((4.0, 0.0), (4.0, SIZE as f32), Color::BLACK),
+        RectangleData::new_shape((8.0, 0.0), (4.0, SIZE as f32), Color::WHITE.with_alpha(0.5)),
+    ];
+    let frame = renderer.render_frame(&[masked(luminance.to_vec(), MaskMode::Luminance)]);
+    assert_eq!(pixel(&frame, SIZE, 2, 2), RED.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 6, 2), 0);
+    assert_eq!(pixel(&frame, SIZE, 10, 2), 0x800000FF);
+    assert_eq!(pixel(&frame, SIZE, 14, 2), 0);
+}
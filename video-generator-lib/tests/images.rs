@@ -0,0 +1,67 @@
+mod common;
+
+use common::{gpu_renderer, pixel};
+use image::{Rgba, RgbaImage};
+use video_generator_lib::{
+    color::Color,
+    renderer::{CpuRenderer, Renderer},
+    shapes::*,
+};
+
+const SIZE: u32 = 16;
+const RED: Color = Color::rgb(255, 0, 0);
+const BLUE: Color = Color::rgb(0, 0, 255);
+
+/// Red on the left half and blue on the right.
+fn halves(width: u32, height: u32) -> ImageSource {
+    ImageSource::from_rgba(RgbaImage::from_fn(width, height, |x, _| {
+        let colour = if x < width / 2 { RED } else { BLUE };
+        Rgba(colour.to_packed().to_le_bytes())
+    }))
+}
+
+#[test]
+fn images_are_drawn_at_their_size_and_region() {
+    let source = halves(4, 4);
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    let frame = renderer.render_frame(&[
+        ImageData::new_shape(source.clone(), (0.0, 0.0)),
+        Shape::Image(
+            ImageData::new(source, (8.0, 8.0))
+                .with_region((2, 0, 2, 4))
+                .with_size((8.0, 8.0))
+                .with_filter(ImageFilter::Nearest),
+        ),
+    ]);
+    for (x, y, expected) in [
+        (1, 1, RED.to_packed()),
+        (2, 1, BLUE.to_packed()),
+        (4, 1, 0),
+        (8, 8, BLUE.to_packed()),
+        (15, 15, BLUE.to_packed()),
+    ] {
+        assert_eq!(pixel(&frame, SIZE, x, y), expected, "pixel ({x}, {y})");
+    }
+}
+
+#[test]
+fn images_too_big_for_a_texture_are_scaled_down() {
+    let Some(mut renderer) = gpu_renderer(SIZE, SIZE) else {
+        return;
+    };
+    let limit = renderer
+        .gpu_instance()
+        .device
+        .limits()
+        .max_texture_dimension_2d;
+    let source = halves(limit * 2, 2);
+    let shapes = [Shape::Image(
+        ImageData::new(source, (0.0, 0.0))
+            .with_size((SIZE as f32, SIZE as f32))
+            .with_filter(ImageFilter::Nearest),
+    )];
+    let frame = renderer.render_frame(&shapes);
+    assert_eq!(frame, CpuRenderer::new(SIZE, SIZE).render_frame(&shapes));
+    assert_eq!(pixel(&frame, SIZE, 0, 0), RED.to_packed());
+    assert_eq!(pixel(&frame, SIZE, SIZE - 1, SIZE - 1), BLUE.to_packed());
+}
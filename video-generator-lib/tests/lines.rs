@@ -0,0 +1,144 @@
+mod common;
+
+use common::{gpu_renderer, COLOUR};
+use video_generator_lib::{
+    node::Line,
+    renderer::{CpuRenderer, Renderer},
+    shapes::*,
+    signal::Signal,
+};
+
+const SIZE: u32 = 16;
+
+/// Checks that hard-edged `line` fills exactly the pixels whose centres `inside` says it does.
+fn assert_fills(
+    renderer: &mut dyn Renderer,
+    line: PolylineData,
+    inside: impl Fn(f32, f32) -> bool,
+) {
+    let what = format!("{:?} {:?}", line.cap, line.join);
+    common::assert_fills(renderer, &Shape::Polyline(line), inside, &what);
+}
+
+/// How far along and how far from the segment from `a` to `b` a point is.
+fn along_and_across(a: (f32, f32), b: (f32, f32), (x, y): (f32, f32)) -> (f32, f32) {
+    let length = (b.0 - a.0).hypot(b.1 - a.1);
+    let direction = ((b.0 - a.0) / length, (b.1 - a.1) / length);
+    let (dx, dy) = (x - a.0, y - a.1);
+    (
+        dx * direction.0 + dy * direction.1,
+        (dx * direction.1 - dy * direction.0).abs(),
+    )
+}
+
+/// Whether a point is inside a segment of `width` with butt ends, with its ends pushed out by `extend`.
+fn in_segment(a: (f32, f32), b: (f32, f32), width: f32, extend: f32, point: (f32, f32)) -> bool {
+    let length = (b.0 - a.0).hypot(b.1 - a.1);
+    let (along, across) = along_and_across(a, b, point);
+    (-extend..=length + extend).contains(&along) && across <= width / 2.0
+}
+
+fn in_triangle([a, b, c]: [(f32, f32); 3], (x, y): (f32, f32)) -> bool {
+    let side = |p: (f32, f32), q: (f32, f32)| (q.0 - p.0) * (y - p.1) - (q.1 - p.1) * (x - p.0);
+    let sides = [side(a, b), side(b, c), side(c, a)];
+    sides.iter().all(|&x| x >= 0.0) || sides.iter().all(|&x| x <= 0.0)
+}
+
+fn check_caps(renderer: &mut dyn Renderer) {
+    let (start, end) = ((4.0, 8.0), (12.0, 8.0));
+    let line = PolylineData::new(vec![start, end], 4.0, COLOUR);
+    assert_fills(renderer, line.clone(), |x, y| {
+        in_segment(start, end, 4.0, 0.0, (x, y))
+    });
+    // Square caps reach half the width past the ends.
+    assert_fills(renderer, line.clone().with_cap(LineCap::Square), |x, y| {
+        in_segment(start, end, 4.0, 2.0, (x, y))
+    });
+    // Round caps are half circles around them.
+    assert_fills(renderer, line.with_cap(LineCap::Round), |x, y| {
+        in_segment(start, end, 4.0, 0.0, (x, y))
+            || (x - start.0).hypot(y - start.1) <= 2.0
+            || (x - end.0).hypot(y - end.1) <= 2.0
+    });
+
+    // A diagonal line keeps its width across it, not along the axes.
+    let (start, end) = ((3.0, 3.0), (13.0, 11.0));
+    let diagonal = PolylineData::new(vec![start, end], 3.0, COLOUR).with_cap(LineCap::Square);
+    assert_fills(renderer, diagonal, |x, y| {
+        in_segment(start, end, 3.0, 1.5, (x, y))
+    });
+}
+
+fn check_joins(renderer: &mut dyn Renderer) {
+    // A right angle, turning down at (11.25, 4.1), whose outer corner is at (13.25, 2.1).
+    // The offsets keep every edge off the pixel centres.
+    let (a, corner, b) = ((3.0, 4.1), (11.25, 4.1), (11.25, 12.0));
+    let elbow = PolylineData::new(vec![a, corner, b], 4.0, COLOUR);
+    let arms =
+        |x, y| in_segment(a, corner, 4.0, 0.0, (x, y)) || in_segment(corner, b, 4.0, 0.0, (x, y));
+    let bevel = [corner, (11.25, 2.1), (13.25, 4.1)];
+
+    assert_fills(renderer, elbow.clone(), |x, y| {
+        arms(x, y) || (11.25..=13.25).contains(&x) && (2.1..=4.1).contains(&y)
+    });
+    assert_fills(
+        renderer,
+        elbow.clone().with_join(LineJoin::Bevel),
+        |x, y| arms(x, y) || in_triangle(bevel, (x, y)),
+    );
+    assert_fills(
+        renderer,
+        elbow.clone().with_join(LineJoin::Round),
+        |x, y| arms(x, y) || (x - corner.0).hypot(y - corner.1) <= 2.0,
+    );
+    // The miter of a right angle is √2 times the width, so a lower limit bevels it instead.
+    assert_fills(renderer, elbow.with_miter_limit(1.4), |x, y| {
+        arms(x, y) || in_triangle(bevel, (x, y))
+    });
+
+    // Closed polylines join their last point back to the first instead of capping both.
+    let square = [(4.0, 4.1), (12.0, 4.1), (12.0, 12.1), (4.0, 12.1)];
+    let closed = PolylineData::new(square.to_vec(), 2.0, COLOUR)
+        .with_cap(LineCap::Round)
+        .with_closed(true);
+    assert_fills(renderer, closed, |x, y| {
+        let inner = (5.0..=11.0).contains(&x) && (5.1..=11.1).contains(&y);
+        let outer = (3.0..=13.0).contains(&x) && (3.1..=13.1).contains(&y);
+        outer && !inner
+    });
+}
+
+#[test]
+fn the_cpu_draws_caps_and_joins() {
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    check_caps(&mut renderer);
+    check_joins(&mut renderer);
+}
+
+#[test]
+fn the_gpu_draws_caps_and_joins() {
+    let Some(mut renderer) = gpu_renderer(SIZE, SIZE) else {
+        return;
+    };
+    check_caps(&mut renderer);
+    check_joins(&mut renderer);
+}
+
+#[test]
+fn line_nodes_follow_their_end_points() {
+    let ball = Signal::new((4.0f32, 6.0f32));
+    let mut line = Line::new(|| 1.0, || 2.0, || 0.0, || 0.0, || 3.0, || COLOUR);
+    line.set_end_x(|| ball.get().0)
+        .set_end_y(|| ball.get().1)
+        .set_cap(LineCap::Round);
+    let polyline = |line: &Line| match line.to_shape() {
+        Shape::Polyline(x) => x,
+        _ => unreachable!(),
+    };
+    assert_eq!(polyline(&line).points, [(1.0, 2.0), (4.0, 6.0)]);
+    assert_eq!(polyline(&line).cap, LineCap::Round);
+    assert_eq!(polyline(&line).width, 3.0);
+
+    ball.update(|x| *x = (10.0, 12.0));
+    assert_eq!(polyline(&line).points, [(1.0, 2.0), (10.0, 12.0)]);
+}
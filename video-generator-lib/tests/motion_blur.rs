@@ -0,0 +1,98 @@
+mod common;
+
+use common::{gpu_renderer, pixel};
+use video_generator_lib::{
+    color::Color,
+    config::{MotionBlur, RenderConfig},
+    renderer::{CpuRenderer, Renderer},
+    shapes::*,
+};
+
+const SIZE: u32 = 16;
+
+fn config(motion_blur: Option<MotionBlur>) -> RenderConfig {
+    RenderConfig::default().with_motion_blur(motion_blur)
+}
+
+fn times(config: &RenderConfig, index: usize) -> Vec<f32> {
+    config.sub_frame_times(index).collect()
+}
+
+#[test]
+fn sub_frames_follow_the_shutter() {
+    // Without motion blur, each frame is sampled once at its own time.
+    assert_eq!(config(None).sub_frames(), 1);
+    assert_eq!(times(&config(None), 3), [3.0]);
+
+    let half = config(Some(MotionBlur::new(4, 180.0)));
+    assert_eq!(half.sub_frames(), 4);
+    assert_eq!(times(&half, 2), [2.0, 2.125, 2.25, 2.375]);
+
+    // A closed shutter samples the same instant every time.
+    let closed = config(Some(MotionBlur::new(3, 0.0)));
+    assert_eq!(times(&closed, 5), [5.0, 5.0, 5.0]);
+
+    // A fully open one spreads over the whole interval without reaching the next frame.
+    let open = config(Some(MotionBlur::new(4, 360.0)));
+    assert_eq!(times(&open, 1), [1.0, 1.25, 1.5, 1.75]);
+
+    // Angles outside 0 to 360 are clamped, and there's always at least one sample.
+    assert_eq!(
+        times(&config(Some(MotionBlur::new(4, 720.0))), 1),
+        times(&open, 1)
+    );
+    assert_eq!(
+        times(&config(Some(MotionBlur::new(3, -90.0))), 5),
+        times(&closed, 5)
+    );
+    let none = config(Some(MotionBlur::new(0, 180.0)));
+    assert_eq!(none.sub_frames(), 1);
+    assert_eq!(times(&none, 7), [7.0]);
+}
+
+#[test]
+fn frame_times_are_every_sub_frame_in_order() {
+    let open = config(Some(MotionBlur::new(2, 360.0)));
+    let all: Vec<f32> = open.frame_times(3).collect();
+    assert_eq!(all, [0.0, 0.5, 1.0, 1.5, 2.0, 2.5]);
+    assert!(all.windows(2).all(|x| x[0] < x[1]));
+    assert_eq!(
+        config(None).frame_times(3).collect::<Vec<_>>(),
+        [0.0, 1.0, 2.0]
+    );
+    assert_eq!(open.frame_times(0).count(), 0);
+}
+
+/// Averages a white square with nothing, over black, and checks the result is half as bright
+/// in linear light rather than in sRGB.
+fn check_averaging(renderer: &mut dyn Renderer) {
+    let background = RectangleData::new_shape((0.0, 0.0), (SIZE as f32, SIZE as f32), Color::BLACK);
+    let square = RectangleData::new_shape((4.0, 4.0), (8.0, 8.0), Color::WHITE);
+    let frame = renderer.render_sub_frames(&[
+        vec![background.clone(), square.clone()],
+        vec![background.clone()],
+    ]);
+    assert_eq!(
+        pixel(&frame, SIZE, 8, 8),
+        Color::rgb(188, 188, 188).to_packed()
+    );
+    assert_eq!(pixel(&frame, SIZE, 0, 0), Color::BLACK.to_packed());
+
+    // One sub-frame is an ordinary frame.
+    let shapes = vec![background, square];
+    let frame = renderer.render_sub_frames(std::slice::from_ref(&shapes));
+    assert_eq!(frame, renderer.render_frame(&shapes));
+}
+
+#[test]
+fn the_cpu_averages_sub_frames() {
+    check_averaging(&mut CpuRenderer::new(SIZE, SIZE));
+}
+
+#[test]
+fn the_gpu_averages_sub_frames() {
+    let Some(mut renderer) = gpu_renderer(SIZE, SIZE) else {
+        return;
+    };
+    check_averaging(&mut renderer);
+}
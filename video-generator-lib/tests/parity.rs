@@ -0,0 +1,192 @@
+mod common;
+
+use common::gpu_renderer;
+use image::{Rgba, RgbaImage};
+use video_generator_lib::{
+    color::Color,
+    renderer::{Antialiasing, Canvas, CpuRenderer, Renderer},
+    shapes::*,
+};
+
+const SIZE: u32 = 64;
+
+fn font() -> Font {
+    Font::open(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fonts/DejaVuSans.ttf"
+    ))
+    .unwrap()
+}
+
+/// A checkerboard of two colours in 2 by 2 squares.
+fn checkerboard() -> ImageSource {
+    ImageSource::from_rgba(RgbaImage::from_fn(8, 8, |x, y| {
+        let colour = if (x / 2 + y / 2) % 2 == 0 {
+            Color::rgb(240, 200, 40)
+        } else {
+            Color::rgba(20, 60, 200, 160)
+        };
+        Rgba(colour.to_packed().to_le_bytes())
+    }))
+}
+
+/// Every kind of shape, overlapping, with strokes, gradients, blend modes, groups and an effect.
+fn scene() -> Vec<Shape> {
+    let sunset = Fill::linear(
+        (0.0, 0.0),
+        (0.0, SIZE as f32),
+        &[
+            (0.0, Color::rgb(30, 20, 80)),
+            (0.6, Color::rgb(220, 90, 60)),
+            (1.0, Color::rgb(250, 210, 120)),
+        ],
+    );
+    let glow = Fill::radial(
+        (44.0, 18.0),
+        12.0,
+        &[
+            (0.0, Color::rgb(255, 250, 200)),
+            (1.0, Color::rgba(255, 200, 80, 0)),
+        ],
+    );
+    let wheel = Fill::conic(
+        (16.0, 46.0),
+        0.3,
+        &[
+            (0.0, Color::rgb(255, 0, 0)),
+            (0.33, Color::rgb(0, 255, 0)),
+            (0.67, Color::rgb(0, 0, 255)),
+            (1.0, Color::rgb(255, 0, 0)),
+        ],
+    );
+    let star: Vec<_> = (0..10)
+        .map(|i| {
+            let angle = i as f32 * std::f32::consts::PI / 5.0;
+            let radius = if i % 2 == 0 { 10.0 } else { 4.5 };
+            (48.0 + radius * angle.sin(), 46.0 - radius * angle.cos())
+        })
+        .collect();
+    vec![
+        RectangleData::new_shape((0.0, 0.0), (SIZE as f32, SIZE as f32), sunset),
+        CircleData::new_shape((44.0, 18.0), 12.0, glow),
+        Shape::Circle(
+            CircleData::new((16.3, 14.7), 9.2, Color::rgba(80, 200, 120, 200))
+                .with_stroke(Some(Stroke::new(2.5, Color::rgb(10, 40, 20)))),
+        ),
+        Shape::Rectangle(
+            RectangleData::new((20.0, 24.0), (22.0, 9.0), Color::rgb(240, 240, 255))
+                .with_rotation(0.4)
+                .with_blend_mode(BlendMode::Multiply),
+        ),
+        EllipseData::new_shape((16.0, 46.0), (11.5, 8.25), wheel),
+        RoundedRectangleData::new_shape(
+            (33.5, 30.25),
+            (26.0, 30.0),
+            [6.0, 1.5, 10.0, 3.0],
+            Color::rgba(30, 30, 60, 140),
+        ),
+        PolygonData::new_shape(star, Color::rgb(255, 220, 40)),
+        Shape::Polyline(
+            PolylineData::new(
+                vec![(4.0, 60.0), (20.0, 30.5), (34.0, 52.0), (60.0, 8.0)],
+                2.75,
+                Color::rgba(255, 255, 255, 220),
+            )
+            .with_cap(LineCap::Round)
+            .with_join(LineJoin::Round),
+        ),
+        Shape::Path(
+            PathData::parse(
+                "M 6 4 C 18 -2 30 12 24 20 Q 14 26 6 18 Z",
+                Color::rgba(200, 40, 160, 180),
+            )
+            .unwrap()
+            .with_stroke(Some(Stroke::new(1.5, Color::rgb(60, 0, 40)))),
+        ),
+        Shape::Image(
+            ImageData::new(checkerboard(), (50.0, 52.0))
+                .with_size((12.0, 10.0))
+                .with_opacity(0.8),
+        ),
+        TextData::new_shape("Hi!", font(), 14.0, (26.0, 2.0), Color::rgb(255, 255, 255)),
+        Shape::Group(
+            GroupData::new(vec![
+                CircleData::new_shape((30.0, 40.0), 6.0, Color::rgb(0, 200, 255)),
+                Shape::Rectangle(
+                    RectangleData::new((24.0, 38.0), (14.0, 4.0), Color::rgb(255, 255, 255))
+                        .with_blend_mode(BlendMode::Screen),
+                ),
+            ])
+            .with_opacity(0.7)
+            .with_mask(Some(Mask::new(
+                CircleData::new_shape((31.0, 39.0), 5.0, Color::WHITE),
+                MaskMode::Alpha,
+            ))),
+        ),
+        Shape::Effect(Effect::Vignette {
+            strength: 0.5,
+            radius: 0.6,
+            softness: 0.6,
+        }),
+    ]
+}
+
+fn assert_identical(actual: &[u8], expected: &[u8], what: &str) {
+    let different = actual.iter().zip(expected).filter(|(a, b)| a != b).count();
+    if let Some(i) = actual.iter().zip(expected).position(|(a, b)| a != b) {
+        let (x, y) = ((i / 4) as u32 % SIZE, (i / 4) as u32 / SIZE);
+        panic!(
+            "{what}: {different} channels differ, the first being channel {} of pixel ({x}, {y}), \
+             which is {} but {} on the CPU",
+            i % 4,
+            actual[i],
+            expected[i]
+        );
+    }
+}
+
+#[test]
+fn the_gpu_draws_what_the_cpu_does() {
+    let Some(mut gpu) = gpu_renderer(SIZE, SIZE) else {
+        return;
+    };
+    let mut cpu = CpuRenderer::new(SIZE, SIZE);
+    let shapes = scene();
+    for antialiasing in [
+        Antialiasing::None,
+        Antialiasing::Analytic,
+        Antialiasing::Supersample(4),
+    ] {
+        cpu.set_antialiasing(antialiasing);
+        gpu.set_antialiasing(antialiasing);
+        assert_identical(
+            &gpu.render_frame(&shapes),
+            &cpu.render_frame(&shapes),
+            &format!("{antialiasing:?}"),
+        );
+    }
+}
+
+#[test]
+fn persisted_canvases_stay_in_step() {
+    let Some(mut gpu) = gpu_renderer(SIZE, SIZE) else {
+        return;
+    };
+    let mut cpu = CpuRenderer::new(SIZE, SIZE);
+    let canvas = Canvas::persist(Color::rgb(10, 10, 30), 0.2);
+    cpu.set_canvas(canvas);
+    gpu.set_canvas(canvas);
+    // A ball moving across a fading trail.
+    for i in 0..8 {
+        let ball = [CircleData::new_shape(
+            (8.0 + i as f32 * 6.3, 32.0),
+            5.0,
+            Color::rgb(255, 180, 60),
+        )];
+        assert_identical(
+            &gpu.render_frame(&ball),
+            &cpu.render_frame(&ball),
+            &format!("frame {i}"),
+        );
+    }
+}
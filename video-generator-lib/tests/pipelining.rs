@@ -0,0 +1,77 @@
+mod common;
+
+use std::sync::Mutex;
+
+use common::gpu_renderer;
+use video_generator_lib::{
+    color::Color,
+    renderer::{Canvas, GpuRenderer, Renderer},
+    shapes::*,
+};
+
+const SIZE: u32 = 32;
+const FRAMES: usize = 12;
+
+/// A ball crossing the canvas, two sub-frames a frame, so every frame differs from the last.
+fn frames() -> Vec<Vec<Vec<Shape>>> {
+    (0..FRAMES)
+        .map(|i| {
+            (0..2)
+                .map(|j| {
+                    let x = 2.0 + (i * 2 + j) as f32 * 1.2;
+                    vec![CircleData::new_shape(
+                        (x, 10.0 + i as f32),
+                        4.0,
+                        Color::rgb(255, 180, 60),
+                    )]
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Renders [`frames`] through `render_frames` with several frames in flight,
+/// returning them by the index they were saved with.
+fn pipelined(renderer: &mut GpuRenderer) -> Vec<Vec<u8>> {
+    let saved = Mutex::new(vec![None; FRAMES]);
+    renderer.render_frames(&mut frames().into_iter(), &|i, pixels| {
+        let previous = saved.lock().unwrap()[i].replace(pixels);
+        assert!(previous.is_none(), "frame {i} was saved twice");
+    });
+    saved
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .enumerate()
+        .map(|(i, x)| x.unwrap_or_else(|| panic!("frame {i} was never saved")))
+        .collect()
+}
+
+fn check_pipelining(canvas: Canvas) {
+    let (Some(mut pipelined_renderer), Some(mut sequential)) =
+        (gpu_renderer(SIZE, SIZE), gpu_renderer(SIZE, SIZE))
+    else {
+        return;
+    };
+    pipelined_renderer
+        .set_frames_in_flight(4)
+        .set_canvas(canvas);
+    sequential.set_canvas(canvas);
+    let actual = pipelined(&mut pipelined_renderer);
+    for (i, (actual, frame)) in actual.iter().zip(frames()).enumerate() {
+        assert!(
+            *actual == sequential.render_sub_frames(&frame),
+            "frame {i} differs from rendering it on its own"
+        );
+    }
+}
+
+#[test]
+fn pipelined_frames_match_sequential_ones() {
+    check_pipelining(Canvas::transparent());
+}
+
+#[test]
+fn pipelined_frames_persist_in_order() {
+    check_pipelining(Canvas::persist(Color::rgb(10, 10, 30), 0.3));
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use common::{gpu_renderer, pixel, COLOUR};
+use video_generator_lib::{
+    renderer::{Antialiasing, CpuRenderer, Renderer},
+    shapes::*,
+};
+
+const SIZE: u32 = 16;
+
+/// Twice the signed area, positive when the points go clockwise on the screen.
+fn signed_area(points: &[(f32, f32)]) -> f32 {
+    (0..points.len())
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum()
+}
+
+/// Whether a point is inside a polygon, by counting the edges a ray from it crosses.
+fn in_polygon(points: &[(f32, f32)], (x, y): (f32, f32)) -> bool {
+    let mut inside = false;
+    for i in 0..points.len() {
+        let (a, b) = (points[i], points[(i + 1) % points.len()]);
+        if (a.1 > y) != (b.1 > y) && x < a.0 + (y - a.1) / (b.1 - a.1) * (b.0 - a.0) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// An L, which is concave at one corner.
+const L: [(f32, f32); 6] = [
+    (2.0, 2.0),
+    (6.0, 2.0),
+    (6.0, 10.0),
+    (13.0, 10.0),
+    (13.0, 14.0),
+    (2.0, 14.0),
+];
+
+/// A comb with three teeth pointing down, concave between each.
+const COMB: [(f32, f32); 12] = [
+    (1.0, 1.0),
+    (15.0, 1.0),
+    (15.0, 14.0),
+    (12.0, 14.0),
+    (12.0, 5.0),
+    (10.0, 5.0),
+    (10.0, 14.0),
+    (6.0, 14.0),
+    (6.0, 5.0),
+    (4.0, 5.0),
+    (4.0, 14.0),
+    (1.0, 14.0),
+];
+
+/// A five-pointed star, concave at each inner point.
+fn star() -> Vec<(f32, f32)> {
+    (0..10)
+        .map(|i| {
+            let angle = i as f32 * std::f32::consts::PI / 5.0;
+            let radius = if i % 2 == 0 { 7.0 } else { 3.0 };
+            (8.0 + radius * angle.sin(), 8.0 - radius * angle.cos())
+        })
+        .collect()
+}
+
+/// Checks that the triangles of a simple polygon cover it exactly: two fewer than it has sides,
+/// each turning the same way as the polygon and inside it, adding up to its area.
+fn assert_covers(points: &[(f32, f32)]) {
+    let triangles = PolygonData::new(points.to_vec(), COLOUR).triangulate();
+    assert_eq!(triangles.len(), points.len() - 2, "{points:?}");
+    let area = signed_area(points);
+    let mut total = 0.0;
+    for triangle in &triangles {
+        let triangle_area = signed_area(triangle);
+        assert!(
+            triangle_area * area.signum() > 0.0,
+            "{triangle:?} turns the wrong way in {points:?}"
+        );
+        let centroid = (
+            triangle.iter().map(|x| x.0).sum::<f32>() / 3.0,
+            triangle.iter().map(|x| x.1).sum::<f32>() / 3.0,
+        );
+        assert!(
+            in_polygon(points, centroid),
+            "{triangle:?} is outside {points:?}"
+        );
+        total += triangle_area;
+    }
+    assert!((total - area).abs() < 1e-3, "{total} is not {area}");
+}
+
+#[test]
+fn concave_polygons_are_clipped_into_ears() {
+    let square = [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)];
+    for points in [square.to_vec(), L.to_vec(), COMB.to_vec(), star()] {
+        assert_covers(&points);
+        // Either way round.
+        let reversed: Vec<_> = points.iter().rev().copied().collect();
+        assert_covers(&reversed);
+        // From any starting point.
+        let mut rotated = points.clone();
+        rotated.rotate_left(3);
+        assert_covers(&rotated);
+    }
+}
+
+#[test]
+fn degenerate_polygons_are_triangulated_safely() {
+    let triangulate =
+        |points: &[(f32, f32)]| PolygonData::new(points.to_vec(), COLOUR).triangulate();
+    // Too few points, even after dropping repeats and a closing point, make no triangles.
+    assert!(triangulate(&[]).is_empty());
+    assert!(triangulate(&[(1.0, 1.0), (5.0, 5.0)]).is_empty());
+    assert!(triangulate(&[(1.0, 1.0), (5.0, 5.0), (5.0, 5.0), (1.0, 1.0)]).is_empty());
+
+    // Repeated and closing points are dropped.
+    let repeated = [
+        (2.0, 2.0),
+        (6.0, 2.0),
+        (6.0, 2.0),
+        (6.0, 10.0),
+        (13.0, 10.0),
+        (13.0, 14.0),
+        (2.0, 14.0),
+        (2.0, 2.0),
+    ];
+    assert_eq!(triangulate(&repeated), triangulate(&L));
+
+    // Points in the middle of a side still leave the area covered.
+    let midpoints = [
+        (2.0, 2.0),
+        (4.0, 2.0),
+        (6.0, 2.0),
+        (6.0, 10.0),
+        (13.0, 10.0),
+        (13.0, 14.0),
+        (8.0, 14.0),
+        (2.0, 14.0),
+        (2.0, 8.0),
+    ];
+    let total: f32 = triangulate(&midpoints).iter().map(|x| signed_area(x)).sum();
+    assert!((total - signed_area(&L)).abs() < 1e-3);
+
+    // Polygons with no area, and ones which cross themselves, give triangles without hanging.
+    let line = [(1.0, 1.0), (5.0, 5.0), (9.0, 9.0), (3.0, 3.0)];
+    assert_eq!(triangulate(&line).len(), 2);
+    assert!(triangulate(&line).iter().all(|x| signed_area(x) == 0.0));
+    let bow_tie = [(2.0, 2.0), (14.0, 14.0), (14.0, 2.0), (2.0, 14.0)];
+    assert_eq!(triangulate(&bow_tie).len(), 2);
+}
+
+/// Checks that hard-edged polygons fill exactly the pixels whose centres are inside them.
+fn check_fills(renderer: &mut dyn Renderer) {
+    renderer.set_antialiasing(Antialiasing::None);
+    for points in [L.to_vec(), COMB.to_vec()] {
+        let frame = renderer.render_frame(&[PolygonData::new_shape(points.clone(), COLOUR)]);
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let inside = in_polygon(&points, (x as f32 + 0.5, y as f32 + 0.5));
+                let expected = if inside { COLOUR.to_packed() } else { 0 };
+                assert_eq!(pixel(&frame, SIZE, x, y), expected, "pixel ({x}, {y})");
+            }
+        }
+    }
+    // Shapes without area draw nothing.
+    let line = [(1.0, 1.0), (5.0, 5.0), (9.0, 9.0), (3.0, 3.0)];
+    let frame = renderer.render_frame(&[PolygonData::new_shape(line.to_vec(), COLOUR)]);
+    assert!(frame.iter().all(|&x| x == 0));
+}
+
+#[test]
+fn the_cpu_fills_concave_polygons() {
+    check_fills(&mut CpuRenderer::new(SIZE, SIZE));
+}
+
+#[test]
+fn the_gpu_fills_concave_polygons() {
+    let Some(mut renderer) = gpu_renderer(SIZE, SIZE) else {
+        return;
+    };
+    check_fills(&mut renderer);
+}
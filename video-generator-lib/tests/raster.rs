@@ -0,0 +1,124 @@
+mod common;
+
+use common::{assert_frames_close, gpu_renderer, pixel};
+use video_generator_lib::{
+    color::Color,
+    renderer::{Antialiasing, CpuRenderer, GpuRenderer, Renderer},
+    shapes::*,
+};
+
+const SIZE: u32 = 16;
+const BACKDROP: Color = Color::rgb(200, 120, 40);
+const SOURCE: Color = Color::rgb(60, 180, 220);
+
+/// The GPU renderer on the adapter the environment asks for, or `None` if there isn't one
+/// or it can't rasterize shapes.
+fn raster_renderer() -> Option<GpuRenderer> {
+    let renderer = gpu_renderer(SIZE, SIZE)?;
+    if renderer.gpu_instance().raster_pipelines.is_none() {
+        eprintln!("Skipping the GPU: it can't shade each sample, so never rasterizes");
+        return None;
+    }
+    Some(renderer)
+}
+
+fn band(y: f32, mode: BlendMode) -> Shape {
+    Shape::Rectangle(RectangleData::new((2.0, y), (12.0, 2.0), SOURCE).with_blend_mode(mode))
+}
+
+/// Normally blended shapes between bands of every other mode, each over the last,
+/// so the order they're drawn in matters. The circle's hard edge is half a pixel off its centre.
+fn scene() -> Vec<Shape> {
+    let mut shapes = vec![
+        RectangleData::new_shape((0.0, 0.0), (SIZE as f32, SIZE as f32), BACKDROP),
+        CircleData::new_shape((8.0, 8.0), 5.0, Color::rgb(30, 90, 160)),
+    ];
+    let modes = [
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Additive,
+        BlendMode::Darken,
+        BlendMode::Lighten,
+    ];
+    for (i, mode) in modes.into_iter().enumerate() {
+        shapes.push(band(i as f32 * 3.0, mode));
+        shapes.push(RectangleData::new_shape(
+            (i as f32 * 3.0, i as f32 * 3.0 + 1.0),
+            (2.0, 2.0),
+            Color::rgba(250, 250, 250, 128),
+        ));
+    }
+    shapes
+}
+
+#[test]
+fn other_blend_modes_fall_back_to_the_kernels() {
+    let Some(mut gpu) = raster_renderer() else {
+        return;
+    };
+    let mut cpu = CpuRenderer::new(SIZE, SIZE);
+    cpu.set_antialiasing(Antialiasing::None);
+    gpu.set_antialiasing(Antialiasing::None);
+    let shapes = scene();
+    let kernels = gpu.set_rasterized(false).render_frame(&shapes);
+    let rasterized = gpu.set_rasterized(true).render_frame(&shapes);
+    assert_frames_close(&rasterized, &kernels, "rasterized against the kernels");
+    assert_frames_close(
+        &rasterized,
+        &cpu.render_frame(&shapes),
+        "rasterized against the CPU",
+    );
+
+    // The bands really were blended, rather than rasterized as if they were normal.
+    let normal: Vec<_> = shapes
+        .iter()
+        .map(|x| match x {
+            Shape::Rectangle(x) => Shape::Rectangle(x.clone().with_blend_mode(BlendMode::Normal)),
+            x => x.clone(),
+        })
+        .collect();
+    let flattened = gpu.render_frame(&normal);
+    for y in [0, 3, 6, 9, 12] {
+        assert_ne!(
+            pixel(&rasterized, SIZE, 13, y),
+            pixel(&flattened, SIZE, 13, y),
+            "row {y}"
+        );
+    }
+    assert_eq!(pixel(&flattened, SIZE, 13, 0), SOURCE.to_packed());
+}
+
+#[test]
+fn rasterized_shapes_are_multisampled() {
+    let Some(mut gpu) = raster_renderer() else {
+        return;
+    };
+    gpu.set_rasterized(true);
+    let circle = [CircleData::new_shape((8.0, 8.0), 5.5, SOURCE)];
+    gpu.set_antialiasing(Antialiasing::None);
+    let hard = gpu.render_frame(&circle);
+    assert!(hard.chunks(4).all(|x| x[3] == 0 || x[3] == 255));
+
+    for antialiasing in [Antialiasing::Analytic, Antialiasing::Supersample(4)] {
+        gpu.set_antialiasing(antialiasing);
+        let smooth = gpu.render_frame(&circle);
+        assert_eq!(pixel(&smooth, SIZE, 8, 8), SOURCE.to_packed());
+        assert_eq!(pixel(&smooth, SIZE, 0, 0), 0);
+        // Edge pixels are covered by some of the RASTER_SAMPLE_COUNT samples.
+        let partial: Vec<u8> = smooth
+            .chunks(4)
+            .map(|x| x[3])
+            .filter(|x| (1..255).contains(x))
+            .collect();
+        assert!(!partial.is_empty(), "{antialiasing:?} has no smoothed edge");
+        for alpha in partial {
+            assert!(
+                (1..RASTER_SAMPLE_COUNT).any(|x| {
+                    let samples = (x as f32 * 255.0 / RASTER_SAMPLE_COUNT as f32).round();
+                    alpha.abs_diff(samples as u8) <= 1
+                }),
+                "{antialiasing:?}: {alpha} isn't a whole number of samples"
+            );
+        }
+    }
+}
@@ -0,0 +1,152 @@
+mod common;
+
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+use common::{pixel, COLOUR};
+use video_generator_lib::{
+    node::Rectangle,
+    renderer::{Antialiasing, CpuRenderer, Renderer},
+    shapes::*,
+    signal::Signal,
+};
+
+const SIZE: u32 = 16;
+
+fn assert_close(actual: (f32, f32), expected: (f32, f32), what: &str) {
+    assert!(
+        (actual.0 - expected.0).abs() < 1e-4 && (actual.1 - expected.1).abs() < 1e-4,
+        "{what}: {actual:?}, expected {expected:?}"
+    );
+}
+
+/// The pixels a rectangle fills, as a string with one row to a line.
+fn drawn(rectangle: RectangleData) -> String {
+    let frame = CpuRenderer::new(SIZE, SIZE).render_frame(&[Shape::Rectangle(rectangle)]);
+    let mut rows = String::new();
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            rows.push(if pixel(&frame, SIZE, x, y) == 0 {
+                '.'
+            } else {
+                '#'
+            });
+        }
+        rows.push('\n');
+    }
+    rows
+}
+
+#[test]
+fn anchors_sit_at_the_position() {
+    let cases = [
+        (Anchor::TopLeft, (8.0, 8.0)),
+        (Anchor::TopRight, (4.0, 8.0)),
+        (Anchor::BottomLeft, (8.0, 6.0)),
+        (Anchor::BottomRight, (4.0, 6.0)),
+        (Anchor::Centre, (6.0, 7.0)),
+        (Anchor::Custom(0.25, 2.0), (7.0, 4.0)),
+    ];
+    for (anchor, top_left) in cases {
+        let rectangle = RectangleData::new((8.0, 8.0), (4.0, 2.0), COLOUR).with_anchor(anchor);
+        assert_eq!(rectangle.top_left(), top_left, "{anchor:?}");
+        assert_eq!(
+            rectangle.bounding_box(),
+            (top_left.0 as i32, top_left.1 as i32, 4, 2),
+            "{anchor:?}"
+        );
+    }
+}
+
+#[test]
+fn rectangles_turn_clockwise_around_their_anchor() {
+    // A quarter turn about the top-left corner swings the rectangle down, to the left of it.
+    let rectangle = RectangleData::new((8.0, 8.0), (4.0, 2.0), COLOUR).with_rotation(FRAC_PI_2);
+    let expected = [(8.0, 8.0), (8.0, 12.0), (6.0, 12.0), (6.0, 8.0)];
+    for (corner, expected) in rectangle.corners().into_iter().zip(expected) {
+        assert_close(corner, expected, "corner");
+    }
+    assert_eq!(
+        drawn(rectangle),
+        drawn(RectangleData::new((6.0, 8.0), (2.0, 4.0), COLOUR))
+    );
+
+    // About the centre, it stays where it was.
+    let rectangle = RectangleData::new((8.0, 8.0), (8.0, 2.0), COLOUR)
+        .with_anchor(Anchor::Centre)
+        .with_rotation(FRAC_PI_2);
+    assert_close(rectangle.centre(), (8.0, 8.0), "centre");
+    assert_eq!(
+        drawn(rectangle),
+        drawn(RectangleData::new((7.0, 4.0), (2.0, 8.0), COLOUR))
+    );
+
+    // A half turn about the bottom-right corner flips it over that corner.
+    let rectangle = RectangleData::new((8.0, 8.0), (4.0, 2.0), COLOUR)
+        .with_anchor(Anchor::BottomRight)
+        .with_rotation(PI);
+    assert_eq!(
+        drawn(rectangle),
+        drawn(RectangleData::new((8.0, 8.0), (4.0, 2.0), COLOUR))
+    );
+}
+
+#[test]
+fn rotated_boxes_cover_the_turned_corners() {
+    // A square of side 4 on its point reaches 2√2 from its centre.
+    let rectangle = RectangleData::new((8.0, 8.0), (4.0, 4.0), COLOUR)
+        .with_anchor(Anchor::Centre)
+        .with_rotation(FRAC_PI_4);
+    let (x, y, width, height) = rectangle.bounding_box();
+    for (corner_x, corner_y) in rectangle.corners() {
+        assert!(corner_x >= x as f32 && corner_x <= (x + width as i32) as f32);
+        assert!(corner_y >= y as f32 && corner_y <= (y + height as i32) as f32);
+    }
+    assert!(width <= 7 && height <= 7, "{width}×{height}");
+
+    let frame = CpuRenderer::new(SIZE, SIZE).render_frame(&[Shape::Rectangle(rectangle)]);
+    // Pixels are filled when their centres are inside, where |dx| + |dy| ≤ 2√2 from (8, 8).
+    assert_eq!(pixel(&frame, SIZE, 8, 6), COLOUR.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 6, 8), COLOUR.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 8, 5), 0);
+    assert_eq!(pixel(&frame, SIZE, 6, 6), 0);
+}
+
+#[test]
+fn culling_goes_by_the_rotated_rectangle() {
+    let records = |shape: RectangleData| {
+        FrameRecords::new(&[Shape::Rectangle(shape)], Antialiasing::None, SIZE, SIZE).shapes
+    };
+    // Off the top of the canvas until turned down into it.
+    let bar = RectangleData::new((8.0, -6.0), (20.0, 2.0), COLOUR);
+    assert!(records(bar.clone()).is_empty());
+    let turned = bar.with_rotation(FRAC_PI_2);
+    assert_eq!(records(turned.clone()).len(), 1);
+    let frame = CpuRenderer::new(SIZE, SIZE).render_frame(&[Shape::Rectangle(turned)]);
+    assert_eq!(pixel(&frame, SIZE, 7, 5), COLOUR.to_packed());
+
+    // On the canvas until turned off it.
+    let bar = RectangleData::new((-1.0, 8.0), (20.0, 2.0), COLOUR);
+    assert_eq!(records(bar.clone()).len(), 1);
+    assert!(records(bar.with_rotation(PI)).is_empty());
+}
+
+#[test]
+fn rectangle_nodes_animate_rotation_and_anchor() {
+    let angle = Signal::new(0.0f32);
+    let anchor = Signal::new(Anchor::TopLeft);
+    let mut node = Rectangle::new(|| 8.0, || 8.0, || 4.0, || 2.0, || COLOUR);
+    node.set_rotation(&angle).set_anchor(&anchor);
+    let rectangle = |node: &Rectangle| match node.to_shape() {
+        Shape::Rectangle(x) => x,
+        _ => unreachable!(),
+    };
+    assert_eq!(rectangle(&node).rotation, 0.0);
+    assert_eq!(rectangle(&node).anchor, Anchor::TopLeft);
+
+    angle.update(|x| *x = FRAC_PI_2);
+    anchor.update(|x| *x = Anchor::Centre);
+    let turned = rectangle(&node);
+    assert_eq!(turned.rotation, FRAC_PI_2);
+    assert_eq!(turned.anchor, Anchor::Centre);
+    assert_close(turned.centre(), (8.0, 8.0), "centre");
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use common::{assert_fills, gpu_renderer, pixel, COLOUR};
+use video_generator_lib::{
+    renderer::{Antialiasing, CpuRenderer, Renderer},
+    shapes::*,
+};
+
+const SIZE: u32 = 16;
+
+/// Checks that hard-edged `shape` fills exactly the pixels whose centres `inside` says it does.
+/// Whether a point is inside a rectangle whose corners, clockwise from the top-left,
+/// are quarter circles of `radii`.
+fn in_rounded_rectangle(
+    (left, top): (f32, f32),
+    (width, height): (f32, f32),
+    radii: [f32; 4],
+    (x, y): (f32, f32),
+) -> bool {
+    let (right, bottom) = (left + width, top + height);
+    if x < left || x > right || y < top || y > bottom {
+        return false;
+    }
+    let corners = [
+        (left + radii[0], top + radii[0], radii[0]),
+        (right - radii[1], top + radii[1], radii[1]),
+        (right - radii[2], bottom - radii[2], radii[2]),
+        (left + radii[3], bottom - radii[3], radii[3]),
+    ];
+    corners.into_iter().enumerate().all(|(i, (cx, cy, r))| {
+        let beyond_x = if i == 0 || i == 3 { x < cx } else { x > cx };
+        let beyond_y = if i < 2 { y < cy } else { y > cy };
+        !(beyond_x && beyond_y) || (x - cx).hypot(y - cy) <= r
+    })
+}
+
+fn check_shapes(renderer: &mut dyn Renderer) {
+    let ellipse = EllipseData::new_shape((8.0, 8.0), (6.0, 3.0), COLOUR);
+    assert_fills(
+        renderer,
+        &ellipse,
+        |x, y| ((x - 8.0) / 6.0).powi(2) + ((y - 8.0) / 3.0).powi(2) <= 1.0,
+        "the ellipse",
+    );
+
+    // Each corner has its own radius, and ones longer than half the shorter side are clamped.
+    let radii = [4.0, 0.0, 2.0, 10.0];
+    let rounded = RoundedRectangleData::new_shape((2.0, 2.0), (12.0, 8.0), radii, COLOUR);
+    assert_fills(
+        renderer,
+        &rounded,
+        |x, y| in_rounded_rectangle((2.0, 2.0), (12.0, 8.0), [4.0, 0.0, 2.0, 4.0], (x, y)),
+        "the rounded rectangle",
+    );
+    renderer.set_antialiasing(Antialiasing::None);
+    let frame = renderer.render_frame(&[rounded]);
+    assert_eq!(pixel(&frame, SIZE, 2, 2), 0);
+    assert_eq!(pixel(&frame, SIZE, 13, 2), COLOUR.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 13, 9), 0);
+    assert_eq!(pixel(&frame, SIZE, 12, 9), COLOUR.to_packed());
+
+    // Fully rounded squares are circles.
+    let pill = RoundedRectangleData::new_shape((4.0, 4.0), (8.0, 8.0), [100.0; 4], COLOUR);
+    assert_fills(
+        renderer,
+        &pill,
+        |x, y| (x - 8.0).hypot(y - 8.0) <= 4.0,
+        "the pill",
+    );
+
+    // Without radii, rounded rectangles are plain ones.
+    let square = RoundedRectangleData::new_shape((3.0, 5.0), (6.0, 4.0), [0.0; 4], COLOUR);
+    assert_fills(
+        renderer,
+        &square,
+        |x, y| (3.0..=9.0).contains(&x) && (5.0..=9.0).contains(&y),
+        "the square",
+    );
+
+    // Smoothed edges keep both shapes symmetric about their centres.
+    for antialiasing in [Antialiasing::Analytic, Antialiasing::Supersample(4)] {
+        renderer.set_antialiasing(antialiasing);
+        for shape in [
+            EllipseData::new_shape((8.0, 8.0), (6.5, 3.25), COLOUR),
+            RoundedRectangleData::new_shape((1.5, 3.5), (13.0, 9.0), [3.5; 4], COLOUR),
+        ] {
+            let frame = renderer.render_frame(&[shape]);
+            for y in 0..SIZE {
+                for x in 0..SIZE {
+                    let alpha = pixel(&frame, SIZE, x, y) >> 24;
+                    let mirrored = [(SIZE - 1 - x, y), (x, SIZE - 1 - y)];
+                    for (mx, my) in mirrored {
+                        let other = pixel(&frame, SIZE, mx, my) >> 24;
+                        assert!(
+                            alpha.abs_diff(other) <= 1,
+                            "{antialiasing:?}: ({x}, {y}) is {alpha}, ({mx}, {my}) is {other}"
+                        );
+                    }
+                }
+            }
+            assert!(
+                (0..SIZE).any(|x| (1..255).contains(&(pixel(&frame, SIZE, x, 8) >> 24))),
+                "{antialiasing:?} has no partly covered edge"
+            );
+        }
+    }
+}
+
+#[test]
+fn the_cpu_draws_ellipses_and_rounded_corners() {
+    check_shapes(&mut CpuRenderer::new(SIZE, SIZE));
+}
+
+#[test]
+fn the_gpu_draws_ellipses_and_rounded_corners() {
+    let Some(mut renderer) = gpu_renderer(SIZE, SIZE) else {
+        return;
+    };
+    check_shapes(&mut renderer);
+}
+
+#[test]
+fn shapes_without_area_are_culled() {
+    let shapes = [
+        EllipseData::new_shape((8.0, 8.0), (0.0, 3.0), COLOUR),
+        EllipseData::new_shape((8.0, 8.0), (3.0, -1.0), COLOUR),
+        RoundedRectangleData::new_shape((2.0, 2.0), (0.0, 8.0), [1.0; 4], COLOUR),
+        RoundedRectangleData::new_shape((2.0, 2.0), (8.0, -8.0), [1.0; 4], COLOUR),
+        EllipseData::new_shape((-8.0, 8.0), (4.0, 4.0), COLOUR),
+    ];
+    let records = FrameRecords::new(&shapes, Antialiasing::Analytic, SIZE, SIZE);
+    assert!(records.shapes.is_empty(), "{:?}", records.shapes);
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use common::{assert_fills, gpu_renderer, pixel, COLOUR};
+use video_generator_lib::{
+    color::Color,
+    node::{Circle, Rectangle},
+    renderer::{Antialiasing, CpuRenderer, Renderer},
+    shapes::*,
+    signal::Signal,
+};
+
+const SIZE: u32 = 16;
+const ALIGNMENTS: [StrokeAlignment; 3] = [
+    StrokeAlignment::Inside,
+    StrokeAlignment::Centre,
+    StrokeAlignment::Outside,
+];
+
+fn stroke(width: f32, alignment: StrokeAlignment) -> Option<Stroke> {
+    Some(Stroke::new(width, COLOUR).with_alignment(alignment))
+}
+
+/// Strokes only, so the pixels they cover are all that's drawn.
+fn check_alignment(renderer: &mut dyn Renderer) {
+    for alignment in ALIGNMENTS {
+        let what = format!("{alignment:?}");
+        let (radius, width) = (3.5, 1.5);
+        let (inner, outer) = stroke(width, alignment).unwrap().edges(radius);
+        // Hard-edged circles are centred half a pixel in from their bounding box, here on (8, 8).
+        let circle = Shape::Circle(
+            CircleData::new((8.2, 7.9), radius, Color::TRANSPARENT)
+                .with_stroke(stroke(width, alignment)),
+        );
+        assert_fills(
+            renderer,
+            &circle,
+            |x, y| (inner..=outer).contains(&(x - 8.0).hypot(y - 8.0)),
+            &format!("the circle's {what} stroke"),
+        );
+
+        // The rectangle's edges lie on 4 and 12, so its stroke's edges fall between pixel centres.
+        let (inner, outer) = stroke(2.0, alignment).unwrap().edges(0.0);
+        let rectangle = Shape::Rectangle(
+            RectangleData::new((4.0, 4.0), (8.0, 8.0), Color::TRANSPARENT)
+                .with_stroke(stroke(2.0, alignment)),
+        );
+        let within = |x: f32, y: f32, grow: f32| {
+            (4.0 - grow..=12.0 + grow).contains(&x) && (4.0 - grow..=12.0 + grow).contains(&y)
+        };
+        assert_fills(
+            renderer,
+            &rectangle,
+            |x, y| within(x, y, outer) && !within(x, y, inner),
+            &format!("the rectangle's {what} stroke"),
+        );
+    }
+
+    // Strokes are drawn over the fill, which still reaches the shape's edge.
+    renderer.set_antialiasing(Antialiasing::None);
+    let red = Color::rgb(255, 0, 0);
+    let frame = renderer.render_frame(&[Shape::Rectangle(
+        RectangleData::new((4.0, 4.0), (8.0, 8.0), red)
+            .with_stroke(stroke(2.0, StrokeAlignment::Outside)),
+    )]);
+    assert_eq!(pixel(&frame, SIZE, 4, 8), red.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 3, 8), COLOUR.to_packed());
+    let frame = renderer.render_frame(&[Shape::Rectangle(
+        RectangleData::new((4.0, 4.0), (8.0, 8.0), red)
+            .with_stroke(stroke(2.0, StrokeAlignment::Inside)),
+    )]);
+    assert_eq!(pixel(&frame, SIZE, 5, 8), COLOUR.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 6, 8), red.to_packed());
+    assert_eq!(pixel(&frame, SIZE, 3, 8), 0);
+}
+
+#[test]
+fn the_cpu_aligns_strokes() {
+    check_alignment(&mut CpuRenderer::new(SIZE, SIZE));
+}
+
+#[test]
+fn the_gpu_aligns_strokes() {
+    let Some(mut renderer) = gpu_renderer(SIZE, SIZE) else {
+        return;
+    };
+    check_alignment(&mut renderer);
+}
+
+#[test]
+fn stroke_nodes_follow_signals() {
+    let width = Signal::new(0.0f32);
+    let colour = Signal::new(COLOUR);
+    let mut circle = Circle::new(|| 8.0, || 8.0, || 4.0, || Color::WHITE);
+    circle
+        .set_stroke_width(|| width.get())
+        .set_stroke_colour(|| colour.get())
+        .set_stroke_alignment(StrokeAlignment::Outside);
+    let mut rectangle = Rectangle::new(|| 2.0, || 2.0, || 6.0, || 4.0, || Color::WHITE);
+    rectangle
+        .set_stroke_width(|| width.get() * 2.0)
+        .set_stroke_colour(|| 0xFF0000FFu32)
+        .set_stroke_alignment(StrokeAlignment::Inside);
+    let strokes =
+        |circle: &Circle, rectangle: &Rectangle| match (circle.to_shape(), rectangle.to_shape()) {
+            (Shape::Circle(circle), Shape::Rectangle(rectangle)) => {
+                (circle.stroke, rectangle.stroke)
+            }
+            _ => unreachable!(),
+        };
+
+    // Outlines without width aren't drawn.
+    assert_eq!(strokes(&circle, &rectangle), (None, None));
+
+    width.update(|x| *x = 1.5);
+    assert_eq!(
+        strokes(&circle, &rectangle),
+        (
+            stroke(1.5, StrokeAlignment::Outside),
+            Some(Stroke::new(3.0, Color::rgb(0, 0, 255)).with_alignment(StrokeAlignment::Inside)),
+        )
+    );
+
+    colour.update(|x| *x = Color::rgb(255, 0, 0));
+    let (circle_stroke, _) = strokes(&circle, &rectangle);
+    assert_eq!(circle_stroke.unwrap().colour, Color::rgb(255, 0, 0));
+}
@@ -0,0 +1,310 @@
+use video_generator_lib::{
+    color::Color,
+    shapes::{ParsePathError, PathData, PathSegment},
+};
+
+use PathSegment::{Close, CubicTo, LineTo, MoveTo, QuadTo};
+
+fn parse(data: &str) -> Result<Vec<PathSegment>, ParsePathError> {
+    PathData::parse(data, Color::BLACK).map(|x| x.segments)
+}
+
+fn segments(data: &str) -> Vec<PathSegment> {
+    parse(data).unwrap_or_else(|error| panic!("{data:?}: {error}"))
+}
+
+fn assert_close(actual: (f32, f32), expected: (f32, f32), what: &str) {
+    assert!(
+        (actual.0 - expected.0).abs() < 1e-3 && (actual.1 - expected.1).abs() < 1e-3,
+        "{what}: {actual:?}, expected {expected:?}"
+    );
+}
+
+fn end((i, segment): (usize, &PathSegment)) -> (f32, f32) {
+    match *segment {
+        MoveTo(x) | LineTo(x) | QuadTo(_, x) | CubicTo(_, _, x) => x,
+        Close => panic!("segment {i} is a close"),
+    }
+}
+
+#[test]
+fn absolute_and_relative_commands_agree() {
+    let pairs = [
+        ("M 10 20 L 30 40", "m 10 20 l 20 20"),
+        ("M 10 20 H 30", "m 10 20 h 20"),
+        ("M 10 20 V 30", "m 10 20 v 10"),
+        ("M 10 20 C 11 21 12 22 13 23", "m 10 20 c 1 1 2 2 3 3"),
+        ("M 10 20 Q 11 21 12 22", "m 10 20 q 1 1 2 2"),
+        (
+            "M 10 20 C 0 0 20 20 30 30 S 50 50 60 60",
+            "m 10 20 c -10 -20 10 0 20 10 s 20 20 30 30",
+        ),
+        (
+            "M 10 20 Q 0 0 30 30 T 60 60",
+            "m 10 20 q -10 -20 20 10 t 30 30",
+        ),
+        (
+            "M 10 20 L 30 40 Z M 50 60 L 70 80",
+            "m 10 20 l 20 20 z m 40 40 l 20 20",
+        ),
+        ("M 0 0 A 5 5 0 0 1 10 0", "m 0 0 a 5 5 0 0 1 10 0"),
+    ];
+    for (absolute, relative) in pairs {
+        assert_eq!(segments(absolute), segments(relative), "{relative:?}");
+    }
+    assert_eq!(
+        segments("M 10 20 C 11 21 12 22 13 23"),
+        [
+            MoveTo((10.0, 20.0)),
+            CubicTo((11.0, 21.0), (12.0, 22.0), (13.0, 23.0))
+        ]
+    );
+    assert_eq!(
+        segments("M 10 20 Q 11 21 12 22"),
+        [MoveTo((10.0, 20.0)), QuadTo((11.0, 21.0), (12.0, 22.0))]
+    );
+}
+
+#[test]
+fn relative_commands_after_a_close_start_from_the_subpath() {
+    assert_eq!(
+        segments("m 10 10 l 10 0 l 0 10 z l 5 5"),
+        [
+            MoveTo((10.0, 10.0)),
+            LineTo((20.0, 10.0)),
+            LineTo((20.0, 20.0)),
+            Close,
+            LineTo((15.0, 15.0)),
+        ]
+    );
+}
+
+#[test]
+fn commands_repeat_while_numbers_follow() {
+    assert_eq!(
+        segments("M 0 0 L 1 1 2 2 h 1 1 V 5 6"),
+        [
+            MoveTo((0.0, 0.0)),
+            LineTo((1.0, 1.0)),
+            LineTo((2.0, 2.0)),
+            LineTo((3.0, 2.0)),
+            LineTo((4.0, 2.0)),
+            LineTo((4.0, 5.0)),
+            LineTo((4.0, 6.0)),
+        ]
+    );
+    assert_eq!(
+        segments("M0 0 q 1 1 2 0 3 -1 4 0"),
+        [
+            MoveTo((0.0, 0.0)),
+            QuadTo((1.0, 1.0), (2.0, 0.0)),
+            QuadTo((5.0, -1.0), (6.0, 0.0)),
+        ]
+    );
+}
+
+#[test]
+fn coordinates_after_a_move_are_lines() {
+    assert_eq!(
+        segments("M 1 2 3 4 5 6"),
+        [MoveTo((1.0, 2.0)), LineTo((3.0, 4.0)), LineTo((5.0, 6.0))]
+    );
+    assert_eq!(
+        segments("m 1 2 3 4 5 6"),
+        [MoveTo((1.0, 2.0)), LineTo((4.0, 6.0)), LineTo((9.0, 12.0))]
+    );
+    // Only the coordinates straight after the move; a new move starts again.
+    assert_eq!(
+        segments("M 0 0 1 1 M 5 5 6 6"),
+        [
+            MoveTo((0.0, 0.0)),
+            LineTo((1.0, 1.0)),
+            MoveTo((5.0, 5.0)),
+            LineTo((6.0, 6.0)),
+        ]
+    );
+}
+
+#[test]
+fn smooth_curves_reflect_only_a_matching_control_point() {
+    // S reflects the second control point of a previous C or S about the current point.
+    assert_eq!(
+        segments("M 0 0 C 0 10 10 10 10 0 S 20 -10 20 0 S 30 10 30 0")[2..],
+        [
+            CubicTo((10.0, -10.0), (20.0, -10.0), (20.0, 0.0)),
+            CubicTo((20.0, 10.0), (30.0, 10.0), (30.0, 0.0)),
+        ]
+    );
+    // After anything else, S starts with its first control point on the current point.
+    for before in ["L 10 0", "Q 5 5 10 0", "T 10 0", "H 10", "A 5 5 0 0 1 10 0"] {
+        let path = segments(&format!("M 0 0 {before} S 20 10 20 0"));
+        assert_eq!(
+            path.last(),
+            Some(&CubicTo((10.0, 0.0), (20.0, 10.0), (20.0, 0.0))),
+            "{before}"
+        );
+    }
+
+    // T reflects the control point of a previous Q or T, including one T made itself.
+    assert_eq!(
+        segments("M 0 0 Q 5 10 10 0 T 20 0 T 30 0")[2..],
+        [
+            QuadTo((15.0, -10.0), (20.0, 0.0)),
+            QuadTo((25.0, 10.0), (30.0, 0.0)),
+        ]
+    );
+    for before in ["L 10 0", "C 0 5 10 5 10 0", "S 5 5 10 0", "V 0 H 10"] {
+        let path = segments(&format!("M 0 0 {before} T 20 0"));
+        assert_eq!(
+            path.last(),
+            Some(&QuadTo((10.0, 0.0), (20.0, 0.0))),
+            "{before}"
+        );
+    }
+}
+
+#[test]
+fn arc_flags_need_no_separators() {
+    let spaced = segments("M 0 0 a 1 1 0 0 0 1 1");
+    assert_eq!(segments("M0 0a1 1 0 00 1 1"), spaced);
+    assert_eq!(segments("M0 0a1,1,0,0,0,1,1"), spaced);
+    assert_eq!(segments("M0 0a1 1 0 001 1"), spaced);
+    assert_eq!(segments("M0 0a1 1 0 1 1 1 1"), segments("M0 0a1 1 0 111 1"));
+}
+
+#[test]
+fn arcs_follow_the_ellipse_they_describe() {
+    // A quarter of a circle of radius 10 around (0, 10), clockwise on the screen.
+    let path = segments("M 0 0 A 10 10 0 0 1 10 10");
+    assert_eq!(path.len(), 2);
+    let CubicTo(first, second, last) = path[1] else {
+        panic!("{path:?}")
+    };
+    let handle = 10.0 * 4.0 / 3.0 * (std::f32::consts::PI / 8.0).tan();
+    assert_close(first, (handle, 0.0), "first control point");
+    assert_close(second, (10.0, 10.0 - handle), "second control point");
+    assert_eq!(last, (10.0, 10.0));
+
+    // The large arc the other way goes three quarters of the way round, in three curves.
+    let path = segments("M 0 0 A 10 10 0 1 0 10 10");
+    assert_eq!(path.len(), 4);
+    for (i, point) in path.iter().enumerate().skip(1).map(|x| (x.0, end(x))) {
+        let (dx, dy) = (point.0 - 0.0, point.1 - 10.0);
+        assert!(
+            (dx.hypot(dy) - 10.0).abs() < 1e-3,
+            "segment {i} ends at {point:?}"
+        );
+    }
+}
+
+#[test]
+fn arc_radii_too_small_are_scaled_up() {
+    // Radii of 1 can't span the 10 pixels between the ends, so they're scaled to 5,
+    // giving half a circle around (5, 0) in two quarter turns.
+    for radii in ["1 1", "2 2", "5 5"] {
+        let path = segments(&format!("M 0 0 A {radii} 0 0 1 10 0"));
+        assert_eq!(path.len(), 3, "{radii}");
+        assert_close(end((1, &path[1])), (5.0, -5.0), radii);
+        assert_eq!(end((2, &path[2])), (10.0, 0.0));
+    }
+    // Elliptical radii keep their proportions.
+    let path = segments("M 0 0 A 2 1 0 0 1 20 0");
+    assert_close(end((1, &path[1])), (10.0, -5.0), "elliptical");
+
+    // Zero radii make a straight line, and an arc to where it starts draws nothing.
+    assert_eq!(segments("M 0 0 A 0 5 0 0 1 10 0")[1], LineTo((10.0, 0.0)));
+    assert_eq!(segments("M 0 0 A 5 5 0 0 1 0 0"), [MoveTo((0.0, 0.0))]);
+}
+
+#[test]
+fn numbers_run_together_where_unambiguous() {
+    let cases = [
+        ("M1e-3 2E2", (0.001, 200.0)),
+        ("M.5.5", (0.5, 0.5)),
+        ("M-1-2", (-1.0, -2.0)),
+        ("M+1+2", (1.0, 2.0)),
+        ("M1.5-.5", (1.5, -0.5)),
+        ("M1.e1 2", (10.0, 2.0)),
+        ("M 1 , 2", (1.0, 2.0)),
+        ("M\t1\n2\r", (1.0, 2.0)),
+    ];
+    for (data, point) in cases {
+        assert_eq!(segments(data), [MoveTo(point)], "{data:?}");
+    }
+    // An `e` without digits after it isn't an exponent, so the number ends before it.
+    assert_eq!(
+        parse("M1e 2"),
+        Err(ParsePathError::ExpectedNumber { position: 2 })
+    );
+    assert_eq!(
+        parse("M1 2e"),
+        Err(ParsePathError::UnexpectedCharacter {
+            position: 4,
+            character: 'e'
+        })
+    );
+    assert_eq!(segments(""), []);
+}
+
+#[test]
+fn errors_give_the_byte_they_were_found_at() {
+    let cases = [
+        ("L 1 2", ParsePathError::ExpectedMoveTo { position: 0 }),
+        ("  h 1", ParsePathError::ExpectedMoveTo { position: 2 }),
+        (
+            "M 10 10 L 5",
+            ParsePathError::ExpectedNumber { position: 11 },
+        ),
+        (
+            "M 10 10 L 5 .",
+            ParsePathError::ExpectedNumber { position: 12 },
+        ),
+        (
+            "M 1 2 C 1 2 3 4 -",
+            ParsePathError::ExpectedNumber { position: 16 },
+        ),
+        (
+            "M 0 0 A 1 1 0 2 1 1 1",
+            ParsePathError::ExpectedFlag { position: 14 },
+        ),
+        (
+            "M 0 0 a1 1 0 0",
+            ParsePathError::ExpectedFlag { position: 14 },
+        ),
+        (
+            "M 1 2 X 3",
+            ParsePathError::UnexpectedCharacter {
+                position: 6,
+                character: 'X',
+            },
+        ),
+        (
+            "M 1 2 Z 3",
+            ParsePathError::UnexpectedCharacter {
+                position: 8,
+                character: '3',
+            },
+        ),
+        (
+            "M 1 2 é",
+            ParsePathError::UnexpectedCharacter {
+                position: 6,
+                character: 'é',
+            },
+        ),
+        (
+            "5 5",
+            ParsePathError::UnexpectedCharacter {
+                position: 0,
+                character: '5',
+            },
+        ),
+    ];
+    for (data, error) in cases {
+        assert_eq!(parse(data), Err(error.clone()), "{data:?}");
+    }
+    assert_eq!(
+        ParsePathError::ExpectedFlag { position: 14 }.to_string(),
+        "expected an arc flag of 0 or 1 at 14"
+    );
+}
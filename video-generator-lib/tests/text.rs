@@ -0,0 +1,78 @@
+use video_generator_lib::{
+    color::Color,
+    renderer::{CpuRenderer, Renderer},
+    shapes::*,
+};
+
+const SIZE: u32 = 160;
+const COLOUR: Color = Color::rgb(255, 255, 255);
+
+/// DejaVu Sans has 2048 units per em and capitals 1493 units tall.
+const CAP_HEIGHT: f32 = 1493.0 / 2048.0;
+
+fn font() -> Font {
+    Font::open(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fonts/DejaVuSans.ttf"
+    ))
+    .unwrap()
+}
+
+/// The number of rows with a pixel at least half covered.
+fn inked_rows(frame: &[u8]) -> u32 {
+    frame
+        .chunks_exact(SIZE as usize * 4)
+        .filter(|row| row.chunks_exact(4).any(|x| x[3] >= 128))
+        .count() as u32
+}
+
+#[test]
+fn the_em_square_is_size_pixels() {
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    // 100 is rasterized as it is, the others a little larger and scaled down.
+    for size in [100.0, 99.5, 123.4] {
+        let frame =
+            renderer.render_frame(&[TextData::new_shape("H", font(), size, (10.0, 10.0), COLOUR)]);
+        let expected = size * CAP_HEIGHT;
+        let rows = inked_rows(&frame);
+        assert!(
+            (rows as f32 - expected).abs() <= 1.0,
+            "{rows} rows tall at size {size}, expected {expected}"
+        );
+    }
+}
+
+#[test]
+fn animated_sizes_share_glyphs() {
+    let font = font();
+    let regions =
+        |size: f32| TextData::new("H", font.clone(), size, (0.0, 0.0), COLOUR).glyphs()[0].region;
+    let first = regions(40.0);
+    for i in 1..16 {
+        assert_eq!(
+            regions(40.0 - i as f32 * 0.1),
+            first,
+            "size {}",
+            40.0 - i as f32 * 0.1
+        );
+    }
+}
+
+/// Each size is rasterized again, and a dozen glyphs this big fill the atlas.
+#[test]
+fn growing_text_does_not_outgrow_the_atlas() {
+    let font = font();
+    let mut renderer = CpuRenderer::new(SIZE, SIZE);
+    for i in 0..20 {
+        let size = 1500.0 + i as f32 * 64.0;
+        let text = TextData::new("H", font.clone(), size, (0.0, 0.0), COLOUR);
+        for glyph in text.glyphs() {
+            let (width, height) = (glyph.source.width(), glyph.source.height());
+            assert!(
+                width <= 4096 && height <= 4096,
+                "{width}x{height} at size {size}"
+            );
+        }
+        renderer.render_frame(&[Shape::Text(text)]);
+    }
+}